@@ -1,4 +1,4 @@
-use rustc_middle::mir::coverage::{CounterId, ExpressionId, Operand};
+use rustc_middle::mir::coverage::{ConditionId, CounterId, ExpressionId, Operand};
 
 /// Must match the layout of `LLVMRustCounterKind`.
 #[derive(Copy, Clone, Debug)]
@@ -110,6 +110,32 @@ pub enum RegionKind {
     /// associated with two counters, each representing the number of times the
     /// expression evaluates to true or false.
     BranchRegion = 4,
+
+    /// A DecisionRegion represents a top-level boolean expression and is
+    /// associated with a variable length bitmap index and condition number.
+    MCDCDecisionRegion = 5,
+
+    /// A Branch Region can be extended to include IDs to facilitate MC/DC.
+    MCDCBranchRegion = 6,
+}
+
+/// The MC/DC parameters of a decision or branch region. Decision regions use `bitmap_idx` and
+/// `num_conditions`, and branch regions use the condition IDs; unused fields are zero.
+///
+/// Corresponds to struct `llvm::coverage::CounterMappingRegion::MCDCParameters`.
+///
+/// Must match the layout of `LLVMRustMCDCParameters`.
+#[derive(Copy, Clone, Debug, Default)]
+#[repr(C)]
+pub struct McdcParameters {
+    /// Index of the first byte of the decision's test vectors in the function's bitmap.
+    bitmap_idx: u32,
+    num_conditions: u32,
+    /// ID of the condition of a branch region, and of the conditions evaluated next when it is
+    /// true or false (zero if that outcome decides the decision).
+    id: u32,
+    true_id: u32,
+    false_id: u32,
 }
 
 /// This struct provides LLVM's representation of a "CoverageMappingRegion", encoded into the
@@ -133,6 +159,10 @@ pub struct CounterMappingRegion {
     /// for the false branch of the region.
     false_counter: Counter,
 
+    /// If the `RegionKind` is an `MCDCDecisionRegion` or an `MCDCBranchRegion`, these are its
+    /// MC/DC parameters.
+    mcdc_params: McdcParameters,
+
     /// An indirect reference to the source filename. In the LLVM Coverage Mapping Format, the
     /// file_id is an index into a function-specific `virtual_file_mapping` array of indexes
     /// that, in turn, are used to look up the filename for this region.
@@ -171,6 +201,7 @@ pub(crate) fn code_region(
         Self {
             counter,
             false_counter: Counter::ZERO,
+            mcdc_params: McdcParameters::default(),
             file_id,
             expanded_file_id: 0,
             start_line,
@@ -181,9 +212,6 @@ pub(crate) fn code_region(
         }
     }
 
-    // This function might be used in the future; the LLVM API is still evolving, as is coverage
-    // support.
-    #[allow(dead_code)]
     pub(crate) fn branch_region(
         counter: Counter,
         false_counter: Counter,
@@ -196,6 +224,7 @@ pub(crate) fn branch_region(
         Self {
             counter,
             false_counter,
+            mcdc_params: McdcParameters::default(),
             file_id,
             expanded_file_id: 0,
             start_line,
@@ -206,6 +235,64 @@ pub(crate) fn branch_region(
         }
     }
 
+    pub(crate) fn mcdc_decision_region(
+        bitmap_idx: u32,
+        num_conditions: u16,
+        file_id: u32,
+        start_line: u32,
+        start_col: u32,
+        end_line: u32,
+        end_col: u32,
+    ) -> Self {
+        Self {
+            counter: Counter::ZERO,
+            false_counter: Counter::ZERO,
+            mcdc_params: McdcParameters {
+                bitmap_idx,
+                num_conditions: u32::from(num_conditions),
+                ..McdcParameters::default()
+            },
+            file_id,
+            expanded_file_id: 0,
+            start_line,
+            start_col,
+            end_line,
+            end_col,
+            kind: RegionKind::MCDCDecisionRegion,
+        }
+    }
+
+    pub(crate) fn mcdc_branch_region(
+        counter: Counter,
+        false_counter: Counter,
+        id: ConditionId,
+        true_next_id: ConditionId,
+        false_next_id: ConditionId,
+        file_id: u32,
+        start_line: u32,
+        start_col: u32,
+        end_line: u32,
+        end_col: u32,
+    ) -> Self {
+        Self {
+            counter,
+            false_counter,
+            mcdc_params: McdcParameters {
+                id: id.as_u32(),
+                true_id: true_next_id.as_u32(),
+                false_id: false_next_id.as_u32(),
+                ..McdcParameters::default()
+            },
+            file_id,
+            expanded_file_id: 0,
+            start_line,
+            start_col,
+            end_line,
+            end_col,
+            kind: RegionKind::MCDCBranchRegion,
+        }
+    }
+
     // This function might be used in the future; the LLVM API is still evolving, as is coverage
     // support.
     #[allow(dead_code)]
@@ -220,6 +307,7 @@ pub(crate) fn expansion_region(
         Self {
             counter: Counter::ZERO,
             false_counter: Counter::ZERO,
+            mcdc_params: McdcParameters::default(),
             file_id,
             expanded_file_id,
             start_line,
@@ -243,6 +331,7 @@ pub(crate) fn skipped_region(
         Self {
             counter: Counter::ZERO,
             false_counter: Counter::ZERO,
+            mcdc_params: McdcParameters::default(),
             file_id,
             expanded_file_id: 0,
             start_line,
@@ -267,6 +356,7 @@ pub(crate) fn gap_region(
         Self {
             counter,
             false_counter: Counter::ZERO,
+            mcdc_params: McdcParameters::default(),
             file_id,
             expanded_file_id: 0,
             start_line,
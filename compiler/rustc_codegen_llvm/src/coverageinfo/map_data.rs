@@ -2,10 +2,43 @@
 
 use rustc_data_structures::fx::FxIndexSet;
 use rustc_index::IndexVec;
-use rustc_middle::mir::coverage::{CodeRegion, CounterId, ExpressionId, Op, Operand};
+use rustc_middle::mir::coverage::{CodeRegion, ConditionId, CounterId, ExpressionId, Op, Operand};
 use rustc_middle::ty::Instance;
 use rustc_middle::ty::TyCtxt;
 
+/// The counters associated with a region of the coverage map: a single counter for a code
+/// region, or the counters of both outcomes for a branch region. MC/DC regions also carry the
+/// parameters that tie their decisions and conditions together.
+#[derive(Clone, Copy, Debug)]
+pub enum RegionCounters {
+    Code(Counter),
+    Branch {
+        true_counter: Counter,
+        false_counter: Counter,
+    },
+    McdcDecision {
+        bitmap_idx: u32,
+        num_conditions: u16,
+    },
+    McdcBranch {
+        true_counter: Counter,
+        false_counter: Counter,
+        id: ConditionId,
+        true_next_id: ConditionId,
+        false_next_id: ConditionId,
+    },
+}
+
+/// A condition of an MC/DC decision, as described by a `CoverageKind::MCDCBranch`.
+#[derive(Clone, Copy, Debug)]
+pub struct McdcBranch {
+    pub true_term: Operand,
+    pub false_term: Operand,
+    pub id: ConditionId,
+    pub true_next_id: ConditionId,
+    pub false_next_id: ConditionId,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Expression {
     lhs: Operand,
@@ -15,9 +48,10 @@ pub struct Expression {
 }
 
 /// Collects all of the coverage regions associated with (a) injected counters, (b) counter
-/// expressions (additions or subtraction), and (c) unreachable regions (always counted as zero),
-/// for a given Function. This struct also stores the `function_source_hash`,
-/// computed during instrumentation, and forwarded with counters.
+/// expressions (additions or subtraction), (c) unreachable regions (always counted as zero), (d)
+/// branches (counting both outcomes), and (e) MC/DC decisions and their conditions, for a given
+/// Function. This struct also stores the `function_source_hash`, computed during instrumentation,
+/// and forwarded with counters.
 ///
 /// Note, it may be important to understand LLVM's definitions of `unreachable` regions versus "gap
 /// regions" (or "gap areas"). A gap region is a code region within a counted region (either counter
@@ -33,6 +67,10 @@ pub struct FunctionCoverage<'tcx> {
     counters: IndexVec<CounterId, Option<Vec<CodeRegion>>>,
     expressions: IndexVec<ExpressionId, Option<Expression>>,
     unreachable_regions: Vec<CodeRegion>,
+    branch_regions: Vec<(Operand, Operand, CodeRegion)>,
+    mcdc_bitmap_bytes: u32,
+    mcdc_decision_regions: Vec<(u32, u16, CodeRegion)>,
+    mcdc_branch_regions: Vec<(McdcBranch, CodeRegion)>,
 }
 
 impl<'tcx> FunctionCoverage<'tcx> {
@@ -59,6 +97,10 @@ fn create(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>, is_used: bool) -> Self {
             counters: IndexVec::from_elem_n(None, coverageinfo.num_counters as usize),
             expressions: IndexVec::from_elem_n(None, coverageinfo.num_expressions as usize),
             unreachable_regions: Vec::new(),
+            branch_regions: Vec::new(),
+            mcdc_bitmap_bytes: 0,
+            mcdc_decision_regions: Vec::new(),
+            mcdc_branch_regions: Vec::new(),
         }
     }
 
@@ -77,6 +119,15 @@ pub fn set_function_source_hash(&mut self, source_hash: u64) {
         }
     }
 
+    /// Sets the size of the MC/DC test vector bitmap of the function, in bytes.
+    pub fn set_mcdc_bitmap_bytes(&mut self, bitmap_bytes: u32) {
+        self.mcdc_bitmap_bytes = bitmap_bytes;
+    }
+
+    pub fn mcdc_bitmap_bytes(&self) -> u32 {
+        self.mcdc_bitmap_bytes
+    }
+
     /// Adds code regions to be counted by an injected counter intrinsic.
     #[instrument(level = "debug", skip(self))]
     pub(crate) fn add_counter(&mut self, id: CounterId, code_regions: &[CodeRegion]) {
@@ -140,6 +191,47 @@ pub(crate) fn add_unreachable_regions(&mut self, code_regions: &[CodeRegion]) {
         self.unreachable_regions.extend_from_slice(code_regions);
     }
 
+    /// Adds branch regions, counting how many times the branch was taken (`true_term`) and how
+    /// many times it was not (`false_term`).
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) fn add_branch_regions(
+        &mut self,
+        true_term: Operand,
+        false_term: Operand,
+        code_regions: &[CodeRegion],
+    ) {
+        self.branch_regions.extend(
+            code_regions.iter().map(|code_region| (true_term, false_term, code_region.clone())),
+        );
+    }
+
+    /// Adds the decision regions of an MC/DC decision, whose test vectors are recorded in the
+    /// function's bitmap starting at byte `bitmap_idx`.
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) fn add_mcdc_decision_regions(
+        &mut self,
+        bitmap_idx: u32,
+        num_conditions: u16,
+        code_regions: &[CodeRegion],
+    ) {
+        self.mcdc_decision_regions.extend(
+            code_regions
+                .iter()
+                .map(|code_region| (bitmap_idx, num_conditions, code_region.clone())),
+        );
+    }
+
+    /// Adds the branch regions of a condition of an MC/DC decision.
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) fn add_mcdc_branch_regions(
+        &mut self,
+        branch: McdcBranch,
+        code_regions: &[CodeRegion],
+    ) {
+        self.mcdc_branch_regions
+            .extend(code_regions.iter().map(|code_region| (branch, code_region.clone())));
+    }
+
     /// Perform some simplifications to make the final coverage mappings
     /// slightly smaller.
     ///
@@ -190,6 +282,24 @@ pub(crate) fn simplify_expressions(&mut self) {
                 zero_expressions.insert(id);
             }
         }
+
+        // Branch regions can also refer to expressions that are always zero.
+        let mcdc_branch_terms = self
+            .mcdc_branch_regions
+            .iter_mut()
+            .map(|(branch, _)| (&mut branch.true_term, &mut branch.false_term));
+        let branch_terms = self
+            .branch_regions
+            .iter_mut()
+            .map(|(true_term, false_term, _)| (true_term, false_term))
+            .chain(mcdc_branch_terms);
+        for (true_term, false_term) in branch_terms {
+            for term in [true_term, false_term] {
+                if let Operand::Expression(id) = *term && zero_expressions.contains(&id) {
+                    *term = Operand::Zero;
+                }
+            }
+        }
     }
 
     /// Return the source hash, generated from the HIR node structure, and used to indicate whether
@@ -198,12 +308,12 @@ pub fn source_hash(&self) -> u64 {
         self.source_hash
     }
 
-    /// Generate an array of CounterExpressions, and an iterator over all `Counter`s and their
+    /// Generate an array of CounterExpressions, and an iterator over all `RegionCounters` and their
     /// associated `Regions` (from which the LLVM-specific `CoverageMapGenerator` will create
     /// `CounterMappingRegion`s.
     pub fn get_expressions_and_counter_regions(
         &self,
-    ) -> (Vec<CounterExpression>, impl Iterator<Item = (Counter, &CodeRegion)>) {
+    ) -> (Vec<CounterExpression>, impl Iterator<Item = (RegionCounters, &CodeRegion)>) {
         assert!(
             self.source_hash != 0 || !self.is_used,
             "No counters provided the source_hash for used function: {:?}",
@@ -220,8 +330,12 @@ pub fn get_expressions_and_counter_regions(
         let expression_regions = self.expression_regions();
         let unreachable_regions = self.unreachable_regions();
 
-        let counter_regions =
-            counter_regions.chain(expression_regions.into_iter().chain(unreachable_regions));
+        let counter_regions = counter_regions
+            .chain(expression_regions.into_iter().chain(unreachable_regions))
+            .map(|(counter, region)| (RegionCounters::Code(counter), region))
+            .chain(self.branch_regions())
+            .chain(self.mcdc_decision_regions())
+            .chain(self.mcdc_branch_regions());
         (counter_expressions, counter_regions)
     }
 
@@ -291,4 +405,36 @@ fn expression_regions(&self) -> Vec<(Counter, &CodeRegion)> {
     fn unreachable_regions(&self) -> impl Iterator<Item = (Counter, &CodeRegion)> {
         self.unreachable_regions.iter().map(|region| (Counter::ZERO, region))
     }
+
+    fn branch_regions(&self) -> impl Iterator<Item = (RegionCounters, &CodeRegion)> {
+        self.branch_regions.iter().map(|(true_term, false_term, region)| {
+            let true_counter = Counter::from_operand(*true_term);
+            let false_counter = Counter::from_operand(*false_term);
+            (RegionCounters::Branch { true_counter, false_counter }, region)
+        })
+    }
+
+    fn mcdc_decision_regions(&self) -> impl Iterator<Item = (RegionCounters, &CodeRegion)> {
+        self.mcdc_decision_regions.iter().map(|&(bitmap_idx, num_conditions, ref region)| {
+            (RegionCounters::McdcDecision { bitmap_idx, num_conditions }, region)
+        })
+    }
+
+    fn mcdc_branch_regions(&self) -> impl Iterator<Item = (RegionCounters, &CodeRegion)> {
+        self.mcdc_branch_regions.iter().map(|(branch, region)| {
+            let McdcBranch { true_term, false_term, id, true_next_id, false_next_id } = *branch;
+            let true_counter = Counter::from_operand(true_term);
+            let false_counter = Counter::from_operand(false_term);
+            (
+                RegionCounters::McdcBranch {
+                    true_counter,
+                    false_counter,
+                    id,
+                    true_next_id,
+                    false_next_id,
+                },
+                region,
+            )
+        })
+    }
 }
@@ -1,7 +1,7 @@
 use crate::common::CodegenCx;
 use crate::coverageinfo;
 use crate::coverageinfo::ffi::CounterMappingRegion;
-use crate::coverageinfo::map_data::FunctionCoverage;
+use crate::coverageinfo::map_data::{FunctionCoverage, RegionCounters};
 use crate::llvm;
 
 use rustc_codegen_ssa::traits::ConstMethods;
@@ -32,9 +32,13 @@ pub fn finalize(cx: &CodegenCx<'_, '_>) {
     let tcx = cx.tcx;
 
     // Ensure the installed version of LLVM supports Coverage Map Version 6
-    // (encoded as a zero-based value: 5), which was introduced with LLVM 13.
+    // (encoded as a zero-based value: 5), which was introduced with LLVM 13,
+    // or Version 7 (encoded as 6), which LLVM 18 uses to add MC/DC regions.
     let version = coverageinfo::mapping_version();
-    assert_eq!(version, 5, "The `CoverageMappingVersion` exposed by `llvm-wrapper` is out of sync");
+    assert!(
+        (5..=6).contains(&version),
+        "The `CoverageMappingVersion` exposed by `llvm-wrapper` is out of sync"
+    );
 
     debug!("Generating coverage map for CodegenUnit: `{}`", cx.codegen_unit.name());
 
@@ -187,18 +191,60 @@ fn encode_mappings_for_function(
 
         // For each counter/region pair in this function+file, convert it to a
         // form suitable for FFI.
-        for &(counter, region) in counter_regions_for_file {
+        for &(counters, region) in counter_regions_for_file {
             let CodeRegion { file_name: _, start_line, start_col, end_line, end_col } = *region;
 
-            debug!("Adding counter {counter:?} to map for {region:?}");
-            mapping_regions.push(CounterMappingRegion::code_region(
-                counter,
-                local_file_id,
-                start_line,
-                start_col,
-                end_line,
-                end_col,
-            ));
+            debug!("Adding counters {counters:?} to map for {region:?}");
+            mapping_regions.push(match counters {
+                RegionCounters::Code(counter) => CounterMappingRegion::code_region(
+                    counter,
+                    local_file_id,
+                    start_line,
+                    start_col,
+                    end_line,
+                    end_col,
+                ),
+                RegionCounters::Branch { true_counter, false_counter } => {
+                    CounterMappingRegion::branch_region(
+                        true_counter,
+                        false_counter,
+                        local_file_id,
+                        start_line,
+                        start_col,
+                        end_line,
+                        end_col,
+                    )
+                }
+                RegionCounters::McdcDecision { bitmap_idx, num_conditions } => {
+                    CounterMappingRegion::mcdc_decision_region(
+                        bitmap_idx,
+                        num_conditions,
+                        local_file_id,
+                        start_line,
+                        start_col,
+                        end_line,
+                        end_col,
+                    )
+                }
+                RegionCounters::McdcBranch {
+                    true_counter,
+                    false_counter,
+                    id,
+                    true_next_id,
+                    false_next_id,
+                } => CounterMappingRegion::mcdc_branch_region(
+                    true_counter,
+                    false_counter,
+                    id,
+                    true_next_id,
+                    false_next_id,
+                    local_file_id,
+                    start_line,
+                    start_col,
+                    end_line,
+                    end_col,
+                ),
+            });
         }
     }
 
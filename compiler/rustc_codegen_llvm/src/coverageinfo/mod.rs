@@ -4,7 +4,7 @@
 use crate::builder::Builder;
 use crate::common::CodegenCx;
 use crate::coverageinfo::ffi::{CounterExpression, CounterMappingRegion};
-use crate::coverageinfo::map_data::FunctionCoverage;
+use crate::coverageinfo::map_data::{FunctionCoverage, McdcBranch};
 
 use libc::c_uint;
 use rustc_codegen_ssa::traits::{
@@ -39,6 +39,9 @@ pub struct CrateCoverageContext<'ll, 'tcx> {
     /// Coverage data for each instrumented function identified by DefId.
     pub(crate) function_coverage_map: RefCell<FxHashMap<Instance<'tcx>, FunctionCoverage<'tcx>>>,
     pub(crate) pgo_func_name_var_map: RefCell<FxHashMap<Instance<'tcx>, &'ll llvm::Value>>,
+    /// The MC/DC condition bitmap of each instrumented function: a local variable in which the
+    /// values of the conditions of the decision being evaluated are accumulated.
+    pub(crate) mcdc_condition_bitmap_map: RefCell<FxHashMap<Instance<'tcx>, &'ll llvm::Value>>,
 }
 
 impl<'ll, 'tcx> CrateCoverageContext<'ll, 'tcx> {
@@ -46,6 +49,7 @@ pub fn new() -> Self {
         Self {
             function_coverage_map: Default::default(),
             pgo_func_name_var_map: Default::default(),
+            mcdc_condition_bitmap_map: Default::default(),
         }
     }
 
@@ -136,6 +140,62 @@ fn add_coverage(&mut self, instance: Instance<'tcx>, coverage: &Coverage) {
             CoverageKind::Expression { id, lhs, op, rhs } => {
                 func_coverage.add_counter_expression(id, lhs, op, rhs, code_regions);
             }
+            CoverageKind::Branch { true_term, false_term } => {
+                func_coverage.add_branch_regions(true_term, false_term, code_regions);
+            }
+            CoverageKind::MCDCParameters { function_source_hash, bitmap_bytes } => {
+                func_coverage.set_function_source_hash(function_source_hash);
+                func_coverage.set_mcdc_bitmap_bytes(bitmap_bytes);
+                drop(coverage_map);
+
+                let fn_name = bx.get_pgo_func_name_var(instance);
+                let hash = bx.const_u64(function_source_hash);
+                let bitmap_bytes = bx.const_u32(bitmap_bytes);
+                bx.mcdc_parameters(fn_name, hash, bitmap_bytes);
+
+                // The condition bitmap starts out empty, and is reset after each decision.
+                let cond_bitmap = get_mcdc_condition_bitmap(bx, instance);
+                let zero = bx.const_i32(0);
+                let align = bx.tcx().data_layout.i32_align.abi;
+                bx.store(zero, cond_bitmap, align);
+            }
+            CoverageKind::MCDCDecision { bitmap_idx, num_conditions } => {
+                func_coverage.add_mcdc_decision_regions(bitmap_idx, num_conditions, code_regions);
+            }
+            CoverageKind::MCDCBranch { true_term, false_term, id, true_next_id, false_next_id } => {
+                func_coverage.add_mcdc_branch_regions(
+                    McdcBranch { true_term, false_term, id, true_next_id, false_next_id },
+                    code_regions,
+                );
+            }
+            CoverageKind::CondBitmapUpdate { id } => {
+                let hash = func_coverage.source_hash();
+                drop(coverage_map);
+
+                let fn_name = bx.get_pgo_func_name_var(instance);
+                let hash = bx.const_u64(hash);
+                // LLVM numbers the bits of the condition bitmap from 0, but the conditions from 1.
+                let cond_loc = bx.const_i32(id.as_u32() as i32 - 1);
+                let cond_bitmap = get_mcdc_condition_bitmap(bx, instance);
+                let bool_value = bx.const_bool(true);
+                bx.mcdc_condbitmap_update(fn_name, hash, cond_loc, cond_bitmap, bool_value);
+            }
+            CoverageKind::TestVectorBitmapUpdate { bitmap_idx } => {
+                let hash = func_coverage.source_hash();
+                let bitmap_bytes = func_coverage.mcdc_bitmap_bytes();
+                drop(coverage_map);
+
+                let fn_name = bx.get_pgo_func_name_var(instance);
+                let hash = bx.const_u64(hash);
+                let bitmap_bytes = bx.const_u32(bitmap_bytes);
+                let bitmap_idx = bx.const_u32(bitmap_idx);
+                let cond_bitmap = get_mcdc_condition_bitmap(bx, instance);
+                bx.mcdc_tvbitmap_update(fn_name, hash, bitmap_bytes, bitmap_idx, cond_bitmap);
+
+                let zero = bx.const_i32(0);
+                let align = bx.tcx().data_layout.i32_align.abi;
+                bx.store(zero, cond_bitmap, align);
+            }
             CoverageKind::Unreachable => {
                 func_coverage.add_unreachable_regions(code_regions);
             }
@@ -143,6 +203,27 @@ fn add_coverage(&mut self, instance: Instance<'tcx>, coverage: &Coverage) {
     }
 }
 
+/// Returns the MC/DC condition bitmap of the given instance, allocating it on the stack of the
+/// function being built when the first MC/DC statement of the instance is codegenned.
+fn get_mcdc_condition_bitmap<'ll, 'tcx>(
+    bx: &mut Builder<'_, 'll, 'tcx>,
+    instance: Instance<'tcx>,
+) -> &'ll llvm::Value {
+    // The coverage context is borrowed from the codegen context, so that `bx` is free to build
+    // the allocation.
+    let cx = bx.cx;
+    let Some(coverage_context) = cx.coverage_context() else {
+        bug!("Could not get the `coverage_context`");
+    };
+    if let Some(&cond_bitmap) = coverage_context.mcdc_condition_bitmap_map.borrow().get(&instance) {
+        return cond_bitmap;
+    }
+    let cond_bitmap = bx.alloca(bx.type_i32(), bx.tcx().data_layout.i32_align.abi);
+    llvm::set_value_name(cond_bitmap, b"mcdc.addr");
+    coverage_context.mcdc_condition_bitmap_map.borrow_mut().insert(instance, cond_bitmap);
+    cond_bitmap
+}
+
 fn declare_unused_fn<'tcx>(cx: &CodegenCx<'_, 'tcx>, def_id: DefId) -> Instance<'tcx> {
     let tcx = cx.tcx;
 
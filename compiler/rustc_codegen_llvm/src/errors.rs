@@ -65,6 +65,12 @@ pub(crate) struct InvalidMinimumAlignmentTooLarge {
 #[diag(codegen_llvm_sanitizer_memtag_requires_mte)]
 pub(crate) struct SanitizerMemtagRequiresMte;
 
+#[derive(Diagnostic)]
+#[diag(codegen_llvm_mcdc_requires_llvm_18)]
+pub(crate) struct McdcRequiresLlvm18 {
+    pub llvm_version: String,
+}
+
 #[derive(Diagnostic)]
 #[diag(codegen_llvm_error_writing_def_file)]
 pub(crate) struct ErrorWritingDEFFile {
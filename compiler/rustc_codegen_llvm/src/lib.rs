@@ -355,6 +355,16 @@ fn codegen_crate<'tcx>(
         metadata: EncodedMetadata,
         need_metadata_module: bool,
     ) -> Box<dyn Any> {
+        // The MC/DC intrinsics and coverage mapping regions were added in LLVM 18. This is only
+        // checked here, so that the decisions can still be type-checked and inspected in MIR.
+        let llvm_version = llvm_util::get_version();
+        if tcx.sess.instrument_coverage_mcdc() && llvm_version < (18, 0, 0) {
+            let (major, minor, patch) = llvm_version;
+            tcx.sess.emit_fatal(errors::McdcRequiresLlvm18 {
+                llvm_version: format!("{major}.{minor}.{patch}"),
+            });
+        }
+
         Box::new(rustc_codegen_ssa::base::codegen_crate(
             LlvmCodegenBackend(()),
             tcx,
@@ -193,6 +193,13 @@ codegen_ssa_shuffle_indices_evaluation = could not evaluate shuffle_indices at c
 
 codegen_ssa_specify_libraries_to_link = use the `-l` flag to specify native libraries to link
 
+codegen_ssa_stack_usage_no_frame_sizes = no frame sizes were found for the stack usage report
+    .note = frame sizes are only emitted by the LLVM backend, for ELF targets, when object files are generated
+
+codegen_ssa_stack_usage_read_frame_sizes = failed to read the frame sizes of `{$path}`: {$error}
+
+codegen_ssa_stack_usage_write_report = failed to write the stack usage report to `{$path}`: {$error}
+
 codegen_ssa_static_library_native_artifacts = Link against the following native artifacts when linking against this static library. The order and any duplication can be significant on some platforms.
 
 codegen_ssa_static_library_native_artifacts_to_file = Native artifacts to link against have been written to {$path}. The order and any duplication can be significant on some platforms.
@@ -31,6 +31,7 @@
 use super::linker::{self, Linker};
 use super::metadata::{create_wrapper_file, MetadataPosition};
 use super::rpath::{self, RPathConfig};
+use super::stack_usage;
 use crate::{
     errors, looks_like_rust_object_file, CodegenResults, CompiledModule, CrateInfo, NativeLib,
 };
@@ -67,6 +68,11 @@ pub fn link_binary<'a>(
     outputs: &OutputFilenames,
 ) -> Result<(), ErrorGuaranteed> {
     let _timer = sess.timer("link_binary");
+    if let Some(call_graph) = &codegen_results.crate_info.stack_usage_call_graph {
+        sess.time("stack_usage_report", || {
+            stack_usage::write_report(sess, call_graph, codegen_results)
+        });
+    }
     let output_metadata = sess.opts.output_types.contains_key(&OutputType::Metadata);
     let mut tempfiles_for_stdout_output: Vec<PathBuf> = Vec::new();
     for &crate_type in &codegen_results.crate_info.crate_types {
@@ -0,0 +1,565 @@
+//! Worst-case stack usage analysis, for `-Z stack-usage-report`.
+//!
+//! The call graph between the functions of the crate is built from their MIR when codegen starts,
+//! and kept in the `CrateInfo` until the object files have been written. The frame size of each
+//! function is then read from the `.stack_sizes` sections that LLVM emits into ELF object files,
+//! and the worst-case stack usage of each entry point of the crate is the largest sum of frame
+//! sizes along a call chain starting at it.
+//!
+//! A function missing from the object files is assumed to have been inlined into its callers, and
+//! its callees are considered to be called by its callers. Recursion, calls through function
+//! pointers and trait objects, calls of functions from other crates, and functions that are in the
+//! object files without a frame size make the stack usage of an entry point unbounded; the report
+//! lists the reasons, and the usage of the chains that are known.
+//!
+//! Upstream crates are not built with `.stack_sizes` sections, so the frame sizes of their
+//! functions are not known. This includes the panics of bounds checks and other assertions, which
+//! are implemented in `core`: any entry point that can reach one is unbounded.
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexSet};
+use rustc_data_structures::stack::ensure_sufficient_stack;
+use rustc_hir::def::DefKind;
+use rustc_hir::LangItem;
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::mir::{AssertKind, TerminatorKind};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::{self, Instance, InstanceDef, TyCtxt};
+use rustc_session::config::SwitchWithOptPath;
+use rustc_session::Session;
+use rustc_span::Span;
+use rustc_target::spec::abi::Abi;
+
+use object::{Object, ObjectSection, ObjectSymbol, RelocationTarget, SymbolKind};
+use serde_json::json;
+
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::{errors, CodegenResults};
+
+#[derive(Debug, Encodable, Decodable)]
+pub struct CallGraph {
+    /// The functions codegened for the crate, sorted by symbol name.
+    functions: Vec<Function>,
+}
+
+#[derive(Debug, Encodable, Decodable)]
+struct Function {
+    symbol: String,
+    name: String,
+    span: String,
+    entry_point: Option<EntryPoint>,
+    calls: Vec<Call>,
+}
+
+#[derive(Clone, Copy, Debug, Encodable, Decodable)]
+enum EntryPoint {
+    /// The `main` function of the crate.
+    Main,
+    /// A `#[no_mangle]` or `#[export_name]` function.
+    Exported,
+    /// A function with an interrupt ABI.
+    Interrupt,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Encodable, Decodable)]
+enum Call {
+    /// A call of the function with the given symbol, including drop glue and the panics of
+    /// assertions.
+    Direct { symbol: String, name: String },
+    /// A call through a function pointer.
+    FnPtr { span: String },
+    /// A call of a trait object method.
+    Virtual { name: String, span: String },
+}
+
+impl CallGraph {
+    pub fn new(tcx: TyCtxt<'_>) -> CallGraph {
+        let (_, codegen_units) = tcx.collect_and_partition_mono_items(());
+        let mut instances: Vec<_> = codegen_units
+            .iter()
+            .flat_map(|cgu| cgu.items().keys())
+            .filter_map(|item| match *item {
+                MonoItem::Fn(instance) => Some((tcx.symbol_name(instance).name, instance)),
+                MonoItem::Static(_) | MonoItem::GlobalAsm(_) => None,
+            })
+            .collect();
+        // Functions that are inlined into several codegen units appear once per unit.
+        instances.sort_by_key(|&(symbol, _)| symbol);
+        instances.dedup_by_key(|&mut (symbol, _)| symbol);
+
+        let entry_fn = tcx.entry_fn(()).map(|(def_id, _)| def_id);
+        let functions = instances
+            .into_iter()
+            .map(|(symbol, instance)| {
+                let def_id = instance.def_id();
+                let entry_point = match instance.def {
+                    InstanceDef::Item(_) if Some(def_id) == entry_fn => Some(EntryPoint::Main),
+                    InstanceDef::Item(_)
+                        if matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+                            && is_interrupt_abi(tcx.fn_sig(def_id).skip_binder().abi()) =>
+                    {
+                        Some(EntryPoint::Interrupt)
+                    }
+                    InstanceDef::Item(_)
+                        if tcx.codegen_fn_attrs(def_id).contains_extern_indicator() =>
+                    {
+                        Some(EntryPoint::Exported)
+                    }
+                    _ => None,
+                };
+                Function {
+                    symbol: symbol.to_owned(),
+                    name: with_no_trimmed_paths!(instance.to_string()),
+                    span: span_to_string(tcx, tcx.def_span(def_id)),
+                    entry_point,
+                    calls: calls(tcx, instance),
+                }
+            })
+            .collect();
+        CallGraph { functions }
+    }
+}
+
+fn is_interrupt_abi(abi: Abi) -> bool {
+    matches!(
+        abi,
+        Abi::Msp430Interrupt
+            | Abi::X86Interrupt
+            | Abi::AvrInterrupt
+            | Abi::AvrNonBlockingInterrupt
+            | Abi::RiscvInterruptM
+            | Abi::RiscvInterruptS
+    )
+}
+
+fn span_to_string(tcx: TyCtxt<'_>, span: Span) -> String {
+    tcx.sess.source_map().span_to_embeddable_string(span)
+}
+
+/// Returns the calls made by the MIR of `instance`, the same way they are codegened.
+fn calls<'tcx>(tcx: TyCtxt<'tcx>, instance: Instance<'tcx>) -> Vec<Call> {
+    let body = tcx.instance_mir(instance.def);
+    let param_env = ty::ParamEnv::reveal_all();
+    let monomorphize = |ty| {
+        instance.instantiate_mir_and_normalize_erasing_regions(
+            tcx,
+            param_env,
+            ty::EarlyBinder::bind(ty),
+        )
+    };
+    let direct = |callee: Instance<'tcx>| Call::Direct {
+        symbol: tcx.symbol_name(callee).name.to_owned(),
+        name: with_no_trimmed_paths!(callee.to_string()),
+    };
+    let lang_item = |lang_item: LangItem, span: Span| {
+        direct(Instance::mono(tcx, tcx.require_lang_item(lang_item, Some(span))))
+    };
+
+    let mut calls = BTreeSet::new();
+    for block in body.basic_blocks.iter() {
+        let terminator = block.terminator();
+        let span = terminator.source_info.span;
+        match terminator.kind {
+            TerminatorKind::Call { ref func, .. } => {
+                let callee_ty = monomorphize(func.ty(body, tcx));
+                match *callee_ty.kind() {
+                    ty::FnDef(def_id, args) => {
+                        // Errors were reported when collecting the mono items.
+                        let Ok(Some(callee)) = Instance::resolve(tcx, param_env, def_id, args)
+                        else {
+                            continue;
+                        };
+                        match callee.def {
+                            InstanceDef::Virtual(..) => {
+                                calls.insert(Call::Virtual {
+                                    name: with_no_trimmed_paths!(callee.to_string()),
+                                    span: span_to_string(tcx, span),
+                                });
+                            }
+                            // Intrinsics are codegened in place.
+                            InstanceDef::Intrinsic(_) => {}
+                            _ => {
+                                calls.insert(direct(callee));
+                            }
+                        }
+                    }
+                    ty::FnPtr(_) => {
+                        calls.insert(Call::FnPtr { span: span_to_string(tcx, span) });
+                    }
+                    _ => {}
+                }
+            }
+            TerminatorKind::Drop { ref place, .. } => {
+                let ty = monomorphize(place.ty(body, tcx).ty);
+                let callee = Instance::resolve_drop_in_place(tcx, ty);
+                if !matches!(callee.def, InstanceDef::DropGlue(_, None)) {
+                    calls.insert(direct(callee));
+                }
+            }
+            TerminatorKind::Assert { ref msg, .. } => {
+                let item = match **msg {
+                    AssertKind::BoundsCheck { .. } => LangItem::PanicBoundsCheck,
+                    AssertKind::MisalignedPointerDereference { .. } => {
+                        LangItem::PanicMisalignedPointerDereference
+                    }
+                    _ => LangItem::Panic,
+                };
+                calls.insert(lang_item(item, span));
+            }
+            TerminatorKind::UnwindTerminate(reason) => {
+                calls.insert(lang_item(reason.lang_item(), span));
+            }
+            TerminatorKind::Goto { .. }
+            | TerminatorKind::SwitchInt { .. }
+            | TerminatorKind::UnwindResume
+            | TerminatorKind::Return
+            | TerminatorKind::Unreachable
+            | TerminatorKind::Yield { .. }
+            | TerminatorKind::GeneratorDrop
+            | TerminatorKind::FalseEdge { .. }
+            | TerminatorKind::FalseUnwind { .. }
+            | TerminatorKind::InlineAsm { .. } => {}
+        }
+    }
+    calls.into_iter().collect()
+}
+
+/// Writes the stack usage report of the crate to the directory given to `-Z stack-usage-report`.
+pub fn write_report(sess: &Session, call_graph: &CallGraph, codegen_results: &CodegenResults) {
+    let SwitchWithOptPath::Enabled(ref output_directory) =
+        sess.opts.unstable_opts.stack_usage_report
+    else {
+        return;
+    };
+
+    let mut frame_sizes = FrameSizes::default();
+    let objects = codegen_results
+        .modules
+        .iter()
+        .chain(&codegen_results.allocator_module)
+        .filter_map(|module| module.object.as_deref());
+    for path in objects {
+        if let Err(error) = read_frame_sizes(path, &mut frame_sizes) {
+            sess.emit_err(errors::StackUsageReadFrameSizes { path: path.to_path_buf(), error });
+        }
+    }
+    if frame_sizes.sizes.is_empty() && !call_graph.functions.is_empty() {
+        sess.emit_warning(errors::StackUsageNoFrameSizes);
+    }
+
+    let crate_name = codegen_results.crate_info.local_crate_name;
+    let output_directory = output_directory.as_deref().unwrap_or(Path::new("."));
+    let path = output_directory.join(format!("{crate_name}.stack_usage.json"));
+    let report = report(crate_name.as_str(), call_graph, &frame_sizes);
+    let result =
+        fs::create_dir_all(output_directory).and_then(|()| File::create(&path)).and_then(|file| {
+            let mut file = BufWriter::new(file);
+            serde_json::to_writer_pretty(&mut file, &report)?;
+            file.flush()
+        });
+    if let Err(error) = result {
+        sess.emit_err(errors::StackUsageWriteReport { path, error });
+    }
+}
+
+/// The frame sizes read from the object files of the crate.
+#[derive(Default)]
+struct FrameSizes {
+    /// The frame size of each function with a `.stack_sizes` entry, by symbol name.
+    sizes: FxHashMap<String, u64>,
+    /// The symbol names of the functions defined in the object files.
+    defined: FxHashSet<String>,
+}
+
+/// Adds the functions defined in an object file, and their frame sizes, to `frame_sizes`. Object
+/// files without a `.stack_sizes` section add no frame sizes.
+fn read_frame_sizes(path: &Path, frame_sizes: &mut FrameSizes) -> Result<(), String> {
+    let data = fs::read(path).map_err(|e| e.to_string())?;
+    let file = object::File::parse(&*data).map_err(|e| e.to_string())?;
+    let address_size = if file.is_64() { 8 } else { 4 };
+
+    // The functions of the object file, by section and address, for the entries of the
+    // `.stack_sizes` sections that refer to a function through the symbol of its section.
+    let functions: FxHashMap<_, _> = file
+        .symbols()
+        .filter(|symbol| symbol.kind() == SymbolKind::Text)
+        .filter_map(|symbol| {
+            Some(((symbol.section_index()?, symbol.address()), symbol.name().ok()?))
+        })
+        .collect();
+    frame_sizes.defined.extend(
+        file.symbols()
+            .filter(|symbol| symbol.kind() == SymbolKind::Text && symbol.is_definition())
+            .filter_map(|symbol| Some(strip_llvm_suffix(symbol.name().ok()?).to_owned())),
+    );
+
+    for section in file.sections().filter(|section| section.name().ok() == Some(".stack_sizes")) {
+        let section_data = section.data().map_err(|e| e.to_string())?;
+        // Each entry is the address of a function, which is relocated against the function,
+        // followed by its frame size in ULEB128.
+        for (offset, relocation) in section.relocations() {
+            let RelocationTarget::Symbol(symbol_index) = relocation.target() else {
+                continue;
+            };
+            let symbol = file.symbol_by_index(symbol_index).map_err(|e| e.to_string())?;
+            let offset = offset as usize;
+            let name = if symbol.kind() == SymbolKind::Section {
+                let addend = if relocation.has_implicit_addend() {
+                    read_address(section_data, offset, address_size, file.is_little_endian())
+                } else {
+                    Some(relocation.addend() as u64)
+                };
+                let function = symbol
+                    .section_index()
+                    .zip(addend)
+                    .and_then(|function| functions.get(&function).copied());
+                let Some(name) = function else { continue };
+                name
+            } else {
+                symbol.name().map_err(|e| e.to_string())?
+            };
+            let size = section_data
+                .get(offset + address_size..)
+                .and_then(read_uleb128)
+                .ok_or_else(|| format!("truncated `.stack_sizes` entry at offset {offset}"))?;
+
+            let frame_size =
+                frame_sizes.sizes.entry(strip_llvm_suffix(name).to_owned()).or_insert(0);
+            *frame_size = (*frame_size).max(size);
+        }
+    }
+    Ok(())
+}
+
+/// Symbols promoted by ThinLTO get a `.llvm.<hash>` suffix.
+fn strip_llvm_suffix(name: &str) -> &str {
+    name.split(".llvm.").next().unwrap()
+}
+
+fn read_address(data: &[u8], offset: usize, size: usize, little_endian: bool) -> Option<u64> {
+    let bytes = data.get(offset..offset + size)?;
+    let fold = |address, &byte| address << 8 | u64::from(byte);
+    Some(if little_endian { bytes.iter().rev().fold(0, fold) } else { bytes.iter().fold(0, fold) })
+}
+
+fn read_uleb128(data: &[u8]) -> Option<u64> {
+    let mut value = 0;
+    for (i, &byte) in data.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn report(crate_name: &str, call_graph: &CallGraph, frame_sizes: &FrameSizes) -> serde_json::Value {
+    let analysis = Analysis {
+        functions: &call_graph.functions,
+        index_by_symbol: call_graph
+            .functions
+            .iter()
+            .enumerate()
+            .map(|(index, function)| (&*function.symbol, index))
+            .collect(),
+        frame_sizes,
+    };
+
+    let mut entry_points: Vec<_> = call_graph
+        .functions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, function)| Some((index, function.entry_point?)))
+        .map(|(index, kind)| analysis.entry_point_report(index, kind))
+        .collect();
+    entry_points.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+    let functions: Vec<_> = call_graph
+        .functions
+        .iter()
+        .map(|function| {
+            json!({
+                "name": function.name,
+                "symbol": function.symbol,
+                "span": function.span,
+                "frame_bytes": frame_sizes.sizes.get(&function.symbol),
+            })
+        })
+        .collect();
+
+    json!({
+        "crate": crate_name,
+        "entry_points": entry_points,
+        "functions": functions,
+    })
+}
+
+struct Analysis<'a> {
+    functions: &'a [Function],
+    index_by_symbol: FxHashMap<&'a str, usize>,
+    frame_sizes: &'a FrameSizes,
+}
+
+impl<'a> Analysis<'a> {
+    fn entry_point_report(&self, entry: usize, kind: EntryPoint) -> serde_json::Value {
+        let mut walk = Walk {
+            analysis: self,
+            worst: FxHashMap::default(),
+            stack: FxIndexSet::default(),
+            unbounded: BTreeSet::new(),
+        };
+        let stack_bytes = walk.visit(entry);
+
+        // Follow the worst callee of each function of the worst call chain. The chain can only
+        // come back to a function through recursion, which is reported separately.
+        let mut chain = vec![entry];
+        let mut visited = FxIndexSet::default();
+        visited.insert(entry);
+        while let Some(&(_, Some(callee))) = walk.worst.get(chain.last().unwrap())
+            && visited.insert(callee)
+        {
+            chain.push(callee);
+        }
+
+        let function = &self.functions[entry];
+        json!({
+            "name": function.name,
+            "symbol": function.symbol,
+            "span": function.span,
+            "kind": match kind {
+                EntryPoint::Main => "main",
+                EntryPoint::Exported => "exported",
+                EntryPoint::Interrupt => "interrupt",
+            },
+            "stack_bytes": stack_bytes,
+            "bounded": walk.unbounded.is_empty(),
+            "worst_call_chain": chain
+                .into_iter()
+                .map(|index| {
+                    let function = &self.functions[index];
+                    json!({
+                        "name": function.name,
+                        "frame_bytes": self.frame_sizes.sizes.get(&function.symbol),
+                    })
+                })
+                .collect::<Vec<_>>(),
+            "unbounded": walk
+                .unbounded
+                .into_iter()
+                .map(|Unbounded { reason, function, detail }| {
+                    json!({
+                        "reason": reason,
+                        "function": self.functions[function].name,
+                        "detail": detail,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// A depth-first walk of the call graph from an entry point.
+struct Walk<'a, 'b> {
+    analysis: &'b Analysis<'a>,
+    /// The worst-case stack usage of the functions visited so far, and their callee with the
+    /// largest usage.
+    worst: FxHashMap<usize, (u64, Option<usize>)>,
+    /// The functions being visited.
+    stack: FxIndexSet<usize>,
+    unbounded: BTreeSet<Unbounded>,
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Unbounded {
+    /// `recursion`, `fn_ptr_call`, `dyn_call`, `external_call` or `unknown_frame_size`.
+    reason: &'static str,
+    function: usize,
+    detail: String,
+}
+
+impl Walk<'_, '_> {
+    /// Returns the worst-case stack usage of a function, including its own frame.
+    fn visit(&mut self, index: usize) -> u64 {
+        if let Some(&(bytes, _)) = self.worst.get(&index) {
+            return bytes;
+        }
+        if let Some(position) = self.stack.get_index_of(&index) {
+            let cycle: Vec<_> = self
+                .stack
+                .iter()
+                .skip(position)
+                .chain([&index])
+                .map(|&index| &*self.analysis.functions[index].name)
+                .collect();
+            self.unbounded.insert(Unbounded {
+                reason: "recursion",
+                function: index,
+                detail: cycle.join(" -> "),
+            });
+            return 0;
+        }
+
+        let analysis = self.analysis;
+        let function = &analysis.functions[index];
+        self.stack.insert(index);
+        let mut worst: (u64, Option<usize>) = (0, None);
+        for call in &function.calls {
+            match call {
+                Call::Direct { symbol, name } => match analysis.index_by_symbol.get(&**symbol) {
+                    Some(&callee) => {
+                        let bytes = ensure_sufficient_stack(|| self.visit(callee));
+                        if worst.1.is_none() || bytes > worst.0 {
+                            worst = (bytes, Some(callee));
+                        }
+                    }
+                    None => {
+                        self.unbounded.insert(Unbounded {
+                            reason: "external_call",
+                            function: index,
+                            detail: name.clone(),
+                        });
+                    }
+                },
+                Call::FnPtr { span } => {
+                    self.unbounded.insert(Unbounded {
+                        reason: "fn_ptr_call",
+                        function: index,
+                        detail: span.clone(),
+                    });
+                }
+                Call::Virtual { name, span } => {
+                    self.unbounded.insert(Unbounded {
+                        reason: "dyn_call",
+                        function: index,
+                        detail: format!("{name} at {span}"),
+                    });
+                }
+            }
+        }
+        self.stack.pop();
+
+        let FrameSizes { sizes, defined } = analysis.frame_sizes;
+        let frame_bytes = match sizes.get(&function.symbol) {
+            Some(&bytes) => bytes,
+            // Functions missing from the object files were inlined into all their callers, and
+            // their frame is part of the frames of their callers. Without object files, nothing is
+            // known about any function.
+            None if !defined.is_empty() && !defined.contains(&function.symbol) => 0,
+            // LLVM emits no frame size for functions with dynamically sized stack allocations.
+            None => {
+                self.unbounded.insert(Unbounded {
+                    reason: "unknown_frame_size",
+                    function: index,
+                    detail: function.symbol.clone(),
+                });
+                0
+            }
+        };
+        let bytes = frame_bytes + worst.0;
+        self.worst.insert(index, (bytes, worst.1));
+        bytes
+    }
+}
@@ -1,5 +1,6 @@
 use crate::back::link::are_upstream_rust_objects_already_included;
 use crate::back::metadata::create_compressed_metadata_file;
+use crate::back::stack_usage::CallGraph;
 use crate::back::write::{
     compute_per_cgu_lto_type, start_async_codegen, submit_codegened_module_to_llvm,
     submit_post_lto_module_to_llvm, submit_pre_lto_module_to_llvm, ComputedLtoType, OngoingCodegen,
@@ -857,6 +858,13 @@ pub fn new(tcx: TyCtxt<'_>, target_cpu: String) -> CrateInfo {
             dependency_formats: tcx.dependency_formats(()).clone(),
             windows_subsystem,
             natvis_debugger_visualizers: Default::default(),
+            stack_usage_call_graph: tcx
+                .sess
+                .opts
+                .unstable_opts
+                .stack_usage_report
+                .enabled()
+                .then(|| CallGraph::new(tcx)),
         };
         let crates = tcx.crates(());
 
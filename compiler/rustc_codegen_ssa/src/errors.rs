@@ -1034,3 +1034,22 @@ pub struct FailedToGetLayout<'tcx> {
 pub struct ErrorCreatingRemarkDir {
     pub error: std::io::Error,
 }
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_stack_usage_read_frame_sizes)]
+pub struct StackUsageReadFrameSizes {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_stack_usage_no_frame_sizes)]
+#[note]
+pub struct StackUsageNoFrameSizes;
+
+#[derive(Diagnostic)]
+#[diag(codegen_ssa_stack_usage_write_report)]
+pub struct StackUsageWriteReport {
+    pub path: PathBuf,
+    pub error: Error,
+}
@@ -164,6 +164,7 @@ pub struct CrateInfo {
     pub dependency_formats: Lrc<Dependencies>,
     pub windows_subsystem: Option<String>,
     pub natvis_debugger_visualizers: BTreeSet<DebuggerVisualizerFile>,
+    pub stack_usage_call_graph: Option<back::stack_usage::CallGraph>,
 }
 
 #[derive(Encodable, Decodable)]
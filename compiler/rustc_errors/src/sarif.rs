@@ -0,0 +1,509 @@
+//! A SARIF emitter for errors.
+//!
+//! [SARIF] is the JSON-based interchange format read by code scanning dashboards and code review
+//! tools. Unlike the JSON emitter, which prints each diagnostic as soon as it is emitted, this
+//! emitter collects the diagnostics of a compilation and prints them as a single SARIF 2.1.0 log
+//! when it is dropped.
+//!
+//! Each diagnostic becomes a SARIF result. Diagnostics with an error code or a lint name refer to
+//! a rule of that name, and the rules of error codes link to their explanation. Suggestions are
+//! turned into SARIF fixes.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::translation::{to_fluent_args, Translate};
+use crate::{
+    CodeSuggestion, DiagnosticId, FluentBundle, LazyFallbackBundle, Level, SpanLabel, SubDiagnostic,
+};
+use rustc_lint_defs::Applicability;
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::{IntoDynSyncSend, Lrc};
+use rustc_error_messages::FluentArgs;
+use rustc_span::Span;
+use std::error::Report;
+use std::io::{self, Write};
+use std::mem;
+use std::path::Path;
+
+use serde::Serialize;
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+/// The error code index, which has a page with the explanation of each error code.
+const ERROR_CODES_URI: &str = "https://doc.rust-lang.org/error_codes";
+
+pub struct SarifEmitter {
+    dst: IntoDynSyncSend<Box<dyn Write + Send>>,
+    registry: Option<Registry>,
+    sm: Lrc<SourceMap>,
+    fluent_bundle: Option<Lrc<FluentBundle>>,
+    fallback_bundle: LazyFallbackBundle,
+    write_empty_log: bool,
+    /// The rules referred to by the results so far, by ID.
+    rules: FxIndexMap<String, Rule>,
+    results: Vec<SarifResult>,
+}
+
+impl SarifEmitter {
+    pub fn stderr(
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        SarifEmitter::new(
+            Box::new(io::BufWriter::new(io::stderr())),
+            registry,
+            source_map,
+            fluent_bundle,
+            fallback_bundle,
+        )
+    }
+
+    pub fn basic(fallback_bundle: LazyFallbackBundle) -> SarifEmitter {
+        let file_path_mapping = FilePathMapping::empty();
+        SarifEmitter::stderr(
+            None,
+            Lrc::new(SourceMap::new(file_path_mapping)),
+            None,
+            fallback_bundle,
+        )
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst: IntoDynSyncSend(dst),
+            registry,
+            sm: source_map,
+            fluent_bundle,
+            fallback_bundle,
+            write_empty_log: true,
+            rules: FxIndexMap::default(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Whether a log is printed even if no diagnostics were emitted. This is turned off for the
+    /// emitters that only exist until the session is created, so that a successful compilation
+    /// still prints a single log.
+    pub fn write_empty_log(mut self, write_empty_log: bool) -> Self {
+        self.write_empty_log = write_empty_log;
+        self
+    }
+}
+
+impl Translate for SarifEmitter {
+    fn fluent_bundle(&self) -> Option<&Lrc<FluentBundle>> {
+        self.fluent_bundle.as_ref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &self.fallback_bundle
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        let level = match diag.level {
+            Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error { .. } => "error",
+            Level::Warning(_) => "warning",
+            Level::Note | Level::OnceNote | Level::Help | Level::OnceHelp => "note",
+            // Failure notes ("aborting due to 2 previous errors", ...) only summarize the other
+            // diagnostics, so they are not results of their own.
+            Level::FailureNote | Level::Allow | Level::Expect(_) => return,
+        };
+
+        let args = to_fluent_args(diag.args());
+        let mut message = self.translate_messages(&diag.message, &args).into_owned();
+        let (rule_id, rule_index) = match &diag.code {
+            Some(code) => {
+                let (rule_id, rule_index) = self.rule(code);
+                (Some(rule_id), Some(rule_index))
+            }
+            None => (None, None),
+        };
+
+        let mut locations = vec![];
+        let mut related_locations = vec![];
+        for span_label in diag.span.span_labels() {
+            if span_label.span.is_dummy() {
+                continue;
+            }
+            let label = self.translate_label(&span_label, &args);
+            let location = Location {
+                physical_location: self.physical_location(span_label.span),
+                message: label.map(Message::text),
+            };
+            if span_label.is_primary {
+                locations.push(location);
+            } else if location.message.is_some() {
+                related_locations.push(location);
+            }
+        }
+
+        // SARIF has no notion of child diagnostics, so the notes and helps attached to a
+        // diagnostic are listed in its message, and their spans become related locations.
+        for child in &diag.children {
+            let child_message = format!(
+                "{}: {}",
+                child.level.to_str(),
+                self.translate_messages(&child.message, &args)
+            );
+            related_locations.extend(self.child_locations(child, &child_message, &args));
+            message.push('\n');
+            message.push_str(&child_message);
+        }
+
+        let fixes =
+            diag.suggestions.iter().flatten().flat_map(|sugg| self.fixes(sugg, &args)).collect();
+
+        self.results.push(SarifResult {
+            rule_id,
+            rule_index,
+            level,
+            message: Message::text(message),
+            locations,
+            related_locations,
+            fixes,
+        });
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        if self.results.is_empty() && !self.write_empty_log {
+            return;
+        }
+
+        let log = Log {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "rustc",
+                        information_uri: "https://www.rust-lang.org/",
+                        rules: mem::take(&mut self.rules).into_values().collect(),
+                    },
+                },
+                // Columns are counted in characters, like in the other emitters.
+                column_kind: "unicodeCodePoints",
+                results: mem::take(&mut self.results),
+            }],
+        };
+        let result = writeln!(&mut self.dst, "{}", serde_json::to_string(&log).unwrap())
+            .and_then(|_| self.dst.flush());
+        if let Err(e) = result
+            && !std::thread::panicking()
+        {
+            panic!("failed to print diagnostics: {e:?}");
+        }
+    }
+}
+
+impl SarifEmitter {
+    /// Returns the ID and index of the rule for the given diagnostic code, adding the rule if no
+    /// previous result referred to it.
+    fn rule(&mut self, code: &DiagnosticId) -> (String, usize) {
+        let id = match code {
+            DiagnosticId::Error(code) => code,
+            DiagnosticId::Lint { name, .. } => name,
+        };
+        if let Some(index) = self.rules.get_index_of(id) {
+            return (id.clone(), index);
+        }
+
+        let mut rule = Rule { id: id.clone(), ..Rule::default() };
+        if let DiagnosticId::Error(code) = code {
+            rule.help_uri = Some(format!("{ERROR_CODES_URI}/{code}.html"));
+            if let Some(explanation) =
+                self.registry.as_ref().and_then(|registry| registry.try_find_description(code).ok())
+            {
+                // Explanations start with a one-line summary of the error.
+                rule.short_description = explanation
+                    .lines()
+                    .find(|line| !line.trim().is_empty())
+                    .map(|line| Message::text(line.trim().to_owned()));
+                rule.full_description = Some(Message {
+                    text: explanation.to_owned(),
+                    markdown: Some(explanation.to_owned()),
+                });
+            }
+        }
+        let (index, _) = self.rules.insert_full(id.clone(), rule);
+        (id.clone(), index)
+    }
+
+    fn translate_label(&self, span_label: &SpanLabel, args: &FluentArgs<'_>) -> Option<String> {
+        span_label
+            .label
+            .as_ref()
+            .map(|label| self.translate_message(label, args).map_err(Report::new).unwrap())
+            .map(|label| label.into_owned())
+    }
+
+    /// Returns the related locations for the spans of a note or help. Its primary spans are
+    /// described by its message, and its other spans by their labels.
+    fn child_locations(
+        &self,
+        child: &SubDiagnostic,
+        child_message: &str,
+        args: &FluentArgs<'_>,
+    ) -> Vec<Location> {
+        let span = child.render_span.as_ref().unwrap_or(&child.span);
+        span.span_labels()
+            .into_iter()
+            .filter(|span_label| !span_label.span.is_dummy())
+            .filter_map(|span_label| {
+                let message = match self.translate_label(&span_label, args) {
+                    Some(label) if span_label.is_primary => format!("{child_message}: {label}"),
+                    Some(label) => label,
+                    None if span_label.is_primary => child_message.to_owned(),
+                    None => return None,
+                };
+                Some(Location {
+                    physical_location: self.physical_location(span_label.span),
+                    message: Some(Message::text(message)),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns one fix for each of the alternative substitutions of a suggestion.
+    fn fixes(&self, sugg: &CodeSuggestion, args: &FluentArgs<'_>) -> Vec<Fix> {
+        let description = self.translate_message(&sugg.msg, args).map_err(Report::new).unwrap();
+        sugg.substitutions
+            .iter()
+            .map(|substitution| {
+                let mut replacements_by_uri = FxIndexMap::<_, Vec<_>>::default();
+                for part in &substitution.parts {
+                    let PhysicalLocation { artifact_location, region } =
+                        self.physical_location(part.span);
+                    replacements_by_uri.entry(artifact_location.uri).or_default().push(
+                        Replacement {
+                            deleted_region: region,
+                            inserted_content: ArtifactContent { text: part.snippet.clone() },
+                        },
+                    );
+                }
+                Fix {
+                    description: Message::text(description.to_string()),
+                    artifact_changes: replacements_by_uri
+                        .into_iter()
+                        .map(|(uri, replacements)| ArtifactChange {
+                            artifact_location: ArtifactLocation { uri },
+                            replacements,
+                        })
+                        .collect(),
+                    properties: FixProperties { applicability: sugg.applicability },
+                }
+            })
+            .collect()
+    }
+
+    fn physical_location(&self, span: Span) -> PhysicalLocation {
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: artifact_uri(&self.sm.filename_for_diagnostics(&start.file.name).to_string()),
+            },
+            region: Region {
+                start_line: start.line,
+                start_column: start.col.0 + 1,
+                end_line: end.line,
+                end_column: end.col.0 + 1,
+            },
+        }
+    }
+}
+
+/// Converts a file name, as it is shown in diagnostics, to a URI reference. Relative paths stay
+/// relative, so that tools resolve them against the directory they were compiled in.
+fn artifact_uri(file_name: &str) -> String {
+    let path = file_name.replace('\\', "/");
+    let mut uri = String::with_capacity(path.len());
+    if Path::new(file_name).is_absolute() {
+        uri.push_str("file://");
+        // Windows paths start with a drive letter rather than a slash.
+        if !path.starts_with('/') {
+            uri.push('/');
+        }
+    }
+    for c in path.chars() {
+        match c {
+            ' ' => uri.push_str("%20"),
+            '#' => uri.push_str("%23"),
+            '%' => uri.push_str("%25"),
+            '?' => uri.push_str("%3F"),
+            c => uri.push(c),
+        }
+    }
+    uri
+}
+
+// The following data types are provided just for serialisation, and follow the names of the
+// SARIF 2.1.0 objects they represent.
+
+#[derive(Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    column_kind: &'static str,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: ToolComponent,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent {
+    name: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    /// The error code or lint name.
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    short_description: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    /// Index of the rule in the rules of the tool.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    /// "error", "warning" or "note".
+    level: &'static str,
+    message: Message,
+    /// The primary spans of the diagnostic.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<Location>,
+    /// The labelled secondary spans of the diagnostic, and the spans of its notes and helps.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    markdown: Option<String>,
+}
+
+impl Message {
+    fn text(text: String) -> Message {
+        Message { text, markdown: None }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, character offset.
+    start_column: usize,
+    end_line: usize,
+    /// Exclusive.
+    end_column: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+struct FixProperties {
+    applicability: Applicability,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: ArtifactContent,
+}
+
+#[derive(Serialize)]
+struct ArtifactContent {
+    text: String,
+}
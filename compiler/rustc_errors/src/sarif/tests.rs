@@ -0,0 +1,200 @@
+use super::*;
+
+use crate::{Diagnostic, Handler, Level};
+use rustc_span::{BytePos, Span};
+
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+const CODE: &str = "fn main() {\n    let x = 1;\n}\n";
+
+/// Emits the diagnostics created by `f` through a SARIF emitter, and returns the printed log.
+fn emit_sarif(write_empty_log: bool, f: impl FnOnce(&Handler)) -> String {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("src/main.rs").to_owned().into(), CODE.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+        let registry = Registry::new(&[("E0999", "A test error happened.\n\nMore details.\n")]);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let emitter = SarifEmitter::new(
+            Box::new(Shared { data: output.clone() }),
+            Some(registry),
+            sm,
+            None,
+            fallback_bundle,
+        )
+        .write_empty_log(write_empty_log);
+
+        let handler = Handler::with_emitter(Box::new(emitter));
+        f(&handler);
+        drop(handler);
+
+        let bytes = output.lock().unwrap();
+        str::from_utf8(&bytes).unwrap().to_owned()
+    })
+}
+
+fn span(lo: u32, hi: u32) -> Span {
+    Span::with_root_ctxt(BytePos(lo), BytePos(hi))
+}
+
+#[test]
+fn error_with_code() {
+    let output = emit_sarif(true, |handler| {
+        handler
+            .struct_span_err_with_code(span(20, 21), "foo", DiagnosticId::Error("E0999".into()))
+            .span_label(span(20, 21), "primary label")
+            .span_label(span(3, 7), "secondary label")
+            .note("a note without a span")
+            .span_note(span(16, 19), "a note with a span")
+            .emit();
+    });
+    let log: Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["name"], "rustc");
+    assert_eq!(
+        run["tool"]["driver"]["rules"],
+        json!([{
+            "id": "E0999",
+            "shortDescription": { "text": "A test error happened." },
+            "fullDescription": {
+                "text": "A test error happened.\n\nMore details.\n",
+                "markdown": "A test error happened.\n\nMore details.\n",
+            },
+            "helpUri": "https://doc.rust-lang.org/error_codes/E0999.html",
+        }])
+    );
+    assert_eq!(
+        run["results"],
+        json!([{
+            "ruleId": "E0999",
+            "ruleIndex": 0,
+            "level": "error",
+            "message": { "text": "foo\nnote: a note without a span\nnote: a note with a span" },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": "src/main.rs" },
+                    "region": { "startLine": 2, "startColumn": 9, "endLine": 2, "endColumn": 10 },
+                },
+                "message": { "text": "primary label" },
+            }],
+            "relatedLocations": [
+                {
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "src/main.rs" },
+                        "region": { "startLine": 1, "startColumn": 4, "endLine": 1, "endColumn": 8 },
+                    },
+                    "message": { "text": "secondary label" },
+                },
+                {
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "src/main.rs" },
+                        "region": { "startLine": 2, "startColumn": 5, "endLine": 2, "endColumn": 8 },
+                    },
+                    "message": { "text": "note: a note with a span" },
+                },
+            ],
+        }])
+    );
+}
+
+#[test]
+fn suggestion_becomes_fix() {
+    let output = emit_sarif(true, |handler| {
+        handler
+            .struct_span_warn(span(20, 21), "bar")
+            .code(DiagnosticId::Lint {
+                name: "unused_variables".into(),
+                has_future_breakage: false,
+                is_force_warn: false,
+            })
+            .span_suggestion(
+                span(20, 21),
+                "prefix it with an underscore",
+                "_x",
+                Applicability::MachineApplicable,
+            )
+            .emit();
+    });
+    let log: Value = serde_json::from_str(&output).unwrap();
+
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["rules"], json!([{ "id": "unused_variables" }]));
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "unused_variables");
+    assert_eq!(result["level"], "warning");
+    assert_eq!(
+        result["fixes"],
+        json!([{
+            "description": { "text": "prefix it with an underscore" },
+            "artifactChanges": [{
+                "artifactLocation": { "uri": "src/main.rs" },
+                "replacements": [{
+                    "deletedRegion": { "startLine": 2, "startColumn": 9, "endLine": 2, "endColumn": 10 },
+                    "insertedContent": { "text": "_x" },
+                }],
+            }],
+            "properties": { "applicability": "MachineApplicable" },
+        }])
+    );
+}
+
+#[test]
+fn one_log_per_emitter() {
+    let output = emit_sarif(true, |handler| {
+        handler.struct_warn("first").emit();
+        handler.struct_warn("second").emit();
+        handler.struct_warn("first").code(DiagnosticId::Error("E0999".into())).emit();
+        handler.emit_diagnostic(&mut Diagnostic::new(Level::FailureNote, "not a result"));
+    });
+    assert_eq!(output.lines().count(), 1);
+    let log: Value = serde_json::from_str(&output).unwrap();
+
+    let messages: Vec<_> = log["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|result| result["message"]["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(messages, ["first", "second", "first"]);
+}
+
+#[test]
+fn empty_log() {
+    let output = emit_sarif(true, |_| {});
+    let log: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(log["runs"][0]["results"], json!([]));
+
+    assert_eq!(emit_sarif(false, |_| {}), "");
+}
+
+#[test]
+fn uris() {
+    assert_eq!(artifact_uri("src/main.rs"), "src/main.rs");
+    assert_eq!(artifact_uri("src/my file#1.rs"), "src/my%20file%231.rs");
+    if cfg!(windows) {
+        assert_eq!(artifact_uri(r"C:\src\main.rs"), "file:///C:/src/main.rs");
+    } else {
+        assert_eq!(artifact_uri("/src/main.rs"), "file:///src/main.rs");
+    }
+}
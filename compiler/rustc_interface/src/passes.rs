@@ -805,6 +805,12 @@ fn analysis(tcx: TyCtxt<'_>, (): ()) -> Result<()> {
         }
     });
 
+    if sess.opts.unstable_opts.unsafe_inventory.enabled() {
+        sess.time("unsafe_inventory", || {
+            rustc_mir_build::unsafe_inventory::dump_unsafe_inventory(tcx)
+        });
+    }
+
     sess.time("layout_testing", || layout_test::test_layout(tcx));
     sess.time("abi_testing", || abi_test::test_abi(tcx));
 
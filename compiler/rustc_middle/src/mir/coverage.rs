@@ -35,6 +35,21 @@ impl ExpressionId {
     pub const START: Self = Self::from_u32(0);
 }
 
+rustc_index::newtype_index! {
+    /// ID of a condition within an MC/DC decision. Values ascend from 1, because LLVM reserves
+    /// the ID 0 to mean that the decision is complete.
+    #[derive(HashStable)]
+    #[max = 0xFFFF]
+    #[debug_format = "ConditionId({})"]
+    pub struct ConditionId {}
+}
+
+impl ConditionId {
+    /// Refers to no condition: a branch leading here decides the outcome of its decision.
+    pub const NONE: Self = Self::from_u32(0);
+    pub const START: Self = Self::from_u32(1);
+}
+
 /// Operand of a coverage-counter expression.
 ///
 /// Operands can be a constant zero value, an actual coverage counter, or another
@@ -73,6 +88,50 @@ pub enum CoverageKind {
         op: Op,
         rhs: Operand,
     },
+    /// Marks a branch point in the function's source code. `true_term` counts how many times
+    /// the branch was taken, and `false_term` how many times it was not. Like expressions,
+    /// branches don't generate any executable code; the code regions of the enclosing
+    /// `Coverage` statement become branch regions in the coverage map.
+    Branch {
+        true_term: Operand,
+        false_term: Operand,
+    },
+    /// Sets up the MC/DC state of the function. Injected once into the start block of every
+    /// function with at least one MC/DC decision; `bitmap_bytes` is the size of the function's
+    /// test vector bitmap.
+    MCDCParameters {
+        function_source_hash: u64,
+        bitmap_bytes: u32,
+    },
+    /// Marks an MC/DC decision, such as the condition of an `if` that combines several
+    /// conditions with `&&` and `||`. The code regions of the enclosing `Coverage` statement
+    /// become decision regions in the coverage map. Like expressions, decisions don't generate
+    /// any executable code.
+    MCDCDecision {
+        /// Index of the first byte of this decision's test vectors in the function's bitmap.
+        bitmap_idx: u32,
+        num_conditions: u16,
+    },
+    /// Like `Branch`, but for one condition of an MC/DC decision. `true_next_id` and
+    /// `false_next_id` are the conditions evaluated next depending on the outcome of this one,
+    /// or [`ConditionId::NONE`] if that outcome decides the whole decision.
+    MCDCBranch {
+        true_term: Operand,
+        false_term: Operand,
+        id: ConditionId,
+        true_next_id: ConditionId,
+        false_next_id: ConditionId,
+    },
+    /// Records that condition `id` of the decision being evaluated was true, by setting its bit
+    /// in the function's condition bitmap.
+    CondBitmapUpdate {
+        id: ConditionId,
+    },
+    /// Records the test vector accumulated in the condition bitmap once the outcome of a
+    /// decision is known, and resets the condition bitmap for the next decision.
+    TestVectorBitmapUpdate {
+        bitmap_idx: u32,
+    },
     Unreachable,
 }
 
@@ -92,6 +151,26 @@ fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
                 },
                 rhs,
             ),
+            Branch { true_term, false_term } => {
+                write!(fmt, "Branch(true: {true_term:?}, false: {false_term:?})")
+            }
+            MCDCParameters { bitmap_bytes, .. } => {
+                write!(fmt, "MCDCParameters(bitmap_bytes: {bitmap_bytes})")
+            }
+            MCDCDecision { bitmap_idx, num_conditions } => {
+                write!(fmt, "MCDCDecision(bitmap_idx: {bitmap_idx}, conditions: {num_conditions})")
+            }
+            MCDCBranch { true_term, false_term, id, true_next_id, false_next_id } => write!(
+                fmt,
+                "MCDCBranch({:?}, true: {true_term:?} -> {:?}, false: {false_term:?} -> {:?})",
+                id.index(),
+                true_next_id.index(),
+                false_next_id.index(),
+            ),
+            CondBitmapUpdate { id } => write!(fmt, "CondBitmapUpdate({:?})", id.index()),
+            TestVectorBitmapUpdate { bitmap_idx } => {
+                write!(fmt, "TestVectorBitmapUpdate({bitmap_idx})")
+            }
             Unreachable => write!(fmt, "Unreachable"),
         }
     }
@@ -5,7 +5,7 @@
 use rustc_data_structures::unord::UnordSet;
 use rustc_errors::ErrorGuaranteed;
 use rustc_hir as hir;
-use rustc_hir::def_id::LocalDefId;
+use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_index::bit_set::BitMatrix;
 use rustc_index::{Idx, IndexVec};
 use rustc_span::symbol::Symbol;
@@ -107,6 +107,20 @@ pub struct UnsafetyViolation {
     pub details: UnsafetyViolationDetails,
 }
 
+/// An unsafe operation allowed by an `unsafe` block or an `unsafe fn`, as found by THIR unsafety
+/// checking.
+#[derive(Copy, Clone, PartialEq, TyEncodable, TyDecodable, HashStable, Debug)]
+pub struct UnsafeOperation {
+    pub span: Span,
+    pub details: UnsafetyViolationDetails,
+    /// The called function, for calls to unsafe functions and to functions with
+    /// `#[target_feature]`.
+    pub callee: Option<DefId>,
+    /// The `unsafe` block allowing the operation, or `None` if it is allowed by the enclosing
+    /// `unsafe fn`.
+    pub unsafe_block: Option<hir::HirId>,
+}
+
 #[derive(Copy, Clone, PartialEq, TyEncodable, TyDecodable, HashStable, Debug)]
 pub enum UnusedUnsafe {
     /// `unsafe` block contains no unsafe operations
@@ -882,7 +882,10 @@
 
     /// Unsafety-check this `LocalDefId` with THIR unsafeck. This should be
     /// used with `-Zthir-unsafeck`.
-    query thir_check_unsafety(key: LocalDefId) {
+    ///
+    /// Returns the unsafe operations found in `unsafe` blocks and `unsafe fn`
+    /// bodies, which are listed by `-Zunsafe-inventory`.
+    query thir_check_unsafety(key: LocalDefId) -> &'tcx [mir::UnsafeOperation] {
         desc { |tcx| "unsafety-checking `{}`", tcx.def_path_str(key) }
         cache_on_disk_if { true }
     }
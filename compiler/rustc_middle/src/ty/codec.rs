@@ -497,6 +497,7 @@ fn decode(decoder: &mut D) -> &'tcx Self {
     rustc_span::def_id::LocalDefId,
     (rustc_middle::middle::exported_symbols::ExportedSymbol<'tcx>, rustc_middle::middle::exported_symbols::SymbolExportInfo),
     ty::DeducedParamAttrs,
+    rustc_middle::mir::UnsafeOperation,
 }
 
 #[macro_export]
@@ -3,7 +3,7 @@
 use rustc_middle::thir::visit::{self, Visitor};
 
 use rustc_hir as hir;
-use rustc_middle::mir::BorrowKind;
+use rustc_middle::mir::{BorrowKind, UnsafeOperation, UnsafetyViolationDetails};
 use rustc_middle::thir::*;
 use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::{self, ParamEnv, Ty, TyCtxt};
@@ -13,6 +13,7 @@
 use rustc_span::symbol::Symbol;
 use rustc_span::Span;
 
+use std::mem;
 use std::ops::Bound;
 
 struct UnsafetyVisitor<'a, 'tcx> {
@@ -34,6 +35,11 @@ struct UnsafetyVisitor<'a, 'tcx> {
     in_union_destructure: bool,
     param_env: ParamEnv<'tcx>,
     inside_adt: bool,
+    /// Whether to report unsafe operations outside of `unsafe` and unused `unsafe` blocks. This
+    /// is `false` when the body is only checked to record its unsafe operations.
+    emit_diagnostics: bool,
+    /// The unsafe operations allowed by `unsafe` blocks and `unsafe fn`s.
+    unsafe_ops: Vec<UnsafeOperation>,
 }
 
 impl<'tcx> UnsafetyVisitor<'_, 'tcx> {
@@ -78,18 +84,22 @@ fn requires_unsafe(&mut self, span: Span, kind: UnsafeOpKind) {
         let unsafe_op_in_unsafe_fn_allowed = self.unsafe_op_in_unsafe_fn_allowed();
         match self.safety_context {
             SafetyContext::BuiltinUnsafeBlock => {}
-            SafetyContext::UnsafeBlock { ref mut used, .. } => {
+            SafetyContext::UnsafeBlock { ref mut used, hir_id, .. } => {
                 // Mark this block as useful (even inside `unsafe fn`, where it is technically
                 // redundant -- but we want to eventually enable `unsafe_op_in_unsafe_fn` by
                 // default which will require those blocks:
                 // https://github.com/rust-lang/rust/issues/71668#issuecomment-1203075594).
                 *used = true;
+                self.unsafe_ops.push(kind.unsafe_operation(span, Some(hir_id)));
             }
-            SafetyContext::UnsafeFn if unsafe_op_in_unsafe_fn_allowed => {}
             SafetyContext::UnsafeFn => {
+                self.unsafe_ops.push(kind.unsafe_operation(span, None));
                 // unsafe_op_in_unsafe_fn is disallowed
-                kind.emit_unsafe_op_in_unsafe_fn_lint(self.tcx, self.hir_context, span);
+                if !unsafe_op_in_unsafe_fn_allowed && self.emit_diagnostics {
+                    kind.emit_unsafe_op_in_unsafe_fn_lint(self.tcx, self.hir_context, span);
+                }
             }
+            SafetyContext::Safe if !self.emit_diagnostics => {}
             SafetyContext::Safe => {
                 kind.emit_requires_unsafe_err(
                     self.tcx,
@@ -107,6 +117,9 @@ fn warn_unused_unsafe(
         block_span: Span,
         enclosing_unsafe: Option<UnusedUnsafeEnclosing>,
     ) {
+        if !self.emit_diagnostics {
+            return;
+        }
         let block_span = self.tcx.sess.source_map().guess_head_span(block_span);
         self.tcx.emit_spanned_lint(
             UNUSED_UNSAFE,
@@ -126,10 +139,16 @@ fn visit_inner_body(&mut self, def: LocalDefId) {
         if let Ok((inner_thir, expr)) = self.tcx.thir_body(def) {
             let inner_thir = &inner_thir.borrow();
             let hir_context = self.tcx.hir().local_def_id_to_hir_id(def);
-            let mut inner_visitor = UnsafetyVisitor { thir: inner_thir, hir_context, ..*self };
+            let mut inner_visitor = UnsafetyVisitor {
+                thir: inner_thir,
+                hir_context,
+                unsafe_ops: mem::take(&mut self.unsafe_ops),
+                ..*self
+            };
             inner_visitor.visit_expr(&inner_thir[expr]);
             // Unsafe blocks can be used in the inner body, make sure to take it into account
             self.safety_context = inner_visitor.safety_context;
+            self.unsafe_ops = inner_visitor.unsafe_ops;
         }
     }
 }
@@ -519,6 +538,23 @@ enum UnsafeOpKind {
 use UnsafeOpKind::*;
 
 impl UnsafeOpKind {
+    fn unsafe_operation(self, span: Span, unsafe_block: Option<hir::HirId>) -> UnsafeOperation {
+        use UnsafetyViolationDetails as Details;
+        let (details, callee) = match self {
+            CallToUnsafeFunction(did) => (Details::CallToUnsafeFunction, did),
+            UseOfInlineAssembly => (Details::UseOfInlineAssembly, None),
+            InitializingTypeWith => (Details::InitializingTypeWith, None),
+            UseOfMutableStatic => (Details::UseOfMutableStatic, None),
+            UseOfExternStatic => (Details::UseOfExternStatic, None),
+            DerefOfRawPointer => (Details::DerefOfRawPointer, None),
+            AccessToUnionField => (Details::AccessToUnionField, None),
+            MutationOfLayoutConstrainedField => (Details::MutationOfLayoutConstrainedField, None),
+            BorrowOfLayoutConstrainedField => (Details::BorrowOfLayoutConstrainedField, None),
+            CallToFunctionWith(did) => (Details::CallToFunctionWith, Some(did)),
+        };
+        UnsafeOperation { span, details, callee, unsafe_block }
+    }
+
     pub fn emit_unsafe_op_in_unsafe_fn_lint(
         &self,
         tcx: TyCtxt<'_>,
@@ -772,22 +808,24 @@ pub fn emit_requires_unsafe_err(
     }
 }
 
-pub fn thir_check_unsafety(tcx: TyCtxt<'_>, def: LocalDefId) {
-    // THIR unsafeck is gated under `-Z thir-unsafeck`
-    if !tcx.sess.opts.unstable_opts.thir_unsafeck {
-        return;
+pub fn thir_check_unsafety(tcx: TyCtxt<'_>, def: LocalDefId) -> &[UnsafeOperation] {
+    // THIR unsafeck is gated under `-Z thir-unsafeck`. With `-Z unsafe-inventory`, it still runs
+    // to record the unsafe operations, without emitting any diagnostics.
+    let emit_diagnostics = tcx.sess.opts.unstable_opts.thir_unsafeck;
+    if !emit_diagnostics && !tcx.sess.opts.unstable_opts.unsafe_inventory.enabled() {
+        return &[];
     }
 
     // Closures and inline consts are handled by their owner, if it has a body
     if tcx.is_typeck_child(def.to_def_id()) {
-        return;
+        return &[];
     }
 
-    let Ok((thir, expr)) = tcx.thir_body(def) else { return };
+    let Ok((thir, expr)) = tcx.thir_body(def) else { return &[] };
     let thir = &thir.borrow();
     // If `thir` is empty, a type error occurred, skip this body.
     if thir.exprs.is_empty() {
-        return;
+        return &[];
     }
 
     let hir_id = tcx.hir().local_def_id_to_hir_id(def);
@@ -812,6 +850,9 @@ pub fn thir_check_unsafety(tcx: TyCtxt<'_>, def: LocalDefId) {
         in_union_destructure: false,
         param_env: tcx.param_env(def),
         inside_adt: false,
+        emit_diagnostics,
+        unsafe_ops: Vec::new(),
     };
     visitor.visit_expr(&thir[expr]);
+    tcx.arena.alloc_from_iter(visitor.unsafe_ops)
 }
@@ -946,3 +946,9 @@ pub enum RustcBoxAttrReason {
     #[note(mir_build_missing_box)]
     MissingBox,
 }
+
+#[derive(Diagnostic)]
+#[diag(mir_build_couldnt_dump_unsafe_inventory)]
+pub struct CouldntDumpUnsafeInventory {
+    pub error: String,
+}
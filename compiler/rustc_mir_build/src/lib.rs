@@ -21,6 +21,7 @@
 mod errors;
 pub mod lints;
 pub mod thir;
+pub mod unsafe_inventory;
 
 use rustc_middle::query::Providers;
 
@@ -0,0 +1,242 @@
+//! Writes the inventory of the unsafe code of the crate, for `-Z unsafe-inventory`.
+//!
+//! The inventory lists every `unsafe` block, `unsafe fn`, `unsafe impl`, `unsafe trait`,
+//! `extern` block, and `#[no_mangle]` or `#[link_section]` item of the crate. The unsafe
+//! operations allowed by each `unsafe` block and `unsafe fn` are the ones recorded by THIR
+//! unsafety checking.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir as hir;
+use rustc_hir::def_id::{LocalDefId, CRATE_DEF_ID, LOCAL_CRATE};
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_middle::hir::nested_filter;
+use rustc_middle::mir::{UnsafeOperation, UnsafetyViolationDetails};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::TyCtxt;
+use rustc_session::config::SwitchWithOptPath;
+use rustc_span::{sym, Span};
+
+use crate::errors::CouldntDumpUnsafeInventory;
+
+#[derive(serde::Serialize)]
+struct Inventory {
+    #[serde(rename = "crate")]
+    crate_name: String,
+    entries: Vec<Entry>,
+}
+
+#[derive(serde::Serialize)]
+struct Entry {
+    kind: &'static str,
+    /// The path of the item itself, or of the enclosing item for `unsafe` and `extern` blocks.
+    item: String,
+    span: String,
+    operations: Vec<Operation>,
+}
+
+#[derive(serde::Serialize)]
+struct Operation {
+    kind: &'static str,
+    description: &'static str,
+    span: String,
+    callee: Option<String>,
+}
+
+/// Writes the unsafe code inventory of the crate as `<crate>.unsafe_inventory.json`, in the
+/// directory given to `-Z unsafe-inventory`.
+pub fn dump_unsafe_inventory(tcx: TyCtxt<'_>) {
+    let SwitchWithOptPath::Enabled(ref output_directory) =
+        tcx.sess.opts.unstable_opts.unsafe_inventory
+    else {
+        return;
+    };
+    if let Err(err) = write_inventory(tcx, output_directory) {
+        tcx.sess.emit_fatal(CouldntDumpUnsafeInventory { error: err.to_string() });
+    }
+}
+
+fn write_inventory(
+    tcx: TyCtxt<'_>,
+    output_directory: &Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_directory = if let Some(ref directory) = output_directory {
+        fs::create_dir_all(directory)?;
+        directory
+    } else {
+        Path::new(".")
+    };
+
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    let inventory = Inventory { crate_name: crate_name.to_string(), entries: collect_entries(tcx) };
+
+    let path = output_directory.join(format!("{crate_name}.unsafe_inventory.json"));
+    let mut file = BufWriter::new(File::create(&path)?);
+    serde_json::to_writer_pretty(&mut file, &inventory)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+fn collect_entries(tcx: TyCtxt<'_>) -> Vec<Entry> {
+    // The unsafe operations of closures and inline consts are recorded with their typeck root,
+    // so the operations allowed by an `unsafe fn` are keyed by its own body.
+    let mut block_ops: FxHashMap<hir::HirId, Vec<Operation>> = Default::default();
+    let mut fn_ops: FxHashMap<LocalDefId, Vec<Operation>> = Default::default();
+    for def_id in tcx.hir().body_owners() {
+        if tcx.is_typeck_child(def_id.to_def_id()) {
+            continue;
+        }
+        for op in tcx.thir_check_unsafety(def_id) {
+            let operation = describe_operation(tcx, op);
+            match op.unsafe_block {
+                Some(hir_id) => block_ops.entry(hir_id).or_default().push(operation),
+                None => fn_ops.entry(def_id).or_default().push(operation),
+            }
+        }
+    }
+
+    let mut collector = EntryCollector { tcx, block_ops, fn_ops, entries: Vec::new() };
+    tcx.hir().walk_toplevel_module(&mut collector);
+    collector.entries
+}
+
+struct EntryCollector<'tcx> {
+    tcx: TyCtxt<'tcx>,
+    block_ops: FxHashMap<hir::HirId, Vec<Operation>>,
+    fn_ops: FxHashMap<LocalDefId, Vec<Operation>>,
+    entries: Vec<Entry>,
+}
+
+impl<'tcx> EntryCollector<'tcx> {
+    fn push(
+        &mut self,
+        kind: &'static str,
+        def_id: LocalDefId,
+        span: Span,
+        operations: Vec<Operation>,
+    ) {
+        self.entries.push(Entry {
+            kind,
+            item: item_path(self.tcx, def_id),
+            span: span_to_string(self.tcx, span),
+            operations,
+        });
+    }
+
+    fn check_fn(&mut self, def_id: LocalDefId, sig: &hir::FnSig<'_>) {
+        if sig.header.unsafety == hir::Unsafety::Unsafe {
+            let operations = self.fn_ops.remove(&def_id).unwrap_or_default();
+            self.push("unsafe_fn", def_id, self.tcx.def_span(def_id), operations);
+        }
+    }
+
+    fn check_attrs(&mut self, def_id: LocalDefId) {
+        let hir_id = self.tcx.hir().local_def_id_to_hir_id(def_id);
+        for attr in self.tcx.hir().attrs(hir_id) {
+            if attr.has_name(sym::no_mangle) {
+                self.push("no_mangle", def_id, attr.span, Vec::new());
+            } else if attr.has_name(sym::link_section) {
+                self.push("link_section", def_id, attr.span, Vec::new());
+            }
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for EntryCollector<'tcx> {
+    type NestedFilter = nested_filter::All;
+
+    fn nested_visit_map(&mut self) -> Self::Map {
+        self.tcx.hir()
+    }
+
+    fn visit_item(&mut self, item: &'tcx hir::Item<'tcx>) {
+        let def_id = item.owner_id.def_id;
+        match item.kind {
+            hir::ItemKind::Fn(ref sig, ..) => self.check_fn(def_id, sig),
+            hir::ItemKind::Impl(impl_) if impl_.unsafety == hir::Unsafety::Unsafe => {
+                self.push("unsafe_impl", def_id, self.tcx.def_span(def_id), Vec::new());
+            }
+            hir::ItemKind::Trait(_, hir::Unsafety::Unsafe, ..) => {
+                self.push("unsafe_trait", def_id, self.tcx.def_span(def_id), Vec::new());
+            }
+            hir::ItemKind::ForeignMod { .. } => {
+                let parent = self.tcx.hir().get_parent_item(item.hir_id()).def_id;
+                self.push("extern_block", parent, item.span, Vec::new());
+            }
+            _ => {}
+        }
+        self.check_attrs(def_id);
+        intravisit::walk_item(self, item);
+    }
+
+    fn visit_trait_item(&mut self, trait_item: &'tcx hir::TraitItem<'tcx>) {
+        let def_id = trait_item.owner_id.def_id;
+        if let hir::TraitItemKind::Fn(ref sig, _) = trait_item.kind {
+            self.check_fn(def_id, sig);
+        }
+        self.check_attrs(def_id);
+        intravisit::walk_trait_item(self, trait_item);
+    }
+
+    fn visit_impl_item(&mut self, impl_item: &'tcx hir::ImplItem<'tcx>) {
+        let def_id = impl_item.owner_id.def_id;
+        if let hir::ImplItemKind::Fn(ref sig, _) = impl_item.kind {
+            self.check_fn(def_id, sig);
+        }
+        self.check_attrs(def_id);
+        intravisit::walk_impl_item(self, impl_item);
+    }
+
+    fn visit_block(&mut self, block: &'tcx hir::Block<'tcx>) {
+        if let hir::BlockCheckMode::UnsafeBlock(hir::UnsafeSource::UserProvided) = block.rules {
+            let owner = self.tcx.hir().get_parent_item(block.hir_id).def_id;
+            let operations = self.block_ops.remove(&block.hir_id).unwrap_or_default();
+            self.push("unsafe_block", owner, block.span, operations);
+        }
+        intravisit::walk_block(self, block);
+    }
+}
+
+fn describe_operation(tcx: TyCtxt<'_>, op: &UnsafeOperation) -> Operation {
+    Operation {
+        kind: operation_kind_name(op.details),
+        description: op.details.description_and_note().0,
+        span: span_to_string(tcx, op.span),
+        callee: op.callee.map(|def_id| with_no_trimmed_paths!(tcx.def_path_str(def_id))),
+    }
+}
+
+fn operation_kind_name(details: UnsafetyViolationDetails) -> &'static str {
+    match details {
+        UnsafetyViolationDetails::CallToUnsafeFunction => "call_to_unsafe_fn",
+        UnsafetyViolationDetails::UseOfInlineAssembly => "inline_asm",
+        UnsafetyViolationDetails::InitializingTypeWith => "init_layout_constrained_type",
+        UnsafetyViolationDetails::CastOfPointerToInt => "ptr_to_int_cast",
+        UnsafetyViolationDetails::UseOfMutableStatic => "static_mut_access",
+        UnsafetyViolationDetails::UseOfExternStatic => "extern_static_access",
+        UnsafetyViolationDetails::DerefOfRawPointer => "raw_ptr_deref",
+        UnsafetyViolationDetails::AccessToUnionField => "union_field_access",
+        UnsafetyViolationDetails::MutationOfLayoutConstrainedField => {
+            "layout_constrained_field_mutation"
+        }
+        UnsafetyViolationDetails::BorrowOfLayoutConstrainedField => {
+            "layout_constrained_field_borrow"
+        }
+        UnsafetyViolationDetails::CallToFunctionWith => "call_to_target_feature_fn",
+    }
+}
+
+fn item_path(tcx: TyCtxt<'_>, def_id: LocalDefId) -> String {
+    if def_id == CRATE_DEF_ID {
+        return "crate".to_string();
+    }
+    with_no_trimmed_paths!(tcx.def_path_str(def_id))
+}
+
+fn span_to_string(tcx: TyCtxt<'_>, span: Span) -> String {
+    tcx.sess.source_map().span_to_embeddable_string(span)
+}
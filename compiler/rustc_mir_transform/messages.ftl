@@ -25,6 +25,10 @@ mir_transform_fn_item_ref = taking a reference to a function item does not give
 
 mir_transform_initializing_valid_range_label = initializing type with `rustc_layout_scalar_valid_range` attr
 mir_transform_initializing_valid_range_note = initializing a layout restricted type's field with a value outside the valid range is undefined behavior
+mir_transform_mcdc_too_many_conditions =
+    this decision has {$num_conditions} conditions, but MC/DC coverage supports at most {$max_conditions}
+    .note = its conditions are instrumented for branch coverage only
+
 mir_transform_must_not_suspend = {$pre}`{$def_path}`{$post} held across a suspend point, but should not be
     .label = the value is held across this suspend point
     .note = {$reason}
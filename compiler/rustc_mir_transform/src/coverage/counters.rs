@@ -178,6 +178,38 @@ fn set_bcb_edge_counter(
         }
     }
 
+    /// Makes an expression that is not associated with any BCB or edge, such as the "not taken"
+    /// side of a branch region, and records it so that it gets injected with the other
+    /// intermediate expressions.
+    pub(super) fn make_intermediate_expression(
+        &mut self,
+        lhs: Operand,
+        op: Op,
+        rhs: Operand,
+    ) -> Operand {
+        let expression = self.make_expression(lhs, op, rhs);
+        let operand = expression.as_operand();
+        self.intermediate_expressions.push(expression);
+        operand
+    }
+
+    /// Returns the counter or expression counting how many times control flowed from `from_bcb`
+    /// to its successor `to_bcb`, if one was made by `make_bcb_counters`.
+    pub(super) fn bcb_branch_operand(
+        &self,
+        from_bcb: BasicCoverageBlock,
+        to_bcb: BasicCoverageBlock,
+        basic_coverage_blocks: &CoverageGraph,
+    ) -> Option<Operand> {
+        let branch = BcbBranch::from_to(from_bcb, to_bcb, basic_coverage_blocks);
+        let counter = if let Some(from_bcb) = branch.edge_from_bcb {
+            self.bcb_edge_counters.get(&(from_bcb, to_bcb))
+        } else {
+            self.bcb_counter(to_bcb)
+        };
+        counter.map(BcbCounter::as_operand)
+    }
+
     pub(super) fn bcb_counter(&self, bcb: BasicCoverageBlock) -> Option<&BcbCounter> {
         self.bcb_counters[bcb].as_ref()
     }
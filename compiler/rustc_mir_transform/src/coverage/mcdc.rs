@@ -0,0 +1,192 @@
+//! Finds the decisions instrumented for MC/DC (modified condition/decision coverage).
+//!
+//! A decision is the condition of an `if` or `while` that combines two or more conditions with
+//! `&&` and `||`. MIR building lowers each of those conditions to its own `SwitchInt`, so the
+//! decisions are found in HIR, and their conditions are matched to the MIR switches by span.
+
+use rustc_hir as hir;
+use rustc_hir::intravisit::{self, Visitor};
+use rustc_middle::mir::coverage::ConditionId;
+use rustc_middle::ty::TypeckResults;
+use rustc_span::Span;
+
+/// The largest number of conditions that LLVM can track for a single decision.
+pub(super) const MAX_CONDITIONS_PER_DECISION: usize = 6;
+
+#[derive(Debug)]
+pub(super) struct Decision {
+    pub span: Span,
+    /// The conditions of the decision, in evaluation order.
+    pub conditions: Vec<Condition>,
+}
+
+#[derive(Debug)]
+pub(super) struct Condition {
+    /// The span of the condition, including any `!` applied directly to it.
+    pub span: Span,
+    /// The span of the boolean that MIR building switches on, without the `!` applied to it.
+    pub switch_span: Span,
+    /// Whether the condition is the negation of the boolean that is switched on. LLVM takes the
+    /// value of the last condition evaluated as the outcome of the decision, so a condition under
+    /// a `!` has to count the negated boolean.
+    pub negated: bool,
+    pub id: ConditionId,
+    /// The condition evaluated next if this one is true, or `ConditionId::NONE` if the decision
+    /// is then true.
+    pub true_next_id: ConditionId,
+    /// The condition evaluated next if this one is false, or `ConditionId::NONE` if the decision
+    /// is then false.
+    pub false_next_id: ConditionId,
+}
+
+/// Returns the decisions in the given function body, not including the bodies of its closures,
+/// which are instrumented separately.
+pub(super) fn find_decisions<'tcx>(
+    hir_body: &'tcx hir::Body<'tcx>,
+    typeck_results: &'tcx TypeckResults<'tcx>,
+) -> Vec<Decision> {
+    let mut finder = DecisionFinder { typeck_results, decisions: Vec::new() };
+    finder.visit_body(hir_body);
+    finder.decisions
+}
+
+struct DecisionFinder<'tcx> {
+    typeck_results: &'tcx TypeckResults<'tcx>,
+    decisions: Vec<Decision>,
+}
+
+impl<'tcx> Visitor<'tcx> for DecisionFinder<'tcx> {
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr<'tcx>) {
+        // `while` loops are lowered to a `loop` around an `if`, so this covers both.
+        if let hir::ExprKind::If(cond, then, els) = expr.kind
+            && let Some(decision) = self.make_decision(cond)
+        {
+            self.decisions.push(decision);
+            // Decisions nested within the conditions of another decision are not instrumented,
+            // because they would share the condition bitmap of the outer decision.
+            self.visit_expr(then);
+            if let Some(els) = els {
+                self.visit_expr(els);
+            }
+            return;
+        }
+        intravisit::walk_expr(self, expr);
+    }
+}
+
+impl<'tcx> DecisionFinder<'tcx> {
+    fn make_decision(&self, cond: &hir::Expr<'_>) -> Option<Decision> {
+        let mut conditions = Vec::new();
+        let mut next_id = ConditionId::START + 1;
+        self.collect_conditions(
+            cond,
+            ConditionId::START,
+            ConditionId::NONE,
+            ConditionId::NONE,
+            false,
+            &mut next_id,
+            &mut conditions,
+        )?;
+        // A decision with a single condition is already covered by its branch region.
+        (conditions.len() > 1).then(|| Decision { span: peel_drop_temps(cond).span, conditions })
+    }
+
+    /// Collects the conditions of `expr`, which are evaluated starting with the condition `id`,
+    /// and followed by `true_next_id` or `false_next_id` depending on the value of `expr`, or of
+    /// `!expr` if `negated` is set.
+    ///
+    /// The left operand of `&&` and `||` keeps the ID of the whole operation, and the right
+    /// operand gets a new one, the same way Clang numbers the conditions of a decision. Returns
+    /// `None` if `expr` contains a `let` expression, which is matched rather than evaluated as a
+    /// boolean condition.
+    fn collect_conditions(
+        &self,
+        expr: &hir::Expr<'_>,
+        id: ConditionId,
+        true_next_id: ConditionId,
+        false_next_id: ConditionId,
+        negated: bool,
+        next_id: &mut ConditionId,
+        conditions: &mut Vec<Condition>,
+    ) -> Option<()> {
+        let expr = peel_drop_temps(expr);
+        match expr.kind {
+            hir::ExprKind::Binary(op, lhs, rhs) if is_logical_op(expr) => {
+                let rhs_id = *next_id;
+                *next_id = *next_id + 1;
+                // By De Morgan's laws, a negated `&&` evaluates its operands like an `||` of the
+                // negated operands, and the other way around.
+                let is_and = (op.node == hir::BinOpKind::And) != negated;
+                let (lhs_true_next_id, lhs_false_next_id) =
+                    if is_and { (rhs_id, false_next_id) } else { (true_next_id, rhs_id) };
+                self.collect_conditions(
+                    lhs,
+                    id,
+                    lhs_true_next_id,
+                    lhs_false_next_id,
+                    negated,
+                    next_id,
+                    conditions,
+                )?;
+                self.collect_conditions(
+                    rhs,
+                    rhs_id,
+                    true_next_id,
+                    false_next_id,
+                    negated,
+                    next_id,
+                    conditions,
+                )
+            }
+            // A built-in `!` negates its operand, without being a condition itself.
+            hir::ExprKind::Unary(hir::UnOp::Not, operand)
+                if !self.typeck_results.is_method_call(expr) =>
+            {
+                let first = conditions.len();
+                self.collect_conditions(
+                    operand,
+                    id,
+                    true_next_id,
+                    false_next_id,
+                    !negated,
+                    next_id,
+                    conditions,
+                )?;
+                // A `!` applied directly to a condition is reported as part of it.
+                if let [condition] = &mut conditions[first..] {
+                    condition.span = expr.span;
+                }
+                Some(())
+            }
+            hir::ExprKind::Let(..) => None,
+            _ => {
+                conditions.push(Condition {
+                    span: expr.span,
+                    switch_span: expr.span,
+                    negated,
+                    id,
+                    true_next_id,
+                    false_next_id,
+                });
+                Some(())
+            }
+        }
+    }
+}
+
+fn is_logical_op(expr: &hir::Expr<'_>) -> bool {
+    matches!(
+        expr.kind,
+        hir::ExprKind::Binary(
+            hir::BinOp { node: hir::BinOpKind::And | hir::BinOpKind::Or, .. },
+            ..
+        )
+    )
+}
+
+fn peel_drop_temps<'a, 'hir>(mut expr: &'a hir::Expr<'hir>) -> &'a hir::Expr<'hir> {
+    while let hir::ExprKind::DropTemps(inner) = expr.kind {
+        expr = inner;
+    }
+    expr
+}
@@ -2,6 +2,7 @@
 
 mod counters;
 mod graph;
+mod mcdc;
 mod spans;
 
 #[cfg(test)]
@@ -11,9 +12,13 @@
 use self::graph::{BasicCoverageBlock, BasicCoverageBlockData, CoverageGraph};
 use self::spans::CoverageSpans;
 
+use crate::errors::McdcTooManyConditions;
 use crate::MirPass;
 
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::graph::WithNumNodes;
 use rustc_data_structures::sync::Lrc;
+use rustc_index::bit_set::BitSet;
 use rustc_middle::hir;
 use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
 use rustc_middle::mir::coverage::*;
@@ -23,7 +28,7 @@
 };
 use rustc_middle::ty::TyCtxt;
 use rustc_span::def_id::DefId;
-use rustc_span::source_map::SourceMap;
+use rustc_span::source_map::{original_sp, SourceMap};
 use rustc_span::{ExpnKind, SourceFile, Span, Symbol};
 
 /// A simple error message wrapper for `coverage::Error`s.
@@ -38,6 +43,12 @@ pub fn from_string<T>(message: String) -> Result<T, Error> {
     }
 }
 
+/// An MC/DC decision, along with the `SwitchInt` block that evaluates each of its conditions.
+struct McdcDecision {
+    decision: mcdc::Decision,
+    condition_bbs: Vec<BasicBlock>,
+}
+
 /// Inserts `StatementKind::Coverage` statements that either instrument the binary with injected
 /// counters, via intrinsic `llvm.instrprof.increment`, and/or inject metadata used during codegen
 /// to construct the coverage map.
@@ -168,12 +179,46 @@ fn inject_counters(&'a mut self) {
         //
         // Intermediate expressions (used to compute other `Expression` values), which have no
         // direct association with any `BasicCoverageBlock`, are accumulated inside `coverage_counters`.
-        let bcb_has_coverage_spans = |bcb| coverage_spans.bcb_has_coverage_spans(bcb);
+        //
+        // With branch coverage, every branch point also needs counters for all of its outgoing
+        // branches, even if the branching `BasicCoverageBlock` has no coverage spans of its own.
+        // The same goes for the conditions of MC/DC decisions, which get MC/DC branch regions
+        // instead of plain ones.
+        let mut branch_points = if self.tcx.sess.instrument_coverage_branch() {
+            self.find_branch_points()
+        } else {
+            Vec::new()
+        };
+        let mcdc_decisions = if self.tcx.sess.instrument_coverage_mcdc() {
+            self.find_mcdc_decisions()
+        } else {
+            Vec::new()
+        };
+        let mut bcb_is_branch_point = BitSet::new_empty(self.basic_coverage_blocks.num_nodes());
+        for &(bcb, _) in &branch_points {
+            bcb_is_branch_point.insert(bcb);
+        }
+        let mut bcb_is_mcdc_condition = BitSet::new_empty(self.basic_coverage_blocks.num_nodes());
+        for &bb in mcdc_decisions.iter().flat_map(|decision| &decision.condition_bbs) {
+            let bcb = self.basic_coverage_blocks.bcb_from_bb(bb).unwrap();
+            bcb_is_branch_point.insert(bcb);
+            bcb_is_mcdc_condition.insert(bcb);
+        }
+        branch_points.retain(|&(bcb, _)| !bcb_is_mcdc_condition.contains(bcb));
+        let bcb_needs_counter =
+            |bcb| coverage_spans.bcb_has_coverage_spans(bcb) || bcb_is_branch_point.contains(bcb);
         let result = self
             .coverage_counters
-            .make_bcb_counters(&mut self.basic_coverage_blocks, bcb_has_coverage_spans);
+            .make_bcb_counters(&mut self.basic_coverage_blocks, bcb_needs_counter);
 
         if let Ok(()) = result {
+            ////////////////////////////////////////////////////
+            // Inject a `Branch` coverage statement for each branch point. These only refer to the
+            // counters of the branches, so they must be injected before the counters are taken
+            // from `coverage_counters` below.
+            self.inject_branch_regions(&branch_points);
+            let mcdc_bitmap_updates = self.inject_mcdc_decisions(&mcdc_decisions);
+
             ////////////////////////////////////////////////////
             // Remove the counter or edge counter from of each coverage cpan's associated
             // `BasicCoverageBlock`, and inject a `Coverage` statement into the MIR.
@@ -194,6 +239,12 @@ fn inject_counters(&'a mut self) {
             // their own independent code region's coverage.
             self.inject_indirect_counters();
 
+            ////////////////////////////////////////////////////
+            // Inject the MC/DC bitmap updates into new blocks on the outgoing edges of the
+            // conditions. This comes last, because edge counters are injected on the edges that
+            // the `CoverageGraph` was built from.
+            self.inject_mcdc_bitmap_updates(mcdc_bitmap_updates);
+
             // Intermediate expressions will be injected as the final step, after generating
             // debug output, if any.
             ////////////////////////////////////////////////////
@@ -242,6 +293,264 @@ fn inject_coverage_span_counters(&mut self, coverage_spans: &CoverageSpans) {
         }
     }
 
+    /// Finds the BCBs that end in a `SwitchInt` with more than one (reachable) successor, along
+    /// with the span of the condition, scrutinee or `?` operator that the switch tests.
+    fn find_branch_points(&self) -> Vec<(BasicCoverageBlock, Span)> {
+        let body_span = self.body_span;
+        self.basic_coverage_blocks
+            .iter_enumerated()
+            .filter(|&(bcb, _)| self.basic_coverage_blocks.successors[bcb].len() > 1)
+            .filter_map(|(bcb, bcb_data)| {
+                let terminator = bcb_data.terminator(&self.mir_body);
+                let TerminatorKind::SwitchInt { .. } = terminator.kind else { return None };
+                // Branches desugared by the compiler (like `?` or `while`) or expanded from a
+                // macro are reported at the source code they were desugared or expanded from.
+                let span =
+                    original_sp(terminator.source_info.span, body_span).with_ctxt(body_span.ctxt());
+                body_span.contains(span).then_some((bcb, span))
+            })
+            .collect()
+    }
+
+    /// Injects a `Branch` coverage statement for each branch point, counting how many times each
+    /// of its branches was taken.
+    ///
+    /// A two-way branch gets a single branch region. Its "false" side is the first target of the
+    /// `SwitchInt`, which is the `false` block of an `if` or `while`, the first variant tested by
+    /// a `match`, and the `Continue` (no early return) block of a `?`. A branch with more targets,
+    /// such as a `match` with several arms, gets one branch region per target, counting that
+    /// target against all of the others.
+    fn inject_branch_regions(&mut self, branch_points: &[(BasicCoverageBlock, Span)]) {
+        let tcx = self.tcx;
+        let source_map = tcx.sess.source_map();
+        let body_span = self.body_span;
+        let file_name = Symbol::intern(&self.source_file.name.prefer_remapped().to_string_lossy());
+
+        for &(bcb, span) in branch_points {
+            let branch_operands = self.basic_coverage_blocks.successors[bcb]
+                .iter()
+                .map(|&to_bcb| {
+                    self.coverage_counters
+                        .bcb_branch_operand(bcb, to_bcb, &self.basic_coverage_blocks)
+                        .unwrap_or_else(|| bug!("branch {bcb:?}->{to_bcb:?} has no counter"))
+                })
+                .collect::<Vec<_>>();
+
+            let branches = if let &[false_term, true_term] = branch_operands.as_slice() {
+                vec![(true_term, false_term)]
+            } else {
+                let branching_operand = self
+                    .coverage_counters
+                    .bcb_counter(bcb)
+                    .unwrap_or_else(|| bug!("branch point {bcb:?} has no counter"))
+                    .as_operand();
+                branch_operands
+                    .into_iter()
+                    .map(|taken| {
+                        let not_taken = self.coverage_counters.make_intermediate_expression(
+                            branching_operand,
+                            Op::Subtract,
+                            taken,
+                        );
+                        (taken, not_taken)
+                    })
+                    .collect()
+            };
+
+            let code_region = make_code_region(source_map, file_name, span, body_span);
+            for (true_term, false_term) in branches {
+                inject_branch_statement(
+                    self.mir_body,
+                    CoverageKind::Branch { true_term, false_term },
+                    code_region.clone(),
+                );
+            }
+        }
+    }
+
+    /// Finds the MC/DC decisions of the function, and the `SwitchInt` block that evaluates each of
+    /// their conditions. Decisions with conditions that can't be matched to exactly one two-way
+    /// branch point are not instrumented.
+    fn find_mcdc_decisions(&self) -> Vec<McdcDecision> {
+        let tcx = self.tcx;
+        let body_span = self.body_span;
+        let def_id = self.mir_body.source.def_id();
+        let (_, hir_body) = fn_sig_and_body(tcx, def_id);
+        let typeck_results = tcx.typeck(def_id.expect_local());
+
+        // MIR building lowers each condition to a `SwitchInt` on a `bool`, with the span of the
+        // condition.
+        let mut switch_bbs_by_span = FxHashMap::<Span, Vec<BasicBlock>>::default();
+        for (bb, bb_data) in self.mir_body.basic_blocks.iter_enumerated() {
+            let terminator = bb_data.terminator();
+            let TerminatorKind::SwitchInt { targets, .. } = &terminator.kind else { continue };
+            if targets.iter().map(|(value, _)| value).eq([0]) {
+                switch_bbs_by_span.entry(terminator.source_info.span).or_default().push(bb);
+            }
+        }
+        let is_two_way_branch_point = |bb| {
+            self.basic_coverage_blocks
+                .bcb_from_bb(bb)
+                .is_some_and(|bcb| self.basic_coverage_blocks.successors[bcb].len() == 2)
+        };
+
+        mcdc::find_decisions(hir_body, typeck_results)
+            .into_iter()
+            .filter_map(|mut decision| {
+                let num_conditions = decision.conditions.len();
+                if num_conditions > mcdc::MAX_CONDITIONS_PER_DECISION {
+                    tcx.sess.emit_warning(McdcTooManyConditions {
+                        span: decision.span,
+                        num_conditions,
+                        max_conditions: mcdc::MAX_CONDITIONS_PER_DECISION,
+                    });
+                    return None;
+                }
+
+                let condition_bbs = decision
+                    .conditions
+                    .iter()
+                    .map(|condition| {
+                        match switch_bbs_by_span.get(&condition.switch_span)?.as_slice() {
+                            &[bb] if is_two_way_branch_point(bb) => Some(bb),
+                            _ => None,
+                        }
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+
+                // Like branches, decisions and conditions within macros are reported at the
+                // source code they were expanded from.
+                let original_span = |span| original_sp(span, body_span).with_ctxt(body_span.ctxt());
+                decision.span = original_span(decision.span);
+                for condition in &mut decision.conditions {
+                    condition.span = original_span(condition.span);
+                }
+                body_span
+                    .contains(decision.span)
+                    .then_some(McdcDecision { decision, condition_bbs })
+            })
+            .collect()
+    }
+
+    /// Injects the decision region of each MC/DC decision, and an MC/DC branch region for each of
+    /// its conditions, counting how many times the condition was true and false.
+    ///
+    /// Each decision is given one bit per test vector (combination of the values of its
+    /// conditions) in the function's test vector bitmap. Returns the bitmap updates that must be
+    /// injected on the outgoing edges of the conditions, as `(bb, outcome, updates)` tuples: an
+    /// outcome of `true` is the edge taken when the boolean switched on by `bb` is true, which
+    /// is the condition being false if it is negated.
+    fn inject_mcdc_decisions(
+        &mut self,
+        mcdc_decisions: &[McdcDecision],
+    ) -> Vec<(BasicBlock, bool, Vec<CoverageKind>)> {
+        let tcx = self.tcx;
+        let source_map = tcx.sess.source_map();
+        let body_span = self.body_span;
+        let file_name = Symbol::intern(&self.source_file.name.prefer_remapped().to_string_lossy());
+
+        let mut bitmap_bytes = 0;
+        let mut bitmap_updates = Vec::new();
+        for McdcDecision { decision, condition_bbs } in mcdc_decisions {
+            let num_conditions = decision.conditions.len();
+            let bitmap_idx = bitmap_bytes;
+            bitmap_bytes += (1_u32 << num_conditions).div_ceil(8);
+
+            inject_branch_statement(
+                self.mir_body,
+                CoverageKind::MCDCDecision { bitmap_idx, num_conditions: num_conditions as u16 },
+                make_code_region(source_map, file_name, decision.span, body_span),
+            );
+
+            for (condition, &bb) in std::iter::zip(&decision.conditions, condition_bbs) {
+                let bcb = self.basic_coverage_blocks.bcb_from_bb(bb).unwrap();
+                let TerminatorKind::SwitchInt { targets, .. } =
+                    &self.mir_body[bb].terminator().kind
+                else {
+                    bug!("MC/DC condition {bb:?} doesn't end in a `SwitchInt`");
+                };
+                let mcdc::Condition { span, negated, id, true_next_id, false_next_id, .. } =
+                    *condition;
+                let mut condition_targets = [targets.otherwise(), targets.target_for_value(0)];
+                if negated {
+                    condition_targets.reverse();
+                }
+                let [true_term, false_term] = condition_targets.map(|to_bb| {
+                    let to_bcb = self.basic_coverage_blocks.bcb_from_bb(to_bb);
+                    to_bcb
+                        .and_then(|to_bcb| {
+                            self.coverage_counters.bcb_branch_operand(
+                                bcb,
+                                to_bcb,
+                                &self.basic_coverage_blocks,
+                            )
+                        })
+                        .unwrap_or_else(|| bug!("branch {bb:?}->{to_bb:?} has no counter"))
+                });
+
+                inject_branch_statement(
+                    self.mir_body,
+                    CoverageKind::MCDCBranch {
+                        true_term,
+                        false_term,
+                        id,
+                        true_next_id,
+                        false_next_id,
+                    },
+                    make_code_region(source_map, file_name, span, body_span),
+                );
+
+                // The bit of a condition is only set when it is true. Once the outcome of the
+                // decision is known, the accumulated test vector is recorded.
+                let mut true_updates = vec![CoverageKind::CondBitmapUpdate { id }];
+                if true_next_id == ConditionId::NONE {
+                    true_updates.push(CoverageKind::TestVectorBitmapUpdate { bitmap_idx });
+                }
+                bitmap_updates.push((bb, !negated, true_updates));
+                if false_next_id == ConditionId::NONE {
+                    let false_updates = vec![CoverageKind::TestVectorBitmapUpdate { bitmap_idx }];
+                    bitmap_updates.push((bb, negated, false_updates));
+                }
+            }
+        }
+
+        if !mcdc_decisions.is_empty() {
+            inject_statement(
+                self.mir_body,
+                CoverageKind::MCDCParameters {
+                    function_source_hash: self.function_source_hash,
+                    bitmap_bytes,
+                },
+                mir::START_BLOCK,
+                Vec::new(),
+            );
+        }
+
+        bitmap_updates
+    }
+
+    /// Injects the MC/DC bitmap updates returned by `inject_mcdc_decisions()` into a new block on
+    /// the corresponding outgoing edge of each condition. The successors of a condition may be
+    /// shared with other conditions (such as the `else` block of an `if`), so the updates can't
+    /// be injected into the successors themselves.
+    fn inject_mcdc_bitmap_updates(
+        &mut self,
+        bitmap_updates: Vec<(BasicBlock, bool, Vec<CoverageKind>)>,
+    ) {
+        for (bb, outcome, updates) in bitmap_updates {
+            let TerminatorKind::SwitchInt { targets, .. } = &self.mir_body[bb].terminator().kind
+            else {
+                bug!("MC/DC condition {bb:?} doesn't end in a `SwitchInt`");
+            };
+            let to_bb = if outcome { targets.otherwise() } else { targets.target_for_value(0) };
+            let new_bb = inject_edge_counter_basic_block(self.mir_body, bb, to_bb);
+            // Statements are injected at the start of the block, so inject the last one first.
+            for update in updates.into_iter().rev() {
+                inject_statement(self.mir_body, update, new_bb, Vec::new());
+            }
+        }
+    }
+
     /// At this point, any BCB with coverage counters has already had its counter injected
     /// into MIR, and had its counter removed from `coverage_counters` (via `take_counter()`).
     ///
@@ -389,6 +698,32 @@ fn inject_intermediate_expression(mir_body: &mut mir::Body<'_>, expression: Cove
     data.statements.push(statement);
 }
 
+// Like expressions, branches and MC/DC decisions are injected into the coverage map without
+// generating executable code.
+fn inject_branch_statement(
+    mir_body: &mut mir::Body<'_>,
+    branch: CoverageKind,
+    code_region: CodeRegion,
+) {
+    debug_assert!(matches!(
+        branch,
+        CoverageKind::Branch { .. }
+            | CoverageKind::MCDCBranch { .. }
+            | CoverageKind::MCDCDecision { .. }
+    ));
+    debug!("  injecting branch {:?} at code region {:?}", branch, code_region);
+    let data = &mut mir_body[mir::START_BLOCK];
+    let source_info = data.terminator().source_info;
+    let statement = Statement {
+        source_info,
+        kind: StatementKind::Coverage(Box::new(Coverage {
+            kind: branch,
+            code_regions: vec![code_region],
+        })),
+    };
+    data.statements.push(statement);
+}
+
 /// Convert the Span into its file name, start line and column, and end line and column
 fn make_code_region(
     source_map: &SourceMap,
@@ -68,7 +68,16 @@ fn visit_coverage(&mut self, coverage: &Coverage) {
                 self.update_from_expression_operand(lhs);
                 self.update_from_expression_operand(rhs);
             }
-            CoverageKind::Unreachable => {}
+            CoverageKind::Branch { true_term, false_term }
+            | CoverageKind::MCDCBranch { true_term, false_term, .. } => {
+                self.update_from_expression_operand(true_term);
+                self.update_from_expression_operand(false_term);
+            }
+            CoverageKind::MCDCParameters { .. }
+            | CoverageKind::MCDCDecision { .. }
+            | CoverageKind::CondBitmapUpdate { .. }
+            | CoverageKind::TestVectorBitmapUpdate { .. }
+            | CoverageKind::Unreachable => {}
         }
     }
 }
@@ -93,6 +102,16 @@ fn coverageinfo<'tcx>(tcx: TyCtxt<'tcx>, instance_def: ty::InstanceDef<'tcx>) ->
 fn covered_code_regions(tcx: TyCtxt<'_>, def_id: DefId) -> Vec<&CodeRegion> {
     let body = mir_body(tcx, def_id);
     all_coverage_in_mir_body(body)
+        // Branch and decision regions can't be represented by the plain code regions reported
+        // for unused functions, and are already covered by the code regions around them.
+        .filter(|coverage| {
+            !matches!(
+                coverage.kind,
+                CoverageKind::Branch { .. }
+                    | CoverageKind::MCDCBranch { .. }
+                    | CoverageKind::MCDCDecision { .. }
+            )
+        })
         // Coverage statements have a list of code regions (possibly empty).
         .flat_map(|coverage| coverage.code_regions.as_slice())
         .collect()
@@ -38,6 +38,16 @@ pub(crate) struct UnalignedPackedRef {
     pub span: Span,
 }
 
+#[derive(Diagnostic)]
+#[diag(mir_transform_mcdc_too_many_conditions)]
+#[note]
+pub(crate) struct McdcTooManyConditions {
+    #[primary_span]
+    pub span: Span,
+    pub num_conditions: usize,
+    pub max_conditions: usize,
+}
+
 #[derive(LintDiagnostic)]
 #[diag(mir_transform_unused_unsafe)]
 pub(crate) struct UnusedUnsafe {
@@ -6,6 +6,7 @@
 use rustc_index::bit_set::BitSet;
 use rustc_index::Idx;
 use rustc_middle::middle::codegen_fn_attrs::{CodegenFnAttrFlags, CodegenFnAttrs};
+use rustc_middle::mir::coverage::CoverageKind;
 use rustc_middle::mir::visit::*;
 use rustc_middle::mir::*;
 use rustc_middle::ty::TypeVisitableExt;
@@ -459,6 +460,16 @@ fn check_mir_body(
     ) -> Result<(), &'static str> {
         let tcx = self.tcx;
 
+        // The MC/DC condition bitmap of a function lives in its own stack frame, so its
+        // instrumentation must stay in its own function.
+        let has_mcdc = callee_body.basic_blocks[START_BLOCK].statements.iter().any(|statement| {
+            let StatementKind::Coverage(coverage) = &statement.kind else { return false };
+            matches!(coverage.kind, CoverageKind::MCDCParameters { .. })
+        });
+        if has_mcdc {
+            return Err("callee has MC/DC coverage instrumentation");
+        }
+
         let mut threshold = if callee_attrs.requests_inline() {
             self.tcx.sess.opts.unstable_opts.inline_mir_hint_threshold.unwrap_or(100)
         } else {
@@ -1,9 +1,15 @@
 monomorphize_consider_type_length_limit =
     consider adding a `#![type_length_limit="{$type_length}"]` attribute to your crate
 
+monomorphize_couldnt_dump_mono_call_graph =
+    unexpected error occurred while dumping the monomorphization call graph: {$error}
+
 monomorphize_couldnt_dump_mono_stats =
     unexpected error occurred while dumping monomorphization stats: {$error}
 
+monomorphize_couldnt_dump_reachable_panics =
+    unexpected error occurred while writing the reachable panics report: {$error}
+
 monomorphize_encountered_error_while_instantiating =
     the above error was encountered while instantiating `{$formatted_item}`
 
@@ -16,6 +22,9 @@ monomorphize_no_optimized_mir =
     missing optimized MIR for an item in the crate `{$crate_name}`
     .note = missing optimized MIR for this item (was the crate `{$crate_name}` compiled with `--emit=metadata`?)
 
+monomorphize_reachable_panic = a panic is reachable from `{$entry}`
+    .note = reached through {$chain}
+
 monomorphize_recursion_limit =
     reached the recursion limit while instantiating `{$shrunk}`
     .note = `{$def_path_str}` defined here
@@ -24,9 +33,18 @@ monomorphize_symbol_already_defined = symbol `{$symbol}` is already defined
 
 monomorphize_type_length_limit = reached the type-length limit while instantiating `{$shrunk}`
 
+monomorphize_unanalyzed_reachable_function =
+    `{$function}` is reachable from `{$entry}`, but the panics within it can't be analyzed
+    .note = reached through {$chain}
+    .help = the function is not codegened in this crate, so it is not known whether it panics
+
 monomorphize_unknown_cgu_collection_mode =
     unknown codegen-item collection mode '{$mode}', falling back to 'lazy' mode
 
+monomorphize_unknown_panic_entry =
+    no function `{$entry}` was found to check for reachable panics
+    .note = the function must be codegened in this crate, and be named by its full path, starting with the crate name
+
 monomorphize_unknown_partition_strategy = unknown partitioning strategy
 
 monomorphize_unused_generic_params = item has unused generic parameters
@@ -0,0 +1,242 @@
+//! Writes the mono item graph built by the collector, for `-Z dump-mono-call-graph`.
+//!
+//! The nodes of the graph are the mono items of the crate, and its edges are the uses found by
+//! the collector: direct calls, methods and drop glue referenced by vtables, drop glue called
+//! directly, functions escaping as function pointers, and references to statics. The functions of
+//! upstream crates that are used without being codegened locally are external nodes, without
+//! edges of their own. The graph is written both as JSON, with the def path, generic arguments
+//! and spans of every item, and as a Graphviz DOT file.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::ty::print::{with_crate_prefix, with_no_trimmed_paths};
+use rustc_middle::ty::{GenericArgKind, Instance, InstanceDef, TyCtxt};
+use rustc_span::{Span, Symbol};
+
+use crate::collector::UsageMap;
+
+#[derive(serde::Serialize)]
+struct Node {
+    id: usize,
+    name: String,
+    kind: &'static str,
+    def_path: String,
+    generic_args: Vec<String>,
+    symbol: String,
+    span: Option<String>,
+    root: bool,
+    /// Whether this is a function of an upstream crate, which is not codegened in this crate.
+    external: bool,
+}
+
+#[derive(serde::Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct Edge {
+    from: usize,
+    to: usize,
+    kind: &'static str,
+    span: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct CallGraph {
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+/// Outputs the mono item graph of the crate as `<crate>.mono_call_graph.json` and
+/// `<crate>.mono_call_graph.dot`, in the given output directory.
+pub(crate) fn dump_call_graph<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    usage_map: &UsageMap<'tcx>,
+    output_directory: &Option<PathBuf>,
+    crate_name: Symbol,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_directory = if let Some(ref directory) = output_directory {
+        fs::create_dir_all(directory)?;
+        directory
+    } else {
+        Path::new(".")
+    };
+
+    let call_graph = build_call_graph(tcx, items, usage_map);
+
+    let json_path = output_directory.join(format!("{crate_name}.mono_call_graph.json"));
+    let mut file = BufWriter::new(File::create(&json_path)?);
+    serde_json::to_writer(&mut file, &call_graph)?;
+    file.flush()?;
+
+    let dot_path = output_directory.join(format!("{crate_name}.mono_call_graph.dot"));
+    let mut file = BufWriter::new(File::create(&dot_path)?);
+    write_dot(&mut file, &call_graph, crate_name)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+fn build_call_graph<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    usage_map: &UsageMap<'tcx>,
+) -> CallGraph {
+    // Number the items in a stable order, sorted by name.
+    let mut items: Vec<_> =
+        items.iter().map(|&item| (with_no_trimmed_paths!(item.to_string()), item)).collect();
+    items.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let ids: FxHashMap<_, _> =
+        items.iter().enumerate().map(|(id, &(_, item))| (item, id)).collect();
+    let roots: FxHashSet<_> = usage_map.roots().iter().copied().collect();
+
+    // The external functions are numbered after the mono items, also sorted by name.
+    let mut external: Vec<_> = items
+        .iter()
+        .flat_map(|&(_, item)| usage_map.get_external_used_items(item))
+        .map(|(instance, _)| instance.node)
+        .collect::<FxHashSet<_>>()
+        .into_iter()
+        .map(|instance| (with_no_trimmed_paths!(instance.to_string()), instance))
+        .collect();
+    external.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let external_ids: FxHashMap<_, _> = external
+        .iter()
+        .enumerate()
+        .map(|(id, &(_, instance))| (instance, ids.len() + id))
+        .collect();
+
+    let mut edges = Vec::new();
+    for (from, &(_, item)) in items.iter().enumerate() {
+        for &(used_item, kind) in usage_map.get_used_items(item) {
+            edges.push(Edge {
+                from,
+                to: ids[&used_item.node],
+                kind: kind.as_str(),
+                span: span_to_string(tcx, used_item.span),
+            });
+        }
+        for &(instance, kind) in usage_map.get_external_used_items(item) {
+            edges.push(Edge {
+                from,
+                to: external_ids[&instance.node],
+                kind: kind.as_str(),
+                span: span_to_string(tcx, instance.span),
+            });
+        }
+    }
+    edges.sort();
+    edges.dedup();
+
+    let mut nodes: Vec<_> = items
+        .into_iter()
+        .enumerate()
+        .map(|(id, (name, item))| {
+            let def_id = item.def_id();
+            let (kind, generic_args) = match item {
+                MonoItem::Fn(instance) => {
+                    (instance_kind_name(instance.def), generic_args(instance))
+                }
+                MonoItem::Static(_) => ("static", Vec::new()),
+                MonoItem::GlobalAsm(_) => ("global_asm", Vec::new()),
+            };
+            Node {
+                id,
+                name,
+                kind,
+                def_path: with_no_trimmed_paths!(with_crate_prefix!(tcx.def_path_str(def_id))),
+                generic_args,
+                symbol: item.symbol_name(tcx).name.to_string(),
+                span: span_to_string(tcx, tcx.def_span(def_id)),
+                root: roots.contains(&item),
+                external: false,
+            }
+        })
+        .collect();
+    let external = external.into_iter().enumerate().map(|(id, (name, instance))| {
+        let def_id = instance.def_id();
+        Node {
+            id: ids.len() + id,
+            name,
+            kind: instance_kind_name(instance.def),
+            def_path: with_no_trimmed_paths!(with_crate_prefix!(tcx.def_path_str(def_id))),
+            generic_args: generic_args(instance),
+            symbol: tcx.symbol_name(instance).name.to_string(),
+            span: span_to_string(tcx, tcx.def_span(def_id)),
+            root: false,
+            external: true,
+        }
+    });
+    nodes.extend(external);
+
+    CallGraph { nodes, edges }
+}
+
+fn generic_args(instance: Instance<'_>) -> Vec<String> {
+    instance
+        .args
+        .iter()
+        .filter(|arg| !matches!(arg.unpack(), GenericArgKind::Lifetime(_)))
+        .map(|arg| with_no_trimmed_paths!(arg.to_string()))
+        .collect()
+}
+
+fn write_dot(
+    out: &mut impl Write,
+    call_graph: &CallGraph,
+    crate_name: Symbol,
+) -> std::io::Result<()> {
+    writeln!(out, "digraph \"{}\" {{", dot_escape(crate_name.as_str()))?;
+    writeln!(out, "    node [shape=box];")?;
+    for node in &call_graph.nodes {
+        let style = match (node.root, node.external) {
+            (true, _) => ", style=bold",
+            (false, true) => ", style=dashed",
+            (false, false) => "",
+        };
+        writeln!(out, "    n{} [label=\"{}\"{style}];", node.id, dot_escape(&node.name))?;
+    }
+    // The edges are sorted, so repeated uses of an item with the same kind are adjacent, and are
+    // drawn as a single edge.
+    let mut last = None;
+    for edge in &call_graph.edges {
+        if last == Some((edge.from, edge.to, edge.kind)) {
+            continue;
+        }
+        last = Some((edge.from, edge.to, edge.kind));
+        let attributes = match edge.kind {
+            "call" => "",
+            "drop" => " [style=dashed, label=\"drop\"]",
+            "vtable_method" => " [style=dashed, label=\"vtable\"]",
+            "fn_pointer" => " [style=dotted, label=\"fn ptr\"]",
+            _ => " [style=dotted, label=\"ref\"]",
+        };
+        writeln!(out, "    n{} -> n{}{attributes};", edge.from, edge.to)?;
+    }
+    writeln!(out, "}}")
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn instance_kind_name(def: InstanceDef<'_>) -> &'static str {
+    match def {
+        InstanceDef::Item(_) => "fn",
+        InstanceDef::Intrinsic(_) => "intrinsic",
+        InstanceDef::VTableShim(_) => "vtable_shim",
+        InstanceDef::ReifyShim(_) => "reify_shim",
+        InstanceDef::FnPtrShim(..) => "fn_ptr_shim",
+        InstanceDef::Virtual(..) => "virtual",
+        InstanceDef::ClosureOnceShim { .. } => "closure_once_shim",
+        InstanceDef::ThreadLocalShim(_) => "thread_local_shim",
+        InstanceDef::DropGlue(..) => "drop_glue",
+        InstanceDef::CloneShim(..) => "clone_shim",
+        InstanceDef::FnPtrAddrShim(..) => "fn_ptr_addr_shim",
+    }
+}
+
+pub(crate) fn span_to_string(tcx: TyCtxt<'_>, span: Span) -> Option<String> {
+    (!span.is_dummy()).then(|| tcx.sess.source_map().span_to_embeddable_string(span))
+}
@@ -202,34 +202,118 @@ pub enum MonoItemCollectionMode {
     Lazy,
 }
 
+/// How a mono item is used by another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UseKind {
+    /// A direct call, including the calls to the panic lang items inserted for MIR terminators.
+    Call,
+    /// The drop glue of a type, dropped directly or through a vtable.
+    Drop,
+    /// A method of a vtable, which may be called through a trait object.
+    VtableMethod,
+    /// A function whose address escapes, as a function pointer, in constant data, or in a `sym`
+    /// operand.
+    FnPointer,
+    /// Any other use, such as a reference to a static.
+    Reference,
+}
+
+impl UseKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UseKind::Call => "call",
+            UseKind::Drop => "drop",
+            UseKind::VtableMethod => "vtable_method",
+            UseKind::FnPointer => "fn_pointer",
+            UseKind::Reference => "reference",
+        }
+    }
+}
+
 pub struct UsageMap<'tcx> {
-    // Maps every mono item to the mono items used by it.
-    used_map: FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
+    // The roots of the mono item graph.
+    roots: Vec<MonoItem<'tcx>>,
+
+    // Maps every mono item to its uses of other mono items.
+    used_map: FxHashMap<MonoItem<'tcx>, Vec<(Spanned<MonoItem<'tcx>>, UseKind)>>,
+
+    // Maps every mono item to its uses of functions that are not codegened in this crate. Only
+    // filled for `-Z dump-mono-call-graph`.
+    external_map: FxHashMap<MonoItem<'tcx>, Vec<(Spanned<Instance<'tcx>>, UseKind)>>,
 
     // Maps every mono item to the mono items that use it.
     user_map: FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
 }
 
-type MonoItems<'tcx> = Vec<Spanned<MonoItem<'tcx>>>;
+/// The uses of mono items found while visiting an item.
+struct MonoItems<'tcx> {
+    /// Whether the uses of functions of upstream crates are recorded, for the mono item graph
+    /// written by `-Z dump-mono-call-graph`.
+    record_external: bool,
+    items: Vec<(Spanned<MonoItem<'tcx>>, UseKind)>,
+    external: Vec<(Spanned<Instance<'tcx>>, UseKind)>,
+}
+
+impl<'tcx> MonoItems<'tcx> {
+    fn new(tcx: TyCtxt<'tcx>) -> MonoItems<'tcx> {
+        MonoItems {
+            record_external: tcx.sess.opts.unstable_opts.dump_mono_call_graph.enabled(),
+            items: Vec::new(),
+            external: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, item: Spanned<MonoItem<'tcx>>, kind: UseKind) {
+        self.items.push((item, kind));
+    }
+
+    /// Records the use of a function that is not codegened in this crate, because it is linked
+    /// to from an upstream crate or is a foreign item.
+    fn push_external(&mut self, instance: Spanned<Instance<'tcx>>, kind: UseKind) {
+        if self.record_external {
+            self.external.push((instance, kind));
+        }
+    }
+}
 
 impl<'tcx> UsageMap<'tcx> {
-    fn new() -> UsageMap<'tcx> {
-        UsageMap { used_map: FxHashMap::default(), user_map: FxHashMap::default() }
+    fn new(roots: Vec<MonoItem<'tcx>>) -> UsageMap<'tcx> {
+        UsageMap {
+            roots,
+            used_map: FxHashMap::default(),
+            external_map: FxHashMap::default(),
+            user_map: FxHashMap::default(),
+        }
     }
 
-    fn record_used<'a>(
-        &mut self,
-        user_item: MonoItem<'tcx>,
-        used_items: &'a [Spanned<MonoItem<'tcx>>],
-    ) where
-        'tcx: 'a,
-    {
-        let used_items: Vec<_> = used_items.iter().map(|item| item.node).collect();
-        for &used_item in used_items.iter() {
-            self.user_map.entry(used_item).or_default().push(user_item);
+    fn record_used(&mut self, user_item: MonoItem<'tcx>, used_items: &MonoItems<'tcx>) {
+        for &(used_item, _) in used_items.items.iter() {
+            self.user_map.entry(used_item.node).or_default().push(user_item);
         }
 
-        assert!(self.used_map.insert(user_item, used_items).is_none());
+        assert!(self.used_map.insert(user_item, used_items.items.clone()).is_none());
+        if !used_items.external.is_empty() {
+            self.external_map.insert(user_item, used_items.external.clone());
+        }
+    }
+
+    pub fn roots(&self) -> &[MonoItem<'tcx>] {
+        &self.roots
+    }
+
+    /// Returns the uses of other mono items by `item`, with the span and the kind of each use.
+    /// An item used several times is listed once per use.
+    pub fn get_used_items(&self, item: MonoItem<'tcx>) -> &[(Spanned<MonoItem<'tcx>>, UseKind)] {
+        self.used_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the uses by `item` of functions that are not codegened in this crate, which are
+    /// only recorded for `-Z dump-mono-call-graph`.
+    pub fn get_external_used_items(
+        &self,
+        item: MonoItem<'tcx>,
+    ) -> &[(Spanned<Instance<'tcx>>, UseKind)] {
+        self.external_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
     }
 
     pub fn get_user_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
@@ -242,10 +326,10 @@ pub fn for_each_inlined_used_item<F>(&self, tcx: TyCtxt<'tcx>, item: MonoItem<'t
         F: FnMut(MonoItem<'tcx>),
     {
         let used_items = self.used_map.get(&item).unwrap();
-        for used_item in used_items.iter() {
-            let is_inlined = used_item.instantiation_mode(tcx) == InstantiationMode::LocalCopy;
+        for &(used_item, _) in used_items.iter() {
+            let is_inlined = used_item.node.instantiation_mode(tcx) == InstantiationMode::LocalCopy;
             if is_inlined {
-                f(*used_item);
+                f(used_item.node);
             }
         }
     }
@@ -264,7 +348,7 @@ pub fn collect_crate_mono_items(
     debug!("building mono item graph, beginning at roots");
 
     let mut visited = MTLock::new(FxHashSet::default());
-    let mut usage_map = MTLock::new(UsageMap::new());
+    let mut usage_map = MTLock::new(UsageMap::new(roots.clone()));
     let recursion_limit = tcx.recursion_limit();
 
     {
@@ -294,7 +378,7 @@ pub fn collect_crate_mono_items(
 #[instrument(skip(tcx, mode), level = "debug")]
 fn collect_roots(tcx: TyCtxt<'_>, mode: MonoItemCollectionMode) -> Vec<MonoItem<'_>> {
     debug!("collecting roots");
-    let mut roots = Vec::new();
+    let mut roots = MonoItems::new(tcx);
 
     {
         let entry_fn = tcx.entry_fn(());
@@ -320,8 +404,9 @@ fn collect_roots(tcx: TyCtxt<'_>, mode: MonoItemCollectionMode) -> Vec<MonoItem<
     // whose predicates hold. Luckily, items that aren't instantiable
     // can't actually be used, so we can just skip codegenning them.
     roots
+        .items
         .into_iter()
-        .filter_map(|Spanned { node: mono_item, .. }| {
+        .filter_map(|(Spanned { node: mono_item, .. }, _)| {
             mono_item.is_instantiable(tcx).then_some(mono_item)
         })
         .collect()
@@ -343,7 +428,7 @@ fn collect_items_rec<'tcx>(
         return;
     }
 
-    let mut used_items = Vec::new();
+    let mut used_items = MonoItems::new(tcx);
     let recursion_depth_reset;
 
     // Post-monomorphization errors MVP
@@ -390,13 +475,16 @@ fn collect_items_rec<'tcx>(
             }
 
             if tcx.needs_thread_local_shim(def_id) {
-                used_items.push(respan(
-                    starting_item.span,
-                    MonoItem::Fn(Instance {
-                        def: InstanceDef::ThreadLocalShim(def_id),
-                        args: GenericArgs::empty(),
-                    }),
-                ));
+                used_items.push(
+                    respan(
+                        starting_item.span,
+                        MonoItem::Fn(Instance {
+                            def: InstanceDef::ThreadLocalShim(def_id),
+                            args: GenericArgs::empty(),
+                        }),
+                    ),
+                    UseKind::Reference,
+                );
             }
         }
         MonoItem::Fn(instance) => {
@@ -438,7 +526,10 @@ fn collect_items_rec<'tcx>(
                             let instance = Instance::mono(tcx, *def_id);
                             if should_codegen_locally(tcx, &instance) {
                                 trace!("collecting static {:?}", def_id);
-                                used_items.push(dummy_spanned(MonoItem::Static(*def_id)));
+                                used_items.push(
+                                    dummy_spanned(MonoItem::Static(*def_id)),
+                                    UseKind::Reference,
+                                );
                             }
                         }
                         hir::InlineAsmOperand::In { .. }
@@ -469,7 +560,7 @@ fn collect_items_rec<'tcx>(
     }
     usage_map.lock_mut().record_used(starting_item.node, &used_items);
 
-    for used_item in used_items {
+    for (used_item, _) in used_items.items {
         collect_items_rec(tcx, used_item, visited, recursion_depths, recursion_limit, usage_map);
     }
 
@@ -722,7 +813,10 @@ fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
                         )
                         .expect("failed to normalize and resolve closure during codegen");
                         if should_codegen_locally(self.tcx, &instance) {
-                            self.output.push(create_fn_mono_item(self.tcx, instance, span));
+                            self.output.push(
+                                create_fn_mono_item(self.tcx, instance, span),
+                                UseKind::FnPointer,
+                            );
                         }
                     }
                     _ => bug!(),
@@ -733,7 +827,7 @@ fn visit_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, location: Location) {
                 let instance = Instance::mono(self.tcx, def_id);
                 if should_codegen_locally(self.tcx, &instance) {
                     trace!("collecting thread-local static {:?}", def_id);
-                    self.output.push(respan(span, MonoItem::Static(def_id)));
+                    self.output.push(respan(span, MonoItem::Static(def_id)), UseKind::Reference);
                 }
             }
             _ => { /* not interesting */ }
@@ -769,9 +863,7 @@ fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Loc
         let tcx = self.tcx;
         let push_mono_lang_item = |this: &mut Self, lang_item: LangItem| {
             let instance = Instance::mono(tcx, tcx.require_lang_item(lang_item, Some(source)));
-            if should_codegen_locally(tcx, &instance) {
-                this.output.push(create_fn_mono_item(tcx, instance, source));
-            }
+            visit_instance_use(tcx, instance, true, UseKind::Call, source, this.output);
         };
 
         match terminator.kind {
@@ -803,7 +895,10 @@ fn visit_terminator(&mut self, terminator: &mir::Terminator<'tcx>, location: Loc
                             let instance = Instance::mono(self.tcx, def_id);
                             if should_codegen_locally(self.tcx, &instance) {
                                 trace!("collecting asm sym static {:?}", def_id);
-                                self.output.push(respan(source, MonoItem::Static(def_id)));
+                                self.output.push(
+                                    respan(source, MonoItem::Static(def_id)),
+                                    UseKind::Reference,
+                                );
                             }
                         }
                         _ => {}
@@ -864,7 +959,7 @@ fn visit_drop_use<'tcx>(
     output: &mut MonoItems<'tcx>,
 ) {
     let instance = Instance::resolve_drop_in_place(tcx, ty);
-    visit_instance_use(tcx, instance, is_direct_call, source, output);
+    visit_instance_use(tcx, instance, is_direct_call, UseKind::Drop, source, output);
 }
 
 fn visit_fn_use<'tcx>(
@@ -886,7 +981,8 @@ fn visit_fn_use<'tcx>(
                 _ => bug!("failed to resolve instance for {ty}"),
             }
         };
-        visit_instance_use(tcx, instance, is_direct_call, source, output);
+        let kind = if is_direct_call { UseKind::Call } else { UseKind::FnPointer };
+        visit_instance_use(tcx, instance, is_direct_call, kind, source, output);
     }
     skip_move_size_check
 }
@@ -895,11 +991,13 @@ fn visit_instance_use<'tcx>(
     tcx: TyCtxt<'tcx>,
     instance: ty::Instance<'tcx>,
     is_direct_call: bool,
+    kind: UseKind,
     source: Span,
     output: &mut MonoItems<'tcx>,
 ) {
     debug!("visit_item_use({:?}, is_direct_call={:?})", instance, is_direct_call);
     if !should_codegen_locally(tcx, &instance) {
+        output.push_external(respan(source, instance), kind);
         return;
     }
 
@@ -915,7 +1013,7 @@ fn visit_instance_use<'tcx>(
         ty::InstanceDef::DropGlue(_, None) => {
             // Don't need to emit noop drop glue if we are calling directly.
             if !is_direct_call {
-                output.push(create_fn_mono_item(tcx, instance, source));
+                output.push(create_fn_mono_item(tcx, instance, source), kind);
             }
         }
         ty::InstanceDef::DropGlue(_, Some(_))
@@ -926,7 +1024,7 @@ fn visit_instance_use<'tcx>(
         | ty::InstanceDef::FnPtrShim(..)
         | ty::InstanceDef::CloneShim(..)
         | ty::InstanceDef::FnPtrAddrShim(..) => {
-            output.push(create_fn_mono_item(tcx, instance, source));
+            output.push(create_fn_mono_item(tcx, instance, source), kind);
         }
     }
 }
@@ -1108,23 +1206,26 @@ fn create_mono_items_for_vtable_methods<'tcx>(
 
             // Walk all methods of the trait, including those of its supertraits
             let entries = tcx.vtable_entries(poly_trait_ref);
-            let methods = entries
-                .iter()
-                .filter_map(|entry| match entry {
+            for entry in entries {
+                match *entry {
                     VtblEntry::MetadataDropInPlace
                     | VtblEntry::MetadataSize
                     | VtblEntry::MetadataAlign
-                    | VtblEntry::Vacant => None,
+                    | VtblEntry::Vacant => {}
                     VtblEntry::TraitVPtr(_) => {
                         // all super trait items already covered, so skip them.
-                        None
+                    }
+                    VtblEntry::Method(instance) if should_codegen_locally(tcx, &instance) => {
+                        output.push(
+                            create_fn_mono_item(tcx, instance, source),
+                            UseKind::VtableMethod,
+                        );
                     }
                     VtblEntry::Method(instance) => {
-                        Some(*instance).filter(|instance| should_codegen_locally(tcx, instance))
+                        output.push_external(respan(source, instance), UseKind::VtableMethod);
                     }
-                })
-                .map(|item| create_fn_mono_item(tcx, item, source));
-            output.extend(methods);
+                }
+            }
         }
 
         // Also add the destructor.
@@ -1161,12 +1262,12 @@ fn process_item(&mut self, id: hir::ItemId) {
                     "RootCollector: ItemKind::GlobalAsm({})",
                     self.tcx.def_path_str(id.owner_id)
                 );
-                self.output.push(dummy_spanned(MonoItem::GlobalAsm(id)));
+                self.output.push(dummy_spanned(MonoItem::GlobalAsm(id)), UseKind::Reference);
             }
             DefKind::Static(..) => {
                 let def_id = id.owner_id.to_def_id();
                 debug!("RootCollector: ItemKind::Static({})", self.tcx.def_path_str(def_id));
-                self.output.push(dummy_spanned(MonoItem::Static(def_id)));
+                self.output.push(dummy_spanned(MonoItem::Static(def_id)), UseKind::Reference);
             }
             DefKind::Const => {
                 // const items only generate mono items if they are
@@ -1219,7 +1320,7 @@ fn push_if_root(&mut self, def_id: LocalDefId) {
             debug!("found root");
 
             let instance = Instance::mono(self.tcx, def_id.to_def_id());
-            self.output.push(create_fn_mono_item(self.tcx, instance, DUMMY_SP));
+            self.output.push(create_fn_mono_item(self.tcx, instance, DUMMY_SP), UseKind::Reference);
         }
     }
 
@@ -1255,7 +1356,8 @@ fn push_extra_entry_roots(&mut self) {
         .unwrap()
         .unwrap();
 
-        self.output.push(create_fn_mono_item(self.tcx, start_instance, DUMMY_SP));
+        self.output
+            .push(create_fn_mono_item(self.tcx, start_instance, DUMMY_SP), UseKind::Reference);
     }
 }
 
@@ -1333,7 +1435,7 @@ fn create_mono_items_for_default_impls<'tcx>(
 
         let mono_item = create_fn_mono_item(tcx, instance, DUMMY_SP);
         if mono_item.node.is_instantiable(tcx) && should_codegen_locally(tcx, &instance) {
-            output.push(mono_item);
+            output.push(mono_item, UseKind::Reference);
         }
     }
 }
@@ -1346,7 +1448,7 @@ fn collect_alloc<'tcx>(tcx: TyCtxt<'tcx>, alloc_id: AllocId, output: &mut MonoIt
             let instance = Instance::mono(tcx, def_id);
             if should_codegen_locally(tcx, &instance) {
                 trace!("collecting static {:?}", def_id);
-                output.push(dummy_spanned(MonoItem::Static(def_id)));
+                output.push(dummy_spanned(MonoItem::Static(def_id)), UseKind::Reference);
             }
         }
         GlobalAlloc::Memory(alloc) => {
@@ -1360,7 +1462,9 @@ fn collect_alloc<'tcx>(tcx: TyCtxt<'tcx>, alloc_id: AllocId, output: &mut MonoIt
         GlobalAlloc::Function(fn_instance) => {
             if should_codegen_locally(tcx, &fn_instance) {
                 trace!("collecting {:?} with {:#?}", alloc_id, fn_instance);
-                output.push(create_fn_mono_item(tcx, fn_instance, DUMMY_SP));
+                output.push(create_fn_mono_item(tcx, fn_instance, DUMMY_SP), UseKind::FnPointer);
+            } else {
+                output.push_external(dummy_spanned(fn_instance), UseKind::FnPointer);
             }
         }
         GlobalAlloc::VTable(ty, trait_ref) => {
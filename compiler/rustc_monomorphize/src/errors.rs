@@ -87,12 +87,53 @@ pub struct SymbolAlreadyDefined {
     pub symbol: String,
 }
 
+#[derive(Diagnostic)]
+#[diag(monomorphize_couldnt_dump_mono_call_graph)]
+pub struct CouldntDumpMonoCallGraph {
+    pub error: String,
+}
+
 #[derive(Diagnostic)]
 #[diag(monomorphize_couldnt_dump_mono_stats)]
 pub struct CouldntDumpMonoStats {
     pub error: String,
 }
 
+#[derive(Diagnostic)]
+#[diag(monomorphize_couldnt_dump_reachable_panics)]
+pub struct CouldntDumpReachablePanics {
+    pub error: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(monomorphize_reachable_panic)]
+#[note]
+pub struct ReachablePanic {
+    #[primary_span]
+    pub span: Span,
+    pub entry: String,
+    pub chain: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(monomorphize_unanalyzed_reachable_function)]
+#[note]
+#[help]
+pub struct UnanalyzedReachableFunction {
+    #[primary_span]
+    pub span: Span,
+    pub entry: String,
+    pub function: String,
+    pub chain: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(monomorphize_unknown_panic_entry)]
+#[note]
+pub struct UnknownPanicEntry {
+    pub entry: String,
+}
+
 #[derive(Diagnostic)]
 #[diag(monomorphize_encountered_error_while_instantiating)]
 pub struct EncounteredErrorWhileInstantiating {
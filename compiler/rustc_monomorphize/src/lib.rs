@@ -1,5 +1,6 @@
 #![feature(array_windows)]
 #![feature(is_sorted)]
+#![feature(let_chains)]
 #![recursion_limit = "256"]
 #![allow(rustc::potential_query_instability)]
 #![deny(rustc::untranslatable_diagnostic)]
@@ -18,8 +19,10 @@
 use rustc_middle::ty::adjustment::CustomCoerceUnsized;
 use rustc_middle::ty::{self, Ty};
 
+mod call_graph;
 mod collector;
 mod errors;
+mod panic_report;
 mod partitioning;
 mod polymorphize;
 mod util;
@@ -0,0 +1,400 @@
+//! Finds the panics reachable from the entry points of the crate, for `-Z reachable-panics-report`
+//! and `-Z deny-reachable-panics`.
+//!
+//! The panics are the calls to the panicking functions of the standard library: the panic lang
+//! items, the functions of the `panicking` modules of `core` and `std`, and the diverging
+//! `#[track_caller]` functions which report a failure, such as the ones called by
+//! `Result::unwrap` or by slice indexing. They are made explicitly (`panic!`, `unwrap`, ...) or by
+//! the `Assert` terminators inserted by MIR building (bounds checks, overflow checks, ...). They
+//! are found in the MIR of the mono items of the crate, and the call chain of a panic is the
+//! shortest path to it in the mono item graph built by the collector, from an entry point.
+//!
+//! Functions of upstream crates which are linked to rather than instantiated in this crate have no
+//! mono item, so the panics within them are not found. They are listed as unanalyzed functions in
+//! the report instead, and are errors for `-Z deny-reachable-panics`, as they may panic.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rustc_data_structures::fx::{FxHashSet, FxIndexMap};
+use rustc_hir::def::DefKind;
+use rustc_hir::def_id::{DefId, LOCAL_CRATE};
+use rustc_hir::definitions::DefPathData;
+use rustc_hir::lang_items::LangItem;
+use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
+use rustc_middle::mir::mono::MonoItem;
+use rustc_middle::mir::{self, AssertKind, TerminatorKind};
+use rustc_middle::ty::print::{with_crate_prefix, with_no_trimmed_paths};
+use rustc_middle::ty::{self, Instance, InstanceDef, TyCtxt};
+use rustc_session::config::SwitchWithOptPath;
+use rustc_span::source_map::Spanned;
+use rustc_span::symbol::sym;
+use rustc_span::{Span, Symbol};
+
+use crate::call_graph::span_to_string;
+use crate::collector::{UsageMap, UseKind};
+use crate::errors::{
+    CouldntDumpReachablePanics, ReachablePanic, UnanalyzedReachableFunction, UnknownPanicEntry,
+};
+
+/// A call into the panic machinery, in the MIR of a mono item.
+struct PanicSite {
+    /// The span of the call, or of the `Assert` terminator.
+    span: Span,
+    /// The span of the call which was inlined into the mono item, if the panic comes from inlined
+    /// MIR.
+    call_site: Option<Span>,
+    kind: &'static str,
+    panic_fn: DefId,
+}
+
+/// The functions which start a panic, which are called by the other panicking functions.
+const PANIC_LANG_ITEMS: &[LangItem] = &[
+    LangItem::Panic,
+    LangItem::PanicNounwind,
+    LangItem::PanicFmt,
+    LangItem::ConstPanicFmt,
+    LangItem::PanicBoundsCheck,
+    LangItem::PanicMisalignedPointerDereference,
+    LangItem::PanicImpl,
+    LangItem::PanicCannotUnwind,
+    LangItem::PanicInCleanup,
+    LangItem::BeginPanic,
+];
+
+/// The mono items reachable from some entry points, each with the use it was first reached
+/// through, in breadth-first order.
+type Reachable<'tcx> = FxIndexMap<MonoItem<'tcx>, Option<(MonoItem<'tcx>, Spanned<UseKind>)>>;
+
+pub(crate) fn check_reachable_panics<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    usage_map: &UsageMap<'tcx>,
+) {
+    if let SwitchWithOptPath::Enabled(ref path) =
+        tcx.sess.opts.unstable_opts.reachable_panics_report
+    {
+        let entry_points: Vec<_> =
+            usage_map.roots().iter().copied().filter(|&item| is_entry_point(tcx, item)).collect();
+        let reachable = reachable_items(tcx, &entry_points, usage_map);
+        if let Err(err) = write_report(tcx, items, &reachable, path, tcx.crate_name(LOCAL_CRATE)) {
+            tcx.sess.emit_fatal(CouldntDumpReachablePanics { error: err.to_string() });
+        }
+    }
+
+    if let Some(ref entry) = tcx.sess.opts.unstable_opts.deny_reachable_panics {
+        let entry_points: Vec<_> = items
+            .iter()
+            .copied()
+            .filter(|item| match *item {
+                MonoItem::Fn(Instance { def: InstanceDef::Item(def_id), .. }) => {
+                    with_no_trimmed_paths!(with_crate_prefix!(tcx.def_path_str(def_id))) == *entry
+                }
+                _ => false,
+            })
+            .collect();
+        if entry_points.is_empty() {
+            tcx.sess.emit_err(UnknownPanicEntry { entry: entry.clone() });
+            return;
+        }
+
+        let reachable = reachable_items(tcx, &entry_points, usage_map);
+        let chain = |item| {
+            call_chain(item, &reachable)
+                .iter()
+                .map(|(item, _)| format!("`{}`", item_name(*item)))
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        };
+        let mut seen = FxHashSet::default();
+        let mut seen_unanalyzed = FxHashSet::default();
+        for &item in reachable.keys() {
+            let MonoItem::Fn(instance) = item else { continue };
+            let (sites, unanalyzed) = panic_sites(tcx, instance, items);
+            for site in sites {
+                let span = site.call_site.unwrap_or(site.span);
+                if seen.insert((span, site.panic_fn)) {
+                    tcx.sess.emit_err(ReachablePanic {
+                        span,
+                        entry: entry.clone(),
+                        chain: chain(item),
+                    });
+                }
+            }
+            // The panics of these functions are not known, so they can't be shown to be absent.
+            for (span, function) in unanalyzed {
+                if seen_unanalyzed.insert((span, function.clone())) {
+                    tcx.sess.emit_err(UnanalyzedReachableFunction {
+                        span,
+                        entry: entry.clone(),
+                        function,
+                        chain: chain(item),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn write_report<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    reachable: &Reachable<'tcx>,
+    output_directory: &Option<PathBuf>,
+    crate_name: Symbol,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_directory = if let Some(ref directory) = output_directory {
+        fs::create_dir_all(directory)?;
+        directory
+    } else {
+        Path::new(".")
+    };
+
+    #[derive(serde::Serialize)]
+    struct Link {
+        function: String,
+        called_at: Option<String>,
+        #[serde(rename = "use")]
+        use_kind: Option<&'static str>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Panic {
+        function: String,
+        span: Option<String>,
+        call_site: Option<String>,
+        kind: &'static str,
+        panic_function: String,
+        entry_point: String,
+        call_chain: Vec<Link>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Report {
+        #[serde(rename = "crate")]
+        crate_name: String,
+        entry_points: Vec<String>,
+        panics: Vec<Panic>,
+        unanalyzed_functions: Vec<String>,
+    }
+
+    let mut panics = Vec::new();
+    let mut seen = FxHashSet::default();
+    let mut unanalyzed_functions = BTreeSet::new();
+    for &item in reachable.keys() {
+        let MonoItem::Fn(instance) = item else { continue };
+        let (sites, unanalyzed) = panic_sites(tcx, instance, items);
+        unanalyzed_functions.extend(unanalyzed.into_iter().map(|(_, function)| function));
+        for site in sites {
+            // The instances of a generic function share their panics, which are reported once,
+            // with the shortest call chain.
+            if !seen.insert((site.span, site.call_site, site.panic_fn)) {
+                continue;
+            }
+            let chain = call_chain(item, reachable);
+            let call_chain = chain
+                .iter()
+                .map(|&(item, use_)| Link {
+                    function: item_name(item),
+                    called_at: use_.and_then(|use_| span_to_string(tcx, use_.span)),
+                    use_kind: use_.map(|use_| use_.node.as_str()),
+                })
+                .collect();
+            panics.push(Panic {
+                function: item_name(item),
+                span: span_to_string(tcx, site.span),
+                call_site: site.call_site.and_then(|span| span_to_string(tcx, span)),
+                kind: site.kind,
+                panic_function: with_no_trimmed_paths!(tcx.def_path_str(site.panic_fn)),
+                entry_point: item_name(chain[0].0),
+                call_chain,
+            });
+        }
+    }
+
+    let report = Report {
+        crate_name: crate_name.to_string(),
+        entry_points: reachable
+            .iter()
+            .filter(|(_, reached_through)| reached_through.is_none())
+            .map(|(&item, _)| item_name(item))
+            .collect(),
+        panics,
+        unanalyzed_functions: unanalyzed_functions.into_iter().collect(),
+    };
+
+    let path = output_directory.join(format!("{crate_name}.reachable_panics.json"));
+    let mut file = BufWriter::new(File::create(&path)?);
+    serde_json::to_writer_pretty(&mut file, &report)?;
+    file.flush()?;
+
+    Ok(())
+}
+
+/// Walks the mono item graph breadth-first from the given entry points, without going through the
+/// functions of the panic machinery.
+fn reachable_items<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_points: &[MonoItem<'tcx>],
+    usage_map: &UsageMap<'tcx>,
+) -> Reachable<'tcx> {
+    let mut reachable: Reachable<'tcx> = entry_points.iter().map(|&item| (item, None)).collect();
+    let mut queue: VecDeque<_> = entry_points.iter().copied().collect();
+    while let Some(item) = queue.pop_front() {
+        for &(used_item, kind) in usage_map.get_used_items(item) {
+            if reachable.contains_key(&used_item.node)
+                || is_panicking_fn(tcx, used_item.node.def_id())
+            {
+                continue;
+            }
+            reachable
+                .insert(used_item.node, Some((item, Spanned { node: kind, span: used_item.span })));
+            queue.push_back(used_item.node);
+        }
+    }
+    reachable
+}
+
+/// Returns the path from an entry point to `item`, with the use through which each item of the
+/// path is reached.
+fn call_chain<'tcx>(
+    mut item: MonoItem<'tcx>,
+    reachable: &Reachable<'tcx>,
+) -> Vec<(MonoItem<'tcx>, Option<Spanned<UseKind>>)> {
+    let mut chain = Vec::new();
+    loop {
+        let reached_through = reachable[&item];
+        chain.push((item, reached_through.map(|(_, use_)| use_)));
+        match reached_through {
+            Some((user_item, _)) => item = user_item,
+            None => break,
+        }
+    }
+    chain.reverse();
+    chain
+}
+
+/// Returns the panics in the MIR of `instance`, and the upstream functions it calls which are not
+/// instantiated in this crate, with the spans of the calls.
+fn panic_sites<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    instance: Instance<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+) -> (Vec<PanicSite>, Vec<(Span, String)>) {
+    let body = tcx.instance_mir(instance.def);
+    let param_env = ty::ParamEnv::reveal_all();
+
+    let mut sites = Vec::new();
+    let mut unanalyzed = Vec::new();
+    for block in body.basic_blocks.iter() {
+        let terminator = block.terminator();
+        let source_info = terminator.source_info;
+        let (kind, panic_fn) = match terminator.kind {
+            TerminatorKind::Call { ref func, .. } => {
+                let callee_ty = instance.instantiate_mir_and_normalize_erasing_regions(
+                    tcx,
+                    param_env,
+                    ty::EarlyBinder::bind(func.ty(body, tcx)),
+                );
+                let ty::FnDef(def_id, args) = *callee_ty.kind() else { continue };
+                if is_panicking_fn(tcx, def_id) {
+                    ("call", def_id)
+                } else {
+                    if let Ok(Some(callee)) = Instance::resolve(tcx, param_env, def_id, args)
+                        && let InstanceDef::Item(callee_def_id) = callee.def
+                        && !callee_def_id.is_local()
+                        && !tcx.is_foreign_item(callee_def_id)
+                        && !items.contains(&MonoItem::Fn(callee.polymorphize(tcx)))
+                    {
+                        unanalyzed.push((
+                            inlined_call_site(body, source_info.scope).unwrap_or(source_info.span),
+                            with_no_trimmed_paths!(callee.to_string()),
+                        ));
+                    }
+                    continue;
+                }
+            }
+            TerminatorKind::Assert { ref msg, .. } => {
+                let (kind, lang_item) = match **msg {
+                    AssertKind::BoundsCheck { .. } => ("bounds_check", LangItem::PanicBoundsCheck),
+                    AssertKind::Overflow(..) => ("overflow", LangItem::Panic),
+                    AssertKind::OverflowNeg(..) => ("negation_overflow", LangItem::Panic),
+                    AssertKind::DivisionByZero(..) => ("division_by_zero", LangItem::Panic),
+                    AssertKind::RemainderByZero(..) => ("remainder_by_zero", LangItem::Panic),
+                    AssertKind::ResumedAfterReturn(..) => ("resumed_after_return", LangItem::Panic),
+                    AssertKind::ResumedAfterPanic(..) => ("resumed_after_panic", LangItem::Panic),
+                    AssertKind::MisalignedPointerDereference { .. } => (
+                        "misaligned_pointer_dereference",
+                        LangItem::PanicMisalignedPointerDereference,
+                    ),
+                };
+                (kind, tcx.require_lang_item(lang_item, Some(source_info.span)))
+            }
+            _ => continue,
+        };
+        sites.push(PanicSite {
+            span: source_info.span,
+            call_site: inlined_call_site(body, source_info.scope),
+            kind,
+            panic_fn,
+        });
+    }
+    (sites, unanalyzed)
+}
+
+/// Returns the span of the outermost call inlined into `body` that `scope` is part of, if any.
+fn inlined_call_site(body: &mir::Body<'_>, scope: mir::SourceScope) -> Option<Span> {
+    let mut call_site = None;
+    let mut scope = Some(scope);
+    while let Some(s) = scope {
+        let data = &body.source_scopes[s];
+        if let Some((_, span)) = data.inlined {
+            call_site = Some(span);
+        }
+        scope = data.inlined_parent_scope;
+    }
+    call_site
+}
+
+/// Whether `def_id` is a panicking function of the standard library: a panic lang item, a function
+/// of the `panicking` module of `core` or `std`, or a diverging `#[track_caller]` function, which
+/// reports a failure at the location of its caller (e.g. `core::result::unwrap_failed` or
+/// `core::slice::index::slice_end_index_len_fail`).
+fn is_panicking_fn(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    if PANIC_LANG_ITEMS.iter().any(|&item| tcx.lang_items().get(item) == Some(def_id)) {
+        return true;
+    }
+    if !matches!(tcx.crate_name(def_id.krate), sym::core | sym::alloc | sym::std) {
+        return false;
+    }
+    if tcx
+        .def_path(def_id)
+        .data
+        .first()
+        .is_some_and(|data| data.data == DefPathData::TypeNs(sym::panicking))
+    {
+        return true;
+    }
+    matches!(tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+        && tcx.codegen_fn_attrs(def_id).flags.contains(CodegenFnAttrFlags::TRACK_CALLER)
+        && tcx.fn_sig(def_id).skip_binder().output().skip_binder().is_never()
+}
+
+fn is_entry_point(tcx: TyCtxt<'_>, item: MonoItem<'_>) -> bool {
+    let MonoItem::Fn(Instance { def: InstanceDef::Item(def_id), .. }) = item else {
+        return false;
+    };
+    tcx.entry_fn(()).is_some_and(|(entry_fn, _)| entry_fn == def_id)
+        || tcx.lang_items().start_fn() == Some(def_id)
+        || tcx.is_reachable_non_generic(def_id)
+        || tcx.codegen_fn_attrs(def_id).contains_extern_indicator()
+}
+
+fn item_name(item: MonoItem<'_>) -> String {
+    match item {
+        MonoItem::Fn(instance) => with_no_trimmed_paths!(instance.to_string()),
+        MonoItem::Static(_) | MonoItem::GlobalAsm(_) => with_no_trimmed_paths!(item.to_string()),
+    }
+}
@@ -117,9 +117,13 @@
 use rustc_session::CodegenUnits;
 use rustc_span::symbol::Symbol;
 
+use crate::call_graph;
 use crate::collector::UsageMap;
 use crate::collector::{self, MonoItemCollectionMode};
-use crate::errors::{CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode};
+use crate::errors::{
+    CouldntDumpMonoCallGraph, CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode,
+};
+use crate::panic_report;
 
 struct PartitioningCx<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -1147,6 +1151,22 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[Co
         }
     }
 
+    // Output the mono item graph
+    if let SwitchWithOptPath::Enabled(ref path) = tcx.sess.opts.unstable_opts.dump_mono_call_graph {
+        if let Err(err) =
+            call_graph::dump_call_graph(tcx, &items, &usage_map, path, tcx.crate_name(LOCAL_CRATE))
+        {
+            tcx.sess.emit_fatal(CouldntDumpMonoCallGraph { error: err.to_string() });
+        }
+    }
+
+    // Report the panics reachable from the entry points
+    if tcx.sess.opts.unstable_opts.reachable_panics_report.enabled()
+        || tcx.sess.opts.unstable_opts.deny_reachable_panics.is_some()
+    {
+        panic_report::check_reachable_panics(tcx, &items, &usage_map);
+    }
+
     if tcx.sess.opts.unstable_opts.print_mono_items.is_some() {
         let mut item_to_cgus: FxHashMap<_, Vec<_>> = Default::default();
 
@@ -164,6 +164,14 @@ pub enum MirSpanview {
 ///
 /// `ExceptUnusedGenerics` will add synthetic functions to the coverage map,
 /// unless the function has type parameters.
+///
+/// `Branch` instruments the same code regions as `All`, and additionally adds a
+/// branch region (counting both outcomes) for each condition of an `if` or
+/// `while`, each arm of a `match`, and each `?` operator.
+///
+/// `Mcdc` instruments the same regions as `Branch`, and additionally records
+/// modified condition/decision coverage for each `if` or `while` condition made
+/// of two or more conditions combined with `&&` and `||`.
 #[derive(Clone, Copy, PartialEq, Hash, Debug)]
 pub enum InstrumentCoverage {
     /// Default `-C instrument-coverage` or `-C instrument-coverage=statement`
@@ -172,6 +180,10 @@ pub enum InstrumentCoverage {
     ExceptUnusedGenerics,
     /// `-Zunstable-options -C instrument-coverage=except-unused-functions`
     ExceptUnusedFunctions,
+    /// `-Zunstable-options -C instrument-coverage=branch`
+    Branch,
+    /// `-Zunstable-options -C instrument-coverage=mcdc`
+    Mcdc,
     /// `-C instrument-coverage=off` (or `no`, etc.)
     Off,
 }
@@ -548,6 +560,8 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A single SARIF log, for code scanning and code review tools.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -1964,6 +1978,7 @@ pub fn parse_error_format(
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
+            Some("sarif") => ErrorOutputType::Sarif,
 
             Some(arg) => {
                 handler.abort_if_error_and_set_error_format(ErrorOutputType::HumanReadable(
@@ -2044,6 +2059,13 @@ fn check_error_format_stability(
             });
             handler.early_error("`--error-format=human-annotate-rs` is unstable");
         }
+        if let ErrorOutputType::Sarif = error_format {
+            handler.abort_if_error_and_set_error_format(ErrorOutputType::Json {
+                pretty: false,
+                json_rendered,
+            });
+            handler.early_error("`--error-format=sarif` is unstable");
+        }
     }
 }
 
@@ -2709,6 +2731,12 @@ pub fn build_session_options(
             );
         }
         (Some(InstrumentCoverage::Off | InstrumentCoverage::All), _) => {}
+        (Some(InstrumentCoverage::Branch), _) if !unstable_opts.unstable_options => {
+            handler.early_error("`-C instrument-coverage=branch` requires `-Z unstable-options`");
+        }
+        (Some(InstrumentCoverage::Mcdc), _) if !unstable_opts.unstable_options => {
+            handler.early_error("`-C instrument-coverage=mcdc` requires `-Z unstable-options`");
+        }
         (Some(_), _) if !unstable_opts.unstable_options => {
             handler.early_error("`-C instrument-coverage=except-*` requires `-Z unstable-options`");
         }
@@ -384,7 +384,7 @@ mod desc {
     pub const parse_mir_spanview: &str = "`statement` (default), `terminator`, or `block`";
     pub const parse_dump_mono_stats: &str = "`markdown` (default) or `json`";
     pub const parse_instrument_coverage: &str =
-        "`all` (default), `except-unused-generics`, `except-unused-functions`, or `off`";
+        "`all` (default), `except-unused-generics`, `except-unused-functions`, `branch`, `mcdc`, or `off`";
     pub const parse_instrument_xray: &str = "either a boolean (`yes`, `no`, `on`, `off`, etc), or a comma separated list of settings: `always` or `never` (mutually exclusive), `ignore-loops`, `instruction-threshold=N`, `skip-entry`, `skip-exit`";
     pub const parse_unpretty: &str = "`string` or `string=string`";
     pub const parse_treat_err_as_bug: &str = "either no value or a non-negative number";
@@ -916,6 +916,8 @@ pub(crate) fn parse_instrument_coverage(
             "except-unused-functions" | "except_unused_functions" => {
                 InstrumentCoverage::ExceptUnusedFunctions
             }
+            "branch" => InstrumentCoverage::Branch,
+            "mcdc" => InstrumentCoverage::Mcdc,
             "off" | "no" | "n" | "false" | "0" => InstrumentCoverage::Off,
             _ => return false,
         });
@@ -1322,6 +1324,8 @@ pub(crate) fn parse_dump_solver_proof_tree(
         `=all` (implicit value)
         `=except-unused-generics`
         `=except-unused-functions`
+        `=branch`
+        `=mcdc`
         `=off` (default)"),
     link_arg: (/* redirected to link_args */) = ((), parse_string_push, [UNTRACKED],
         "a single extra argument to append to the linker invocation (can be used several times)"),
@@ -1449,6 +1453,8 @@ pub(crate) fn parse_dump_solver_proof_tree(
         "compress debug info sections (none, zlib, zstd, default: none)"),
     deduplicate_diagnostics: bool = (true, parse_bool, [UNTRACKED],
         "deduplicate identical diagnostics (default: yes)"),
+    deny_reachable_panics: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "emit an error for every panic reachable from the function with the given path"),
     dep_info_omit_d_target: bool = (false, parse_bool, [TRACKED],
         "in dep-info output, omit targets for tracking dependencies of the dep-info files \
         themselves (default: no)"),
@@ -1484,6 +1490,9 @@ pub(crate) fn parse_dump_solver_proof_tree(
         all `statement`s (including terminators), only `terminator` spans, or \
         computed `block` spans (one span encompassing a block's terminator and \
         all statements)."),
+    dump_mono_call_graph: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [UNTRACKED],
+        "output the graph of the uses between monomorphized items, as JSON and DOT files"),
     dump_mono_stats: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
         parse_switch_with_opt_path, [UNTRACKED],
         "output statistics about monomorphization collection"),
@@ -1562,6 +1571,8 @@ pub(crate) fn parse_dump_solver_proof_tree(
         `=all` (implicit value)
         `=except-unused-generics`
         `=except-unused-functions`
+        `=branch`
+        `=mcdc`
         `=off` (default)"),
     instrument_mcount: bool = (false, parse_bool, [TRACKED],
         "insert function instrument code for mcount-based tracing (default: no)"),
@@ -1714,6 +1725,10 @@ pub(crate) fn parse_dump_solver_proof_tree(
         "enable queries of the dependency graph for regression testing (default: no)"),
     randomize_layout: bool = (false, parse_bool, [TRACKED],
         "randomize the layout of types (default: no)"),
+    reachable_panics_report: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [UNTRACKED],
+        "write a report of the panics reachable from the entry points of the crate, \
+        to the given directory"),
     relax_elf_relocations: Option<bool> = (None, parse_opt_bool, [TRACKED],
         "whether ELF relocations can be relaxed"),
     relro_level: Option<RelroLevel> = (None, parse_relro_level, [TRACKED],
@@ -1785,6 +1800,10 @@ pub(crate) fn parse_dump_solver_proof_tree(
     #[rustc_lint_opt_deny_field_access("use `Session::stack_protector` instead of this field")]
     stack_protector: StackProtector = (StackProtector::None, parse_stack_protector, [TRACKED],
         "control stack smash protection strategy (`rustc --print stack-protector-strategies` for details)"),
+    stack_usage_report: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [TRACKED],
+        "write a report of the worst-case stack usage of the entry points of the crate, \
+        to the given directory (implies `-Z emit-stack-sizes`)"),
     staticlib_allow_rdylib_deps: bool = (false, parse_bool, [TRACKED],
         "allow staticlibs to have rust dylib dependencies"),
     staticlib_prefer_dynamic: bool = (false, parse_bool, [TRACKED],
@@ -1870,6 +1889,9 @@ pub(crate) fn parse_dump_solver_proof_tree(
         `hir,typed` (HIR with types for each node),
         `hir-tree` (dump the raw HIR),
         `mir` (the MIR), or `mir-cfg` (graphviz formatted MIR)"),
+    unsafe_inventory: SwitchWithOptPath = (SwitchWithOptPath::Disabled,
+        parse_switch_with_opt_path, [TRACKED],
+        "write a JSON inventory of the unsafe code of the crate, to the given directory"),
     unsound_mir_opts: bool = (false, parse_bool, [TRACKED],
         "enable unsound and buggy MIR optimizations (default: no)"),
     /// This name is kind of confusing: Most unstable options enable something themselves, while
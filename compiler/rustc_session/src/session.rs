@@ -23,6 +23,7 @@
 use rustc_errors::emitter::{DynEmitter, EmitterWriter, HumanReadableErrorType};
 use rustc_errors::json::JsonEmitter;
 use rustc_errors::registry::Registry;
+use rustc_errors::sarif::SarifEmitter;
 use rustc_errors::{
     error_code, fallback_fluent_bundle, DiagnosticBuilder, DiagnosticId, DiagnosticMessage,
     ErrorGuaranteed, FluentBundle, Handler, IntoDiagnostic, LazyFallbackBundle, MultiSpan, Noted,
@@ -709,6 +710,17 @@ pub fn instrument_coverage_except_unused_functions(&self) -> bool {
         self.opts.cg.instrument_coverage() == InstrumentCoverage::ExceptUnusedFunctions
     }
 
+    pub fn instrument_coverage_branch(&self) -> bool {
+        matches!(
+            self.opts.cg.instrument_coverage(),
+            InstrumentCoverage::Branch | InstrumentCoverage::Mcdc
+        )
+    }
+
+    pub fn instrument_coverage_mcdc(&self) -> bool {
+        self.opts.cg.instrument_coverage() == InstrumentCoverage::Mcdc
+    }
+
     pub fn is_sanitizer_cfi_enabled(&self) -> bool {
         self.opts.unstable_opts.sanitizer.contains(SanitizerSet::CFI)
     }
@@ -1320,6 +1332,9 @@ fn default_emitter(
                 sopts.unstable_opts.ignore_directory_in_diagnostics_source_blocks.clone(),
             ),
         ),
+        config::ErrorOutputType::Sarif => {
+            Box::new(SarifEmitter::stderr(Some(registry), source_map, bundle, fallback_bundle))
+        }
     }
 }
 
@@ -1760,6 +1775,10 @@ fn mk_emitter(output: ErrorOutputType) -> Box<DynEmitter> {
             false,
             TerminalUrl::No,
         )),
+        // Early errors are followed by the log of the session, unless they abort the compilation.
+        config::ErrorOutputType::Sarif => {
+            Box::new(SarifEmitter::basic(fallback_bundle).write_empty_log(false))
+        }
     };
     emitter
 }
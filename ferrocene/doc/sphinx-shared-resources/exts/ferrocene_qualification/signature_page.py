@@ -33,26 +33,35 @@ class SignatureStatus:
 
             self.context["signatures"] = {}
             for role in self.context["config"]["roles"]:
-                try:
-                    bundle = json.loads(
-                        self.load_private_file(f"{role}.cosign-bundle", copy=True)
-                    )
-                    time = datetime.datetime.utcfromtimestamp(
-                        bundle["rekorBundle"]["Payload"]["integratedTime"]
-                    ).strftime("%Y-%m-%d %H:%M:%S UTC")
-                    present = True
-                except FileNotFoundError:
-                    time = "-"
-                    present = False
-                self.context["signatures"][role] = {
-                    "time": time,
-                    "present": present,
-                }
+                signature = self.load_signature(role)
+                if signature is None:
+                    signature = {"time": "-", "present": False, "bundle": None}
+                self.context["signatures"][role] = signature
 
             self.state = "signed"
         except FileNotFoundError:
             self.state = "inconsistent"
 
+    def load_signature(self, role):
+        # Roles can be signed either with cosign or with a local ed25519 key,
+        # each storing the signature time in a different bundle format.
+        bundle_name = f"{role}.cosign-bundle"
+        try:
+            bundle = json.loads(self.load_private_file(bundle_name, copy=True))
+            timestamp = bundle["rekorBundle"]["Payload"]["integratedTime"]
+        except FileNotFoundError:
+            bundle_name = f"{role}.ed25519-bundle"
+            try:
+                bundle = json.loads(self.load_private_file(bundle_name, copy=True))
+                timestamp = bundle["signed-at"]
+            except FileNotFoundError:
+                return None
+
+        time = datetime.datetime.utcfromtimestamp(timestamp).strftime(
+            "%Y-%m-%d %H:%M:%S UTC"
+        )
+        return {"time": time, "present": True, "bundle": bundle_name}
+
     def load_file(self, name, *, copy=False):
         path = f"{self.app.srcdir}/../signature/{name}"
         self.loaded_files.append(path)
@@ -2,18 +2,18 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 use anyhow::Error;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 
-static IDPS: &[IdP] = &[IdP {
-    display_name: "Microsoft",
-    url: "https://login.microsoftonline.com",
-    email_domains: &["ferrous-systems.com"],
-}];
-
 #[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub(crate) struct Config {
-    pub(crate) roles: HashMap<String, Role>,
+    /// Identity providers cosign signers are allowed to authenticate with.
+    #[serde(default)]
+    pub(crate) idps: BTreeMap<String, IdP>,
+    pub(crate) roles: BTreeMap<String, Role>,
+    #[serde(default)]
+    pub(crate) policy: Policy,
 }
 
 impl Config {
@@ -26,32 +26,297 @@ pub(crate) fn load(source_dir: &Path) -> Result<Self, Error> {
             }
             Err(err) => return Err(err.into()),
         };
-        Ok(toml::from_slice(&content)?)
+        let config: Config = toml::from_slice(&content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if let Some(required_roles) = &self.policy.required_roles {
+            let mut seen = HashSet::new();
+            for role in required_roles {
+                if !self.roles.contains_key(role) {
+                    anyhow::bail!("required role {role} is not defined in the roles section");
+                }
+                if !seen.insert(role) {
+                    anyhow::bail!("role {role} is required multiple times");
+                }
+            }
+        } else if self.policy.ordered {
+            anyhow::bail!("signing the roles in order requires an explicit list of required roles");
+        }
+
+        let mut domains = HashSet::new();
+        for idp in self.idps.values() {
+            for domain in &idp.email_domains {
+                if !domains.insert(domain) {
+                    anyhow::bail!(
+                        "email domain {domain} is allowed by multiple identity providers"
+                    );
+                }
+            }
+        }
+
+        // Roles signing with a local key don't authenticate with an identity provider.
+        for role in self.roles.values() {
+            if role.public_key.is_none() {
+                self.idp(role)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Roles that must sign the document, in the order they must sign it if the policy is ordered.
+    pub(crate) fn required_roles(&self) -> Vec<&str> {
+        match &self.policy.required_roles {
+            Some(required_roles) => required_roles.iter().map(|r| r.as_str()).collect(),
+            None => self.roles.keys().map(|r| r.as_str()).collect(),
+        }
+    }
+
+    /// Required roles that have to sign the document before the provided role. Roles that are
+    /// not required to sign the document can sign it at any time.
+    pub(crate) fn roles_signing_before(&self, role_name: &str) -> Vec<&str> {
+        let required_roles = self.required_roles();
+        match required_roles.iter().position(|r| *r == role_name) {
+            Some(position) if self.policy.ordered => required_roles[..position].to_vec(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Check the roles signed the document in the order required by the policy, given the time
+    /// each role signed it at. Returns the list of problems found.
+    pub(crate) fn check_signing_order(&self, signed_at: &BTreeMap<&str, u64>) -> Vec<String> {
+        let mut problems = Vec::new();
+        for role_name in self.required_roles() {
+            let Some(time) = signed_at.get(role_name) else { continue };
+            for before in self.roles_signing_before(role_name) {
+                if let Some(before_time) = signed_at.get(before) {
+                    if before_time > time {
+                        problems.push(format!("role {role_name} signed before role {before}"));
+                    }
+                }
+            }
+        }
+        problems
+    }
+
+    pub(crate) fn idp(&self, role: &Role) -> Result<&IdP, Error> {
+        let Some((_, domain)) = role.email.split_once('@') else {
+            anyhow::bail!("invalid email address: {}", role.email);
+        };
+
+        for idp in self.idps.values() {
+            if idp.email_domains.iter().any(|d| d == domain) {
+                return Ok(idp);
+            }
+        }
+        anyhow::bail!(
+            "no identity provider in the document's signature config.toml allows {domain}"
+        );
     }
 }
 
 #[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub(crate) struct Role {
     pub(crate) email: String,
+    /// PEM-encoded ed25519 public key of the person assigned to the role. When present, the role
+    /// signs with the corresponding local private key instead of using cosign.
+    pub(crate) public_key: Option<String>,
 }
 
 impl Role {
-    pub(crate) fn idp(&self) -> Result<&'static IdP, Error> {
-        let Some((_, domain)) = self.email.split_once('@') else {
-            anyhow::bail!("invalid email address: {}", self.email);
-        };
-
-        for idp in IDPS {
-            if idp.email_domains.contains(&domain) {
-                return Ok(idp);
-            }
+    pub(crate) fn signature_file_name(&self, role_name: &str) -> String {
+        if self.public_key.is_some() {
+            format!("{role_name}.ed25519-bundle")
+        } else {
+            format!("{role_name}.cosign-bundle")
         }
-        anyhow::bail!("unsupported email domain: {domain}");
     }
 }
 
+#[derive(Debug, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub(crate) struct IdP {
-    pub(crate) display_name: &'static str,
-    pub(crate) url: &'static str,
-    pub(crate) email_domains: &'static [&'static str],
+    pub(crate) display_name: String,
+    pub(crate) url: String,
+    pub(crate) email_domains: Vec<String>,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct Policy {
+    /// Roles that must sign the document. When missing, all roles must sign it.
+    required_roles: Option<Vec<String>>,
+    /// Whether the required roles must sign in the order they are listed.
+    #[serde(default)]
+    ordered: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDPS: &str = r#"
+        [idps.microsoft]
+        display-name = "Microsoft"
+        url = "https://login.microsoftonline.com"
+        email-domains = ["ferrous-systems.com"]
+    "#;
+
+    const ROLES: &str = r#"
+        [roles.engineer]
+        email = "engineer@ferrous-systems.com"
+
+        [roles.verifier]
+        email = "verifier@ferrous-systems.com"
+
+        [roles.technical-lead]
+        email = "technical-lead@ferrous-systems.com"
+    "#;
+
+    fn parse(policy: &str) -> Result<Config, Error> {
+        let config: Config = toml::from_str(&format!("{IDPS}\n{ROLES}\n{policy}"))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    #[test]
+    fn test_no_policy() {
+        let config = parse("").unwrap();
+
+        assert_eq!(vec!["engineer", "technical-lead", "verifier"], config.required_roles());
+        assert!(config.roles_signing_before("verifier").is_empty());
+    }
+
+    #[test]
+    fn test_valid_ordered_policy() {
+        let config = parse(
+            r#"
+            [policy]
+            required-roles = ["verifier", "engineer"]
+            ordered = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(vec!["verifier", "engineer"], config.required_roles());
+        assert!(config.roles_signing_before("verifier").is_empty());
+        assert_eq!(vec!["verifier"], config.roles_signing_before("engineer"));
+        // Roles not required to sign can sign at any time.
+        assert!(config.roles_signing_before("technical-lead").is_empty());
+
+        let signed_at = [("verifier", 100), ("engineer", 200), ("technical-lead", 50)];
+        assert!(config.check_signing_order(&signed_at.into_iter().collect()).is_empty());
+    }
+
+    #[test]
+    fn test_unordered_policy() {
+        let config = parse(
+            r#"
+            [policy]
+            required-roles = ["verifier", "engineer"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.roles_signing_before("engineer").is_empty());
+
+        let signed_at = [("verifier", 200), ("engineer", 100)];
+        assert!(config.check_signing_order(&signed_at.into_iter().collect()).is_empty());
+    }
+
+    #[test]
+    fn test_wrong_order() {
+        let config = parse(
+            r#"
+            [policy]
+            required-roles = ["verifier", "engineer", "technical-lead"]
+            ordered = true
+            "#,
+        )
+        .unwrap();
+
+        let signed_at = [("verifier", 200), ("engineer", 100), ("technical-lead", 300)];
+        assert_eq!(
+            vec!["role engineer signed before role verifier"],
+            config.check_signing_order(&signed_at.into_iter().collect())
+        );
+    }
+
+    #[test]
+    fn test_missing_required_role() {
+        let err = parse(
+            r#"
+            [policy]
+            required-roles = ["verifier", "reviewer"]
+            "#,
+        )
+        .unwrap_err();
+        assert_eq!("required role reviewer is not defined in the roles section", err.to_string());
+    }
+
+    #[test]
+    fn test_duplicate_required_role() {
+        let err = parse(
+            r#"
+            [policy]
+            required-roles = ["verifier", "verifier"]
+            "#,
+        )
+        .unwrap_err();
+        assert_eq!("role verifier is required multiple times", err.to_string());
+    }
+
+    #[test]
+    fn test_ordered_without_required_roles() {
+        let err = parse(
+            r#"
+            [policy]
+            ordered = true
+            "#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            "signing the roles in order requires an explicit list of required roles",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_unknown_idp() {
+        let config: Config = toml::from_str(&format!(
+            "{IDPS}\n{ROLES}\n[roles.external]\nemail = \"external@example.com\"\n"
+        ))
+        .unwrap();
+        assert_eq!(
+            "no identity provider in the document's signature config.toml allows example.com",
+            config.validate().unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_unknown_idp_with_local_key() {
+        let config: Config = toml::from_str(&format!(
+            "{IDPS}\n{ROLES}\n[roles.external]\nemail = \"external@example.com\"\n\
+             public-key = \"not parsed during validation\"\n"
+        ))
+        .unwrap();
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn test_email_domain_in_multiple_idps() {
+        let config: Config = toml::from_str(&format!(
+            "{IDPS}\n{ROLES}\n[idps.other]\ndisplay-name = \"Other\"\n\
+             url = \"https://example.com\"\nemail-domains = [\"ferrous-systems.com\"]\n"
+        ))
+        .unwrap();
+        assert_eq!(
+            "email domain ferrous-systems.com is allowed by multiple identity providers",
+            config.validate().unwrap_err().to_string()
+        );
+    }
 }
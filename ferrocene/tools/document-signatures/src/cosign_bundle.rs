@@ -12,6 +12,7 @@
 
 pub(crate) struct RawCosignBundle {
     pem: Pem,
+    integrated_time: u64,
 }
 
 impl RawCosignBundle {
@@ -23,7 +24,12 @@ pub(crate) fn load(path: &Path) -> Result<Self, Error> {
             base64::engine::general_purpose::STANDARD.decode(&bundle.cert)?,
         ));
         let pem = Pem::read(raw_pem)?.0;
-        Ok(Self { pem })
+        Ok(Self { pem, integrated_time: bundle.rekor_bundle.payload.integrated_time })
+    }
+
+    /// When the signature was recorded in the Rekor transparency log, as a UNIX timestamp.
+    pub(crate) fn signed_at(&self) -> u64 {
+        self.integrated_time
     }
 
     pub(crate) fn parse(&self) -> Result<CosignBundle<'_>, Error> {
@@ -62,6 +68,20 @@ pub(crate) fn idp(&self) -> Result<String, Error> {
 }
 
 #[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct OnDiskBundle {
     cert: String,
+    rekor_bundle: RekorBundle,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct RekorBundle {
+    payload: RekorPayload,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RekorPayload {
+    integrated_time: u64,
 }
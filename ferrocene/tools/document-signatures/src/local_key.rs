@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Signatures created with local ed25519 keys, as an alternative to cosign for environments
+//! without network access (cosign requires access to Sigstore's Fulcio and Rekor). The private
+//! key is provided as a PKCS#8 PEM file, while the public key is pinned in the role definition
+//! inside of the document's `signature/config.toml`.
+
+use crate::config::Role;
+use anyhow::{Context, Error};
+use base64::Engine;
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) struct LocalKey {
+    signing_key: SigningKey,
+}
+
+impl LocalKey {
+    pub(crate) fn load(path: &Path) -> Result<Self, Error> {
+        let pem = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read private key {}", path.display()))?;
+        let signing_key = SigningKey::from_pkcs8_pem(&pem)
+            .map_err(|err| anyhow::anyhow!("{err}"))
+            .with_context(|| format!("failed to parse ed25519 private key {}", path.display()))?;
+        Ok(Self { signing_key })
+    }
+
+    pub(crate) fn matches(&self, role: &Role) -> Result<bool, Error> {
+        match public_key(role)? {
+            Some(public_key) => Ok(public_key == self.signing_key.verifying_key()),
+            None => Ok(false),
+        }
+    }
+
+    pub(crate) fn sign(&self, message: &[u8]) -> Result<LocalKeyBundle, Error> {
+        let signed_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let signature = self.signing_key.sign(&signed_payload(message, signed_at));
+        Ok(LocalKeyBundle {
+            signature: base64::engine::general_purpose::STANDARD.encode(signature.to_bytes()),
+            signed_at,
+        })
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct LocalKeyBundle {
+    signature: String,
+    /// Covered by the signature, as it's used to check the order roles signed the document in.
+    /// Unlike cosign's timestamps it's not attested by a third party: it's the time the signer
+    /// claims to have signed the document at.
+    signed_at: u64,
+}
+
+impl LocalKeyBundle {
+    pub(crate) fn signed_at(&self) -> u64 {
+        self.signed_at
+    }
+
+    pub(crate) fn verify(&self, role: &Role, message: &[u8]) -> Result<(), Error> {
+        let Some(public_key) = public_key(role)? else {
+            anyhow::bail!("the role has no public key");
+        };
+        let signature = base64::engine::general_purpose::STANDARD.decode(&self.signature)?;
+        let signature = Signature::from_slice(&signature)?;
+        public_key.verify_strict(&signed_payload(message, self.signed_at), &signature)?;
+        Ok(())
+    }
+}
+
+/// The payload actually signed: the signing time followed by the message.
+fn signed_payload(message: &[u8], signed_at: u64) -> Vec<u8> {
+    let mut payload = signed_at.to_be_bytes().to_vec();
+    payload.extend_from_slice(message);
+    payload
+}
+
+fn public_key(role: &Role) -> Result<Option<VerifyingKey>, Error> {
+    let Some(pem) = &role.public_key else { return Ok(None) };
+    Ok(Some(
+        VerifyingKey::from_public_key_pem(pem)
+            .map_err(|err| anyhow::anyhow!("{err}"))
+            .with_context(|| format!("failed to parse public key of {}", role.email))?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+    use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey};
+
+    const PINNED: &[u8] = b"document-id = \"00000000-0000-0000-0000-000000000000\"\n\
+        tarball-sha256 = \"0000000000000000000000000000000000000000000000000000000000000000\"\n";
+
+    fn generate_key(seed: u8) -> (LocalKey, Role) {
+        let signing_key = SigningKey::from_bytes(&[seed; 32]);
+        let role = Role {
+            email: "signer@example.com".into(),
+            public_key: Some(
+                signing_key.verifying_key().to_public_key_pem(LineEnding::LF).unwrap(),
+            ),
+        };
+        (LocalKey { signing_key }, role)
+    }
+
+    #[test]
+    fn test_load() {
+        let (key, role) = generate_key(1);
+        let pem = key.signing_key.to_pkcs8_pem(LineEnding::LF).unwrap();
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), pem.as_bytes()).unwrap();
+
+        let loaded = LocalKey::load(file.path()).unwrap();
+        assert!(loaded.matches(&role).unwrap());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let (key, role) = generate_key(1);
+        let bundle = key.sign(PINNED).unwrap();
+
+        let bundle: LocalKeyBundle =
+            serde_json::from_slice(&serde_json::to_vec(&bundle).unwrap()).unwrap();
+        bundle.verify(&role, PINNED).unwrap();
+    }
+
+    #[test]
+    fn test_tampered_tarball() {
+        let (key, role) = generate_key(1);
+        let bundle = key.sign(PINNED).unwrap();
+
+        let tampered = std::str::from_utf8(PINNED)
+            .unwrap()
+            .replace("tarball-sha256 = \"0", "tarball-sha256 = \"1");
+        assert!(bundle.verify(&role, tampered.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_tampered_signing_time() {
+        let (key, role) = generate_key(1);
+        let mut bundle = key.sign(PINNED).unwrap();
+
+        bundle.signed_at -= 3600;
+        assert!(bundle.verify(&role, PINNED).is_err());
+    }
+
+    #[test]
+    fn test_wrong_public_key() {
+        let (key, _) = generate_key(1);
+        let (other_key, other_role) = generate_key(2);
+        let bundle = key.sign(PINNED).unwrap();
+
+        assert!(!key.matches(&other_role).unwrap());
+        assert!(other_key.matches(&other_role).unwrap());
+        assert!(bundle.verify(&other_role, PINNED).is_err());
+    }
+
+    #[test]
+    fn test_role_without_public_key() {
+        let (key, _) = generate_key(1);
+        let bundle = key.sign(PINNED).unwrap();
+        let role = Role { email: "signer@example.com".into(), public_key: None };
+
+        assert!(!key.matches(&role).unwrap());
+        assert_eq!(
+            "the role has no public key",
+            bundle.verify(&role, PINNED).unwrap_err().to_string()
+        );
+    }
+}
@@ -3,12 +3,16 @@
 
 mod config;
 mod cosign_bundle;
+mod local_key;
 mod pinned;
 mod sign;
 mod signature_files;
+mod tarball;
 mod verify;
 
+use crate::signature_files::Storage;
 use anyhow::{Context, Error};
+use std::env::VarError;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
@@ -41,19 +45,33 @@ fn main() -> Result<(), Error> {
 }
 
 struct CliOptions {
-    cosign_binary: PathBuf,
-    s3_bucket: String,
-    s3_cache_dir: PathBuf,
+    cosign_binary: Option<PathBuf>,
+    private_key: Option<PathBuf>,
+    storage: Storage,
 }
 
 impl CliOptions {
     fn load() -> Result<Self, Error> {
+        let storage = match env_optional("LOCAL_DIR")? {
+            Some(dir) => Storage::Local { dir },
+            None => Storage::S3 { bucket: env("S3_BUCKET")?, cache_dir: env("S3_CACHE_DIR")? },
+        };
+
         Ok(Self {
-            cosign_binary: env("COSIGN_BINARY")?,
-            s3_bucket: env("S3_BUCKET")?,
-            s3_cache_dir: env("S3_CACHE_DIR")?,
+            cosign_binary: env_optional("COSIGN_BINARY")?,
+            private_key: env_optional("PRIVATE_KEY")?,
+            storage,
         })
     }
+
+    fn cosign_binary(&self) -> Result<&Path, Error> {
+        match &self.cosign_binary {
+            Some(binary) => Ok(binary),
+            None => anyhow::bail!(
+                "cosign is required, but DOCUMENT_SIGNATURES_COSIGN_BINARY is not set"
+            ),
+        }
+    }
 }
 
 fn env<T>(var: &str) -> Result<T, Error>
@@ -67,3 +85,15 @@ fn env<T>(var: &str) -> Result<T, Error>
         Err(e) => Err(Error::from(e).context(format!("failed to read {var}"))),
     }
 }
+
+fn env_optional<T>(var: &str) -> Result<Option<T>, Error>
+where
+    T: FromStr,
+    T::Err: Send + Sync + std::error::Error + 'static,
+{
+    match env(var) {
+        Ok(v) => Ok(Some(v)),
+        Err(e) if matches!(e.downcast_ref(), Some(VarError::NotPresent)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
@@ -1,36 +1,11 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
+use crate::tarball::{tarball_sha256, TAR_REPRODUCIBILITY_FLAGS};
 use crate::TOML_HEADER_COMMENTS;
 use anyhow::{Context, Error};
 use base64::Engine;
-use sha2::{Digest, Sha256};
 use std::path::Path;
-use std::process::{Command, Stdio};
-
-const TAR_REPRODUCIBILITY_FLAGS: &[&str] = &[
-    // Use a consistent ordering for the files in the archive.
-    "--sort=name",
-    // Use a pinned date for the modification time, otherwise touching a file would change the hash
-    // of the archive.
-    "--mtime=2020-01-01T00:00:00Z",
-    // Tarballs include the UNIX owner and groups, which change between systems. Hardcode them to
-    // "0", and avoid looking up the corresponding name for ID 0.
-    "--owner=0",
-    "--group=0",
-    "--numeric-owner",
-    // Make all files readable by everyone and writable by the owner, preserving the executable
-    // bit. This is needed otherwise systems with non-reproducible umasks will generate different
-    // tarballs.
-    "--mode=u+rw,go+r,go-w",
-    // The default format includes non-deterministic bits. Use the GNU format, which omits them.
-    "--format=gnu",
-    // The generated documentation includes a directory called "signature" that contains data about
-    // the signature being generated right now. We exclude it from the hash as we can't know the
-    // signature contents in advance.
-    "--anchored",
-    "--exclude=./signature",
-];
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
@@ -46,22 +21,8 @@ pub(crate) fn generate(output_dir: &Path) -> Result<Self, Error> {
             .trim()
             .to_string();
 
-        let mut tar = Command::new("tar")
-            .args(TAR_REPRODUCIBILITY_FLAGS)
-            .arg("-C")
-            .arg(output_dir)
-            .args(&["-c", "."])
-            .stdout(Stdio::piped())
-            .spawn()?;
-
-        let mut hasher = Sha256::new();
-        std::io::copy(&mut tar.stdout.take().unwrap(), &mut hasher)?;
-        let tarball_sha256 = hex::encode(hasher.finalize());
-
-        let status = tar.wait()?;
-        if !status.success() {
-            anyhow::bail!("failed to invoke tar to create content tarball");
-        }
+        let tarball_sha256 = tarball_sha256(output_dir)
+            .context("failed to hash the contents of the output directory")?;
 
         Ok(Self { document_id, tarball_sha256 })
     }
@@ -3,6 +3,7 @@
 
 use crate::config::Config;
 use crate::cosign_bundle::RawCosignBundle;
+use crate::local_key::LocalKey;
 use crate::pinned::Pinned;
 use crate::signature_files::SignatureFiles;
 use crate::CliOptions;
@@ -40,9 +41,38 @@ pub(crate) fn sign(
         signature_files.write("pinned.toml", &contents)?;
     }
 
+    let (role_name, bundle) = match &options.private_key {
+        Some(private_key) => sign_with_local_key(private_key, &config, &signature_files)?,
+        None => sign_with_cosign(&config, &signature_files, options)?,
+    };
+
+    // When the policy requires an order, the roles before this one must have already signed.
+    // Signatures made before pinned.toml was regenerated are outdated, and don't count.
+    let missing = config
+        .roles_signing_before(&role_name)
+        .into_iter()
+        .filter(|r| {
+            regenerate_pinned || !signature_files.contains(&config.roles[*r].signature_file_name(r))
+        })
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        anyhow::bail!("role {role_name} can only sign after these roles: {}", missing.join(", "));
+    }
+
+    let role = &config.roles[&role_name];
+    signature_files.write(&role.signature_file_name(&role_name), &bundle)?;
+
+    Ok(())
+}
+
+fn sign_with_cosign(
+    config: &Config,
+    signature_files: &SignatureFiles<'_>,
+    options: &CliOptions,
+) -> Result<(String, Vec<u8>), Error> {
     let bundle_temp = NamedTempFile::new()?;
     let pinned_temp = signature_files.on_disk_as_tempfile("pinned.toml")?.unwrap();
-    let status = Command::new(&options.cosign_binary)
+    let status = Command::new(options.cosign_binary()?)
         .arg("sign-blob")
         .arg(pinned_temp.path())
         .arg("--bundle")
@@ -59,14 +89,41 @@ pub(crate) fn sign(
     let Some((role_name, role)) = config.roles.iter().find(|(_, role)| role.email == email) else {
         anyhow::bail!("email {email} has no role in the document's signature config.toml");
     };
+    if role.public_key.is_some() {
+        anyhow::bail!("role {role_name} must be signed with its private key instead of cosign");
+    }
 
-    let role_idp = role.idp()?;
+    let role_idp = config.idp(role)?;
     if role_idp.url != bundle.idp()? {
         anyhow::bail!("you must authenticate with {}", role_idp.display_name);
     }
 
-    signature_files
-        .write(&format!("{role_name}.cosign-bundle"), &std::fs::read(bundle_temp.path())?)?;
+    Ok((role_name.clone(), std::fs::read(bundle_temp.path())?))
+}
+
+fn sign_with_local_key(
+    private_key: &Path,
+    config: &Config,
+    signature_files: &SignatureFiles<'_>,
+) -> Result<(String, Vec<u8>), Error> {
+    let key = LocalKey::load(private_key)?;
 
-    Ok(())
+    let mut role_name = None;
+    for (name, role) in &config.roles {
+        if key.matches(role)? {
+            role_name = Some(name);
+            break;
+        }
+    }
+    let Some(role_name) = role_name else {
+        anyhow::bail!(
+            "the public key of {} has no role in the document's signature config.toml",
+            private_key.display()
+        );
+    };
+
+    let pinned = signature_files.read("pinned.toml")?.unwrap();
+    let bundle = key.sign(&pinned)?;
+
+    Ok((role_name.clone(), serde_json::to_vec_pretty(&bundle)?))
 }
@@ -2,8 +2,10 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 //! This module implements an abstraction for reading and writing signature-related files. Behind
-//! the scenes, the files are written to an S3 bucket and recorded in the `signature.toml` file,
-//! and files are read from the `src/bootstrap`-maintained cache.
+//! the scenes, the files are named after a random UUID and recorded in the `signature.toml` file.
+//! Depending on the [`Storage`] in use, the files are either written to an S3 bucket and read
+//! from the `src/bootstrap`-maintained cache, or both written to and read from a local directory
+//! (for environments without network access).
 
 use crate::{CliOptions, TOML_HEADER_COMMENTS};
 use anyhow::{bail, Context, Error};
@@ -34,6 +36,10 @@ pub(crate) fn load(document: &Path, options: &'opts CliOptions) -> Result<Self,
         Ok(Self { signature_toml, signature_toml_path, options })
     }
 
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.signature_toml.files.contains_key(name)
+    }
+
     pub(crate) fn read(&self, name: &str) -> Result<Option<Vec<u8>>, Error> {
         // Treat files not mentioned in `signature.toml` as missing.
         let Some(uuid) = self.signature_toml.files.get(name) else {
@@ -41,9 +47,9 @@ pub(crate) fn read(&self, name: &str) -> Result<Option<Vec<u8>>, Error> {
         };
 
         Ok(Some(
-            std::fs::read(self.options.s3_cache_dir.join(uuid.to_string()))
-                // Assume that if a file is in `signature.toml` it must exist in S3.
-                .context("this is a bootstrap bug (file is supposed to be cached)")
+            std::fs::read(self.options.storage.dir().join(uuid.to_string()))
+                // Assume that if a file is in `signature.toml` it must exist in the storage.
+                .context(self.options.storage.missing_file_hint())
                 .with_context(|| {
                     format!("failed to retrieve signature file {name} (with UUID {uuid})")
                 })?,
@@ -61,8 +67,8 @@ pub(crate) fn on_disk_as_tempfile(&self, name: &str) -> Result<Option<NamedTempF
             return Ok(None);
         };
 
-        let mut cache = File::open(self.options.s3_cache_dir.join(uuid.to_string()))
-            .context("this is a bootstrap bug (the file is supposed to be cached)")
+        let mut cache = File::open(self.options.storage.dir().join(uuid.to_string()))
+            .context(self.options.storage.missing_file_hint())
             .with_context(|| {
                 format!("failed to retrieve signature file {name} (with UUID {uuid})")
             })?;
@@ -77,28 +83,16 @@ pub(crate) fn on_disk_as_tempfile(&self, name: &str) -> Result<Option<NamedTempF
     pub(crate) fn write(&mut self, name: &str, contents: &[u8]) -> Result<(), Error> {
         let uuid = Uuid::new_v4();
 
-        // First off, we upload the file to S3, named after the UUID.
-        let mut command = Command::new("aws")
-            .args(["s3", "cp", "-"])
-            .arg(format!("s3://{}/{uuid}", self.options.s3_bucket))
-            .stdin(Stdio::piped())
-            .spawn()
-            .with_context(|| format!("failed to invoke AWS CLI to upload {name}"))?;
-
-        let mut stdin = command.stdin.take().unwrap();
-        stdin.write_all(contents)?;
-        drop(stdin); // Close stdin
-
-        let result = command
-            .wait()
-            .with_context(|| format!("failed to wait for AWS CLI completion (to upload {name})"))?;
-        if !result.success() {
-            bail!("uploading {name} to S3 exited with {result}");
+        // First off, if we're using S3 we upload the file to it, named after the UUID.
+        if let Storage::S3 { bucket, .. } = &self.options.storage {
+            upload_to_s3(bucket, uuid, name, contents)?;
         }
 
-        // Then we write the file in the local cache, to avoid having bootstrap read it from S3
-        // the next time it's invoked.
-        std::fs::write(self.options.s3_cache_dir.join(uuid.to_string()), contents)?;
+        // Then we write the file in the local directory. When using S3 this is the local cache,
+        // to avoid having bootstrap read it from S3 the next time it's invoked.
+        let dir = self.options.storage.dir();
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(uuid.to_string()), contents)?;
 
         // And finally we update `signature.toml` to record the UUID of the file.
         self.signature_toml.files.insert(name.into(), uuid);
@@ -120,6 +114,55 @@ fn persist(&self) -> Result<(), Error> {
     }
 }
 
+pub(crate) enum Storage {
+    /// Files are uploaded to an S3 bucket, and read from a cache populated by `src/bootstrap`.
+    S3 { bucket: String, cache_dir: PathBuf },
+    /// Files are stored in a local directory, without needing any network access.
+    Local { dir: PathBuf },
+}
+
+impl Storage {
+    fn dir(&self) -> &Path {
+        match self {
+            Storage::S3 { cache_dir, .. } => cache_dir,
+            Storage::Local { dir } => dir,
+        }
+    }
+
+    fn missing_file_hint(&self) -> String {
+        match self {
+            Storage::S3 { .. } => {
+                "this is a bootstrap bug (the file is supposed to be cached)".into()
+            }
+            Storage::Local { dir } => {
+                format!("the file is supposed to be stored in {}", dir.display())
+            }
+        }
+    }
+}
+
+fn upload_to_s3(bucket: &str, uuid: Uuid, name: &str, contents: &[u8]) -> Result<(), Error> {
+    let mut command = Command::new("aws")
+        .args(["s3", "cp", "-"])
+        .arg(format!("s3://{bucket}/{uuid}"))
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("failed to invoke AWS CLI to upload {name}"))?;
+
+    let mut stdin = command.stdin.take().unwrap();
+    stdin.write_all(contents)?;
+    drop(stdin); // Close stdin
+
+    let result = command
+        .wait()
+        .with_context(|| format!("failed to wait for AWS CLI completion (to upload {name})"))?;
+    if !result.success() {
+        bail!("uploading {name} to S3 exited with {result}");
+    }
+
+    Ok(())
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 struct Signature {
     files: BTreeMap<String, Uuid>,
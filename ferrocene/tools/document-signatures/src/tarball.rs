@@ -0,0 +1,336 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! In-process implementation of the reproducible tarball hashed in `pinned.toml`. The generated
+//! tarball is byte-for-byte identical to the one produced by GNU tar when invoked with the
+//! [`TAR_REPRODUCIBILITY_FLAGS`], without depending on the tar implementation (and the locale)
+//! of the host system.
+
+use anyhow::{bail, Context, Error};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{File, Metadata};
+use std::io::Write;
+use std::path::Path;
+
+pub(crate) const TAR_REPRODUCIBILITY_FLAGS: &[&str] = &[
+    // Use a consistent ordering for the files in the archive.
+    "--sort=name",
+    // Use a pinned date for the modification time, otherwise touching a file would change the hash
+    // of the archive.
+    "--mtime=2020-01-01T00:00:00Z",
+    // Tarballs include the UNIX owner and groups, which change between systems. Hardcode them to
+    // "0", and avoid looking up the corresponding name for ID 0.
+    "--owner=0",
+    "--group=0",
+    "--numeric-owner",
+    // Make all files readable by everyone and writable by the owner, preserving the executable
+    // bit. This is needed otherwise systems with non-reproducible umasks will generate different
+    // tarballs.
+    "--mode=u+rw,go+r,go-w",
+    // The default format includes non-deterministic bits. Use the GNU format, which omits them.
+    "--format=gnu",
+    // The generated documentation includes a directory called "signature" that contains data about
+    // the signature being generated right now. We exclude it from the hash as we can't know the
+    // signature contents in advance.
+    "--anchored",
+    "--exclude=./signature",
+];
+
+/// 2020-01-01T00:00:00Z, matching the `--mtime` flag.
+const MTIME: u64 = 1577836800;
+/// Matching the `--anchored` and `--exclude` flags.
+const EXCLUDED: &str = "./signature";
+
+const BLOCK_SIZE: usize = 512;
+/// GNU tar pads the archive to a multiple of its default blocking factor (20 blocks).
+const RECORD_SIZE: u64 = BLOCK_SIZE as u64 * 20;
+const NAME_FIELD_SIZE: usize = 100;
+
+const TYPE_FILE: u8 = b'0';
+const TYPE_HARD_LINK: u8 = b'1';
+const TYPE_SYMLINK: u8 = b'2';
+const TYPE_DIRECTORY: u8 = b'5';
+const TYPE_LONG_LINK: u8 = b'K';
+const TYPE_LONG_NAME: u8 = b'L';
+
+pub(crate) fn tarball_sha256(root: &Path) -> Result<String, Error> {
+    let mut hasher = Sha256::new();
+    write_tarball(root, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+pub(crate) fn write_tarball<W: Write>(root: &Path, output: W) -> Result<(), Error> {
+    let mut builder = Builder { output, written: 0, hard_links: HashMap::new() };
+    builder.append(root, ".".into())?;
+    builder.finish()
+}
+
+struct Builder<W: Write> {
+    output: W,
+    written: u64,
+    hard_links: HashMap<(u64, u64), String>,
+}
+
+impl<W: Write> Builder<W> {
+    fn append(&mut self, path: &Path, name: String) -> Result<(), Error> {
+        if name == EXCLUDED {
+            return Ok(());
+        }
+
+        let metadata = std::fs::symlink_metadata(path)
+            .with_context(|| format!("failed to read metadata of {}", path.display()))?;
+        let mode = normalized_mode(&metadata);
+
+        if metadata.is_dir() {
+            self.header(&format!("{name}/"), TYPE_DIRECTORY, mode, 0, "", MTIME)?;
+
+            let mut children = Vec::new();
+            for entry in std::fs::read_dir(path)? {
+                let file_name = entry?.file_name();
+                let Some(file_name) = file_name.to_str() else {
+                    bail!("non UTF-8 file name inside {}: {file_name:?}", path.display());
+                };
+                children.push(file_name.to_string());
+            }
+            // Strings are sorted by their bytes, like `--sort=name` does.
+            children.sort();
+
+            for child in children {
+                self.append(&path.join(&child), format!("{name}/{child}"))?;
+            }
+        } else if metadata.is_symlink() {
+            let target = std::fs::read_link(path)?;
+            let Some(target) = target.to_str() else {
+                bail!("non UTF-8 symlink target for {}: {target:?}", path.display());
+            };
+            self.header(&name, TYPE_SYMLINK, mode, 0, target, MTIME)?;
+        } else if metadata.is_file() {
+            if let Some(original) = self.hard_link_original(&metadata, &name) {
+                self.header(&name, TYPE_HARD_LINK, mode, 0, &original, MTIME)?;
+            } else {
+                self.header(&name, TYPE_FILE, mode, metadata.len(), "", MTIME)?;
+                let copied = std::io::copy(&mut File::open(path)?, &mut self.output)?;
+                if copied != metadata.len() {
+                    bail!("{} changed while it was being archived", path.display());
+                }
+                self.written += copied;
+                self.pad_to(BLOCK_SIZE as u64)?;
+            }
+        } else {
+            bail!("unsupported file type: {}", path.display());
+        }
+
+        Ok(())
+    }
+
+    fn header(
+        &mut self,
+        name: &str,
+        kind: u8,
+        mode: u32,
+        size: u64,
+        link_name: &str,
+        mtime: u64,
+    ) -> Result<(), Error> {
+        // Names not fitting in the header are stored in special entries preceding the header.
+        if link_name.len() > NAME_FIELD_SIZE {
+            self.long_name(TYPE_LONG_LINK, link_name)?;
+        }
+        if name.len() > NAME_FIELD_SIZE {
+            self.long_name(TYPE_LONG_NAME, name)?;
+        }
+
+        let mut block = [0; BLOCK_SIZE];
+        copy_truncated(&mut block[0..100], name);
+        write_octal(&mut block[100..108], mode.into())?;
+        write_octal(&mut block[108..116], 0)?; // uid
+        write_octal(&mut block[116..124], 0)?; // gid
+        write_octal(&mut block[124..136], size)?;
+        write_octal(&mut block[136..148], mtime)?;
+        block[156] = kind;
+        copy_truncated(&mut block[157..257], link_name);
+        // Magic and version of the GNU format. The user and group names are left empty, as with
+        // the `--numeric-owner` flag.
+        block[257..265].copy_from_slice(b"ustar  \0");
+
+        // The checksum is calculated with the checksum field filled with spaces, and is stored as
+        // six octal digits followed by a NUL and a space.
+        block[148..156].fill(b' ');
+        let checksum = block.iter().map(|&byte| u64::from(byte)).sum();
+        write_octal(&mut block[148..155], checksum)?;
+
+        self.output.write_all(&block)?;
+        self.written += BLOCK_SIZE as u64;
+        Ok(())
+    }
+
+    fn long_name(&mut self, kind: u8, name: &str) -> Result<(), Error> {
+        let size = name.len() as u64 + 1; // Including the trailing NUL.
+        self.header("././@LongLink", kind, 0o644, size, "", 0)?;
+        self.output.write_all(name.as_bytes())?;
+        self.output.write_all(&[0])?;
+        self.written += size;
+        self.pad_to(BLOCK_SIZE as u64)
+    }
+
+    fn hard_link_original(&mut self, metadata: &Metadata, name: &str) -> Option<String> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if metadata.nlink() > 1 {
+                let key = (metadata.dev(), metadata.ino());
+                if let Some(original) = self.hard_links.get(&key) {
+                    return Some(original.clone());
+                }
+                self.hard_links.insert(key, name.to_string());
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = (metadata, name);
+        None
+    }
+
+    fn pad_to(&mut self, multiple: u64) -> Result<(), Error> {
+        let padding = (multiple - self.written % multiple) % multiple;
+        self.output.write_all(&vec![0; padding as usize])?;
+        self.written += padding;
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), Error> {
+        // The end of the archive is marked by two empty blocks.
+        self.output.write_all(&[0; BLOCK_SIZE * 2])?;
+        self.written += BLOCK_SIZE as u64 * 2;
+        self.pad_to(RECORD_SIZE)?;
+        self.output.flush()?;
+        Ok(())
+    }
+}
+
+/// Apply `--mode=u+rw,go+r,go-w` to the permissions of the file.
+fn normalized_mode(metadata: &Metadata) -> u32 {
+    #[cfg(unix)]
+    let mode = {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.permissions().mode() & 0o7777
+    };
+    #[cfg(not(unix))]
+    let mode = if metadata.is_dir() { 0o755 } else { 0o644 };
+
+    (mode | 0o644) & !0o022
+}
+
+fn copy_truncated(field: &mut [u8], value: &str) {
+    let len = value.len().min(field.len());
+    field[..len].copy_from_slice(&value.as_bytes()[..len]);
+}
+
+/// Write a zero-padded octal number followed by a NUL, filling the whole field.
+fn write_octal(field: &mut [u8], value: u64) -> Result<(), Error> {
+    let digits = field.len() - 1;
+    let octal = format!("{value:0digits$o}");
+    if octal.len() > digits {
+        bail!("{value} does not fit in a {digits} digits tar header field");
+    }
+    field[..digits].copy_from_slice(octal.as_bytes());
+    field[digits] = 0;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::process::Command;
+
+    fn gnu_tar_sha256(root: &Path) -> String {
+        let version = Command::new("tar").arg("--version").output().expect("failed to invoke tar");
+        assert!(
+            String::from_utf8_lossy(&version.stdout).contains("GNU tar"),
+            "GNU tar is required to compare the generated tarballs"
+        );
+
+        let output = Command::new("tar")
+            .args(TAR_REPRODUCIBILITY_FLAGS)
+            .arg("-C")
+            .arg(root)
+            .args(["-c", "."])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "failed to invoke GNU tar");
+        hex::encode(Sha256::digest(&output.stdout))
+    }
+
+    fn assert_same_as_gnu_tar(root: &Path) {
+        assert_eq!(gnu_tar_sha256(root), tarball_sha256(root).unwrap(), "{}", root.display());
+    }
+
+    #[test]
+    fn test_matches_gnu_tar() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path();
+        let write = |name: &str, contents: &[u8]| -> Result<PathBuf, Error> {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap())?;
+            std::fs::write(&path, contents)?;
+            Ok(path)
+        };
+
+        write("index.html", b"<html></html>")?;
+        write("empty.txt", b"")?;
+        write("block.bin", &[1; BLOCK_SIZE])?;
+        write("_static/search.js", &[b'a'; 3000])?;
+        for name in ["a", "B", "a.b", "a-b", "ab", "a_b"] {
+            write(&format!("sorting/{name}"), name.as_bytes())?;
+        }
+        for len in [99, 100, 101, 250] {
+            write(&format!("long/{}", "n".repeat(len - "./long/".len())), b"long")?;
+        }
+        write("signature/pinned.toml", b"excluded")?;
+        write("nested/signature/included.txt", b"included")?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let script = write("script.sh", b"#!/bin/sh\n")?;
+            std::fs::set_permissions(script, std::fs::Permissions::from_mode(0o700))?;
+            let private = write("private.txt", b"private")?;
+            std::fs::set_permissions(private, std::fs::Permissions::from_mode(0o600))?;
+            let writable = write("writable.txt", b"writable")?;
+            std::fs::set_permissions(writable, std::fs::Permissions::from_mode(0o666))?;
+
+            std::os::unix::fs::symlink("index.html", root.join("link.html"))?;
+            std::os::unix::fs::symlink("x".repeat(150), root.join("long-link"))?;
+            std::fs::hard_link(root.join("index.html"), root.join("hard.html"))?;
+        }
+
+        assert_same_as_gnu_tar(root);
+        Ok(())
+    }
+
+    #[test]
+    fn test_matches_gnu_tar_fixture() {
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("test_data/document");
+
+        // The hash must not depend on the checkout (permissions, umask or modification times),
+        // otherwise signed documents couldn't be verified on other machines. Generated with:
+        // tar -C test_data/document $TAR_REPRODUCIBILITY_FLAGS -c . | sha256sum
+        assert_eq!(
+            "21f02395d209698bc38e07ee8908cb47bfa512c0722e8740ef90b772d45b13b5",
+            tarball_sha256(&fixture).unwrap()
+        );
+        assert_same_as_gnu_tar(&fixture);
+    }
+
+    #[test]
+    fn test_write_octal() {
+        let mut field = [b'x'; 8];
+        write_octal(&mut field, 0o644).unwrap();
+        assert_eq!(b"0000644\0", &field);
+
+        let mut field = [0; 4];
+        assert!(write_octal(&mut field, 0o7777).is_err());
+    }
+}
@@ -1,11 +1,14 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
-use crate::config::Config;
+use crate::config::{Config, Role};
+use crate::cosign_bundle::RawCosignBundle;
+use crate::local_key::LocalKeyBundle;
 use crate::pinned::Pinned;
 use crate::signature_files::SignatureFiles;
 use crate::CliOptions;
 use anyhow::{anyhow, Context, Error};
+use std::collections::BTreeMap;
 use std::io::Read;
 use std::path::Path;
 use std::process::Command;
@@ -17,51 +20,120 @@ pub(crate) fn verify(
 ) -> Result<(), Error> {
     let signature_files = SignatureFiles::load(source_dir, options)?;
 
-    let pinned_toml = if let Some(mut file) = signature_files.on_disk_as_tempfile("pinned.toml")? {
-        let mut contents = Vec::new();
-        file.read_to_end(&mut contents)?;
+    let (pinned_toml, pinned_contents) =
+        if let Some(mut file) = signature_files.on_disk_as_tempfile("pinned.toml")? {
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents)?;
 
-        let existing: Pinned = toml::from_slice(&contents)?;
-        let expected = Pinned::generate(output_dir)?;
+            let existing: Pinned = toml::from_slice(&contents)?;
+            let expected = Pinned::generate(output_dir)?;
 
-        if existing != expected {
-            if existing.document_id != expected.document_id {
-                eprintln!("existing document id: {}", existing.document_id);
-                eprintln!("expected document id: {}", expected.document_id);
+            if existing != expected {
+                if existing.document_id != expected.document_id {
+                    eprintln!("existing document id: {}", existing.document_id);
+                    eprintln!("expected document id: {}", expected.document_id);
+                }
+                if existing.tarball_sha256 != expected.tarball_sha256 {
+                    eprintln!("existing tarball sha256: {}", existing.tarball_sha256);
+                    eprintln!("expected tarball sha256: {}", expected.tarball_sha256);
+                }
+                anyhow::bail!("pinned documentation file outdated");
             }
-            if existing.tarball_sha256 != expected.tarball_sha256 {
-                eprintln!("existing tarball sha256: {}", existing.tarball_sha256);
-                eprintln!("expected tarball sha256: {}", expected.tarball_sha256);
-            }
-            anyhow::bail!("pinned documentation file outdated");
-        }
 
-        file
-    } else {
-        // The document was not signed.
-        return Ok(());
-    };
+            (file, contents)
+        } else {
+            // The document was not signed.
+            return Ok(());
+        };
 
     let config = Config::load(source_dir)?;
+    let mut signed_at = BTreeMap::new();
+    let mut problems = Vec::new();
     for (role_name, role) in config.roles.iter() {
-        let bundle = signature_files
-            .on_disk_as_tempfile(&format!("{role_name}.cosign-bundle"))
-            .with_context(|| format!("failed to read signature for role {role_name}"))?
-            .ok_or_else(|| anyhow!("missing signature file for role {role_name}"))?;
+        // Missing signatures are reported later, only for the roles required to sign.
+        if !signature_files.contains(&role.signature_file_name(role_name)) {
+            continue;
+        }
 
         eprintln!("checking role {role_name}");
-        let status = Command::new(&options.cosign_binary)
-            .arg("verify-blob")
-            .arg(pinned_toml.path())
-            .arg("--bundle")
-            .arg(bundle.path())
-            .args(["--certificate-identity", &role.email])
-            .args(["--certificate-oidc-issuer", role.idp()?.url])
-            .status()?;
-        if !status.success() {
-            anyhow::bail!("failed to verify signature for role {role_name} (exited with {status})");
+        let result = if role.public_key.is_some() {
+            verify_local_key(role_name, role, &pinned_contents, &signature_files)
+        } else {
+            verify_cosign(role_name, role, &config, pinned_toml.path(), &signature_files, options)
+        };
+        match result {
+            Ok(time) => {
+                signed_at.insert(role_name.as_str(), time);
+            }
+            Err(err) => problems.push(format!("{err:#}")),
+        }
+    }
+
+    let required_roles = config.required_roles();
+    for role_name in &required_roles {
+        let role = &config.roles[*role_name];
+        if !signature_files.contains(&role.signature_file_name(role_name)) {
+            problems.push(format!("missing signature for required role {role_name}"));
+        }
+    }
+
+    problems.extend(config.check_signing_order(&signed_at));
+
+    if !problems.is_empty() {
+        eprintln!();
+        for problem in &problems {
+            eprintln!("error: {problem}");
         }
+        anyhow::bail!("the document signatures are not valid");
     }
 
     Ok(())
 }
+
+fn verify_cosign(
+    role_name: &str,
+    role: &Role,
+    config: &Config,
+    pinned_toml: &Path,
+    signature_files: &SignatureFiles<'_>,
+    options: &CliOptions,
+) -> Result<u64, Error> {
+    let bundle = signature_files
+        .on_disk_as_tempfile(&role.signature_file_name(role_name))
+        .with_context(|| format!("failed to read signature for role {role_name}"))?
+        .ok_or_else(|| anyhow!("missing signature file for role {role_name}"))?;
+
+    let status = Command::new(options.cosign_binary()?)
+        .arg("verify-blob")
+        .arg(pinned_toml)
+        .arg("--bundle")
+        .arg(bundle.path())
+        .args(["--certificate-identity", &role.email])
+        .args(["--certificate-oidc-issuer", &config.idp(role)?.url])
+        .status()?;
+    if !status.success() {
+        anyhow::bail!("failed to verify signature for role {role_name} (exited with {status})");
+    }
+
+    Ok(RawCosignBundle::load(bundle.path())?.signed_at())
+}
+
+fn verify_local_key(
+    role_name: &str,
+    role: &Role,
+    pinned_contents: &[u8],
+    signature_files: &SignatureFiles<'_>,
+) -> Result<u64, Error> {
+    let bundle = signature_files
+        .read(&role.signature_file_name(role_name))
+        .with_context(|| format!("failed to read signature for role {role_name}"))?
+        .ok_or_else(|| anyhow!("missing signature file for role {role_name}"))?;
+    let bundle: LocalKeyBundle = serde_json::from_slice(&bundle)
+        .with_context(|| format!("failed to parse signature for role {role_name}"))?;
+
+    bundle
+        .verify(role, pinned_contents)
+        .with_context(|| format!("failed to verify signature for role {role_name}"))?;
+
+    Ok(bundle.signed_at())
+}
@@ -0,0 +1 @@
+var search = "fixture";
@@ -0,0 +1,225 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+// SPDX-FileCopyrightText: The Rust Project Developers (see https://thanks.rust-lang.org)
+
+use crate::compression::{CompressionFormat, CompressionFormats, CompressionProfile};
+use crate::scripter::Scripter;
+use crate::signatures::hash_file;
+use crate::tarballer::Tarballer;
+use crate::util::{copy_recursive, create_dir_all, create_new_file, path_to_str, remove_dir_all};
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use tar::Archive;
+use walkdir::WalkDir;
+
+#[derive(Debug, clap::Args)]
+pub struct Combiner {
+    /// The name of the product, for display
+    #[clap(value_name = "NAME")]
+    #[clap(long, default_value = "Product")]
+    product_name: String,
+    /// The name of the package tarball
+    #[clap(value_name = "NAME")]
+    #[clap(long, default_value = "package")]
+    package_name: String,
+    /// The directory under lib/ where the manifest lives
+    #[clap(value_name = "DIR")]
+    #[clap(long, default_value = "packagelib")]
+    rel_manifest_dir: String,
+    /// The string to print after successful installation
+    #[clap(value_name = "MESSAGE")]
+    #[clap(long, default_value = "Installed.")]
+    success_message: String,
+    /// Places to look for legacy manifests to uninstall
+    #[clap(value_name = "DIRS")]
+    #[clap(long, default_value = "")]
+    legacy_manifest_dirs: String,
+    /// Tarballs generated by the `generate` subcommand to combine
+    #[clap(value_name = "FILE,FILE")]
+    #[clap(long, default_value = "")]
+    input_tarballs: String,
+    /// Directory containing files that should not be installed
+    #[clap(value_name = "DIR")]
+    #[clap(long, default_value = "")]
+    non_installed_overlay: String,
+    /// The directory to do temporary work
+    #[clap(value_name = "DIR")]
+    #[clap(long, default_value = "./workdir")]
+    work_dir: String,
+    /// The location to put the final image and tarball
+    #[clap(value_name = "DIR")]
+    #[clap(long, default_value = "./dist")]
+    output_dir: String,
+    /// The profile used to compress the tarball.
+    #[clap(value_name = "FORMAT", default_value_t)]
+    #[clap(long)]
+    compression_profile: CompressionProfile,
+    /// The formats used to compress the tarball
+    #[clap(value_name = "FORMAT", default_value_t)]
+    #[clap(long)]
+    compression_formats: CompressionFormats,
+    /// The commit SHA of the current build
+    #[clap(long, value_name = "SHA")]
+    ferrocene_commit_sha: Option<String>,
+    /// The ARN of the AWS KMS key used to sign the criticalup manifest
+    #[clap(long, value_name = "ARN")]
+    ferrocene_signing_kms_key_arn: Option<String>,
+    /// Path of the local key file used to sign the criticalup manifest
+    #[clap(long, value_name = "FILE")]
+    ferrocene_signing_local_key: Option<String>,
+    /// Path prefix that should only contain Ferrocene files.
+    #[clap(long, value_name = "PATH")]
+    ferrocene_managed_prefix: Vec<String>,
+    /// Path of a binary that should be proxied by criticalup.
+    #[clap(long, value_name = "PATH")]
+    ferrocene_proxied_binary: Vec<String>,
+    /// Name of the Ferrocene component.
+    #[clap(long, value_name = "NAME")]
+    ferrocene_component: Option<String>,
+}
+
+impl Combiner {
+    /// Combines the installer tarballs.
+    pub fn run(self) -> Result<()> {
+        let Self {
+            // These arguments are emitted by the build step but they serve no purpose when
+            // combining tarballs: the criticalup manifests were already signed when generating
+            // each input tarball, and they are included as-is in the combined tarball.
+            ferrocene_commit_sha: _,
+            ferrocene_signing_kms_key_arn: _,
+            ferrocene_signing_local_key: _,
+            ferrocene_managed_prefix: _,
+            ferrocene_proxied_binary: _,
+            ferrocene_component: _,
+            //
+            product_name,
+            package_name,
+            rel_manifest_dir,
+            success_message,
+            legacy_manifest_dirs,
+            input_tarballs,
+            non_installed_overlay,
+            work_dir,
+            output_dir,
+            compression_profile,
+            compression_formats,
+        } = self;
+
+        create_dir_all(&work_dir)?;
+        let package_dir = Path::new(&work_dir).join(&package_name);
+        if package_dir.exists() {
+            remove_dir_all(&package_dir)?;
+        }
+        create_dir_all(&package_dir)?;
+
+        // Merge each input tarball into its own component directory, using the same layout as
+        // upstream's rust-installer so that the install script can be used.
+        let mut components = Vec::new();
+        let mut installed = InstalledFiles::default();
+        for input_tarball in input_tarballs.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let compression = CompressionFormat::detect_from_path(input_tarball)
+                .ok_or_else(|| anyhow!("couldn't figure out the format of {input_tarball}"))?;
+            let component =
+                input_tarball.trim_end_matches(&format!(".tar.{}", compression.extension()));
+            let component = path_to_str(Path::new(component).file_name().unwrap().as_ref())?;
+
+            let component_dir = package_dir.join(component);
+            if component_dir.exists() {
+                bail!("component {component} is present in multiple input tarballs");
+            }
+            create_dir_all(&component_dir)?;
+
+            let mut archive = Archive::new(compression.decode(input_tarball)?);
+            archive.set_preserve_permissions(true);
+            archive.unpack(&component_dir).with_context(|| {
+                format!("unable to extract '{input_tarball}' into '{}'", component_dir.display())
+            })?;
+
+            let files = installed.record(component, &component_dir)?;
+            let mut manifest = create_new_file(component_dir.join("manifest.in"))?;
+            for file in files {
+                writeln!(manifest, "file:{file}").context("failed to write manifest.in")?;
+            }
+
+            components.push(component.to_string());
+        }
+
+        let mut components_file = create_new_file(package_dir.join("components"))?;
+        for component in &components {
+            writeln!(components_file, "{component}").context("failed to write components")?;
+        }
+
+        let version = package_dir.join("rust-installer-version");
+        writeln!(create_new_file(version)?, "{}", crate::RUST_INSTALLER_VERSION)
+            .context("failed to write new installer version")?;
+
+        if !non_installed_overlay.is_empty() {
+            copy_recursive(non_installed_overlay.as_ref(), &package_dir)?;
+        }
+
+        let output_script = package_dir.join("install.sh");
+        Scripter {
+            product_name,
+            rel_manifest_dir,
+            success_message,
+            legacy_manifest_dirs,
+            output_script: path_to_str(&output_script)?.into(),
+        }
+        .run()?;
+
+        // Make the tarballs
+        create_dir_all(&output_dir)?;
+        let output = Path::new(&output_dir).join(&package_name);
+        let tarballer = Tarballer {
+            work_dir,
+            input: package_name,
+            output: path_to_str(&output)?.into(),
+            compression_profile,
+            compression_formats,
+        };
+        tarballer.run()?;
+
+        Ok(())
+    }
+}
+
+/// Files installed by the components merged so far. Each component includes a criticalup
+/// manifest signed when its tarball was generated, which would not be valid anymore if another
+/// component overwrote one of its files with different contents.
+#[derive(Default)]
+struct InstalledFiles {
+    files: HashMap<String, (String, Vec<u8>)>,
+}
+
+impl InstalledFiles {
+    /// Records the files of a component, returning their sorted paths.
+    fn record(&mut self, component: &str, component_dir: &Path) -> Result<Vec<String>> {
+        let mut paths = Vec::new();
+        for entry in WalkDir::new(component_dir).min_depth(1) {
+            let entry = entry?;
+            if entry.file_type().is_dir() {
+                continue;
+            }
+            let path = path_to_str(entry.path().strip_prefix(component_dir)?)?.to_string();
+
+            let hash = if entry.path_is_symlink() {
+                path_to_str(&std::fs::read_link(entry.path())?)?.as_bytes().to_vec()
+            } else {
+                hash_file(entry.path())?
+            };
+            if let Some((other, other_hash)) = self.files.get(&path) {
+                if *other_hash != hash {
+                    bail!("{path} is installed by both {other} and {component} with different contents");
+                }
+            } else {
+                self.files.insert(path.clone(), (component.to_string(), hash));
+            }
+
+            paths.push(path);
+        }
+        paths.sort();
+        Ok(paths)
+    }
+}
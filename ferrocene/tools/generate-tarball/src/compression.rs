@@ -3,8 +3,16 @@
 // SPDX-FileCopyrightText: The Rust Project Developers (see https://thanks.rust-lang.org)
 
 use anyhow::{Context, Error};
+use flate2::{read::GzDecoder, write::GzEncoder};
 use rayon::prelude::*;
-use std::{convert::TryFrom, fmt, io::Write, path::Path, str::FromStr};
+use std::{
+    convert::TryFrom,
+    fmt,
+    io::{Read, Write},
+    path::Path,
+    str::FromStr,
+};
+use xz2::read::XzDecoder;
 use xz2::write::XzEncoder;
 
 #[derive(Default, Debug, Copy, Clone)]
@@ -40,13 +48,26 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 
 #[derive(Debug, Copy, Clone)]
 pub enum CompressionFormat {
+    Gz,
     Xz,
+    Zstd,
 }
 
 impl CompressionFormat {
+    pub(crate) fn detect_from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("gz") => Some(CompressionFormat::Gz),
+            Some("xz") => Some(CompressionFormat::Xz),
+            Some("zst") => Some(CompressionFormat::Zstd),
+            _ => None,
+        }
+    }
+
     pub(crate) fn extension(&self) -> &'static str {
         match self {
+            CompressionFormat::Gz => "gz",
             CompressionFormat::Xz => "xz",
+            CompressionFormat::Zstd => "zst",
         }
     }
 
@@ -65,6 +86,14 @@ pub(crate) fn encode(
         let file = crate::util::create_new_file(path)?;
 
         Ok(match self {
+            CompressionFormat::Gz => Box::new(GzEncoder::new(
+                file,
+                match profile {
+                    CompressionProfile::Fast => flate2::Compression::fast(),
+                    CompressionProfile::Balanced => flate2::Compression::new(6),
+                    CompressionProfile::Best => flate2::Compression::best(),
+                },
+            )),
             CompressionFormat::Xz => {
                 let encoder = match profile {
                     CompressionProfile::Fast => {
@@ -85,6 +114,27 @@ pub(crate) fn encode(
                 let compressor = XzEncoder::new_stream(std::io::BufWriter::new(file), encoder);
                 Box::new(compressor)
             }
+            CompressionFormat::Zstd => {
+                let level = match profile {
+                    CompressionProfile::Fast => 1,
+                    CompressionProfile::Balanced => zstd::DEFAULT_COMPRESSION_LEVEL,
+                    CompressionProfile::Best => 19,
+                };
+                let compressor = zstd::Encoder::new(std::io::BufWriter::new(file), level)
+                    .context("failed to create the .zst encoder")?;
+                Box::new(compressor)
+            }
+        })
+    }
+
+    pub(crate) fn decode(&self, path: impl AsRef<Path>) -> Result<Box<dyn Read>, Error> {
+        let file = crate::util::open_file(path.as_ref())?;
+        Ok(match self {
+            CompressionFormat::Gz => Box::new(GzDecoder::new(file)),
+            CompressionFormat::Xz => Box::new(XzDecoder::new(file)),
+            CompressionFormat::Zstd => {
+                Box::new(zstd::Decoder::new(file).context("failed to create the .zst decoder")?)
+            }
         })
     }
 }
@@ -100,7 +150,9 @@ fn try_from(value: &str) -> Result<Self, Self::Error> {
         let mut parsed = Vec::new();
         for format in value.split(',') {
             match format.trim() {
+                "gz" => parsed.push(CompressionFormat::Gz),
                 "xz" => parsed.push(CompressionFormat::Xz),
+                "zst" => parsed.push(CompressionFormat::Zstd),
                 other => anyhow::bail!("unknown compression format: {}", other),
             }
         }
@@ -122,12 +174,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             if i != 0 {
                 write!(f, ",")?;
             }
-            fmt::Display::fmt(
-                match format {
-                    CompressionFormat::Xz => "xz",
-                },
-                f,
-            )?;
+            fmt::Display::fmt(format.extension(), f)?;
         }
         Ok(())
     }
@@ -149,6 +196,13 @@ pub(crate) trait Encoder: Send + Write {
     fn finish(self: Box<Self>) -> Result<(), Error>;
 }
 
+impl<W: Send + Write> Encoder for GzEncoder<W> {
+    fn finish(self: Box<Self>) -> Result<(), Error> {
+        GzEncoder::finish(*self).context("failed to finish .gz file")?;
+        Ok(())
+    }
+}
+
 impl<W: Send + Write> Encoder for XzEncoder<W> {
     fn finish(self: Box<Self>) -> Result<(), Error> {
         XzEncoder::finish(*self).context("failed to finish .xz file")?;
@@ -156,6 +210,13 @@ fn finish(self: Box<Self>) -> Result<(), Error> {
     }
 }
 
+impl<W: Send + Write> Encoder for zstd::Encoder<'static, W> {
+    fn finish(self: Box<Self>) -> Result<(), Error> {
+        zstd::Encoder::finish(*self).context("failed to finish .zst file")?;
+        Ok(())
+    }
+}
+
 pub(crate) struct CombinedEncoder {
     encoders: Vec<Box<dyn Encoder>>,
 }
@@ -5,13 +5,21 @@
 mod signatures;
 mod util;
 
+mod combiner;
 mod compression;
 mod generator;
+mod scripter;
 mod tarballer;
+mod verifier;
 
 use anyhow::Context;
 use clap::{self, Parser};
 
+pub use crate::verifier::{Problem, VerificationError};
+
+/// Version of the installer layout, shared with upstream's rust-installer.
+pub(crate) const RUST_INSTALLER_VERSION: u32 = 3;
+
 #[derive(Parser)]
 pub struct CommandLine {
     #[clap(subcommand)]
@@ -21,14 +29,17 @@ pub struct CommandLine {
 impl CommandLine {
     pub fn run(self) -> anyhow::Result<()> {
         match self.command {
-            Subcommand::Combine => anyhow::bail!("the `combine` subcommand is not supported"),
+            Subcommand::Combine(combiner) => combiner.run().context("failed to combine installers"),
             Subcommand::Generate(generator) => {
                 generator.run().context("failed to generate installer")
             }
-            Subcommand::Script => anyhow::bail!("the `scripter` subcommand is not supported"),
+            Subcommand::Script(scripter) => {
+                scripter.run().context("failed to generate installation script")
+            }
             Subcommand::Tarball(tarballer) => {
                 tarballer.run().context("failed to generate tarballs")
             }
+            Subcommand::Verify(verifier) => verifier.run().context("failed to verify tarball"),
         }
     }
 }
@@ -36,7 +47,8 @@ pub fn run(self) -> anyhow::Result<()> {
 #[derive(clap::Subcommand)]
 enum Subcommand {
     Generate(generator::Generator),
-    Combine,
-    Script,
+    Combine(combiner::Combiner),
+    Script(scripter::Scripter),
     Tarball(tarballer::Tarballer),
+    Verify(verifier::Verifier),
 }
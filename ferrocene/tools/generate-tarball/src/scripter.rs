@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+// SPDX-FileCopyrightText: The Rust Project Developers (see https://thanks.rust-lang.org)
+
+use crate::util::create_new_executable;
+use anyhow::{Context, Result};
+use std::io::Write;
+
+// The install script is shared with upstream's rust-installer, to avoid diverging from it.
+const TEMPLATE: &str = include_str!("../../../../src/tools/rust-installer/install-template.sh");
+
+#[derive(Debug, clap::Args)]
+pub struct Scripter {
+    /// The name of the product, for display
+    #[clap(value_name = "NAME")]
+    #[clap(long, default_value = "Product")]
+    pub(crate) product_name: String,
+    /// The directory under lib/ where the manifest lives
+    #[clap(value_name = "DIR")]
+    #[clap(long, default_value = "manifestlib")]
+    pub(crate) rel_manifest_dir: String,
+    /// The string to print after successful installation
+    #[clap(value_name = "MESSAGE")]
+    #[clap(long, default_value = "Installed.")]
+    pub(crate) success_message: String,
+    /// Places to look for legacy manifests to uninstall
+    #[clap(value_name = "DIRS")]
+    #[clap(long, default_value = "")]
+    pub(crate) legacy_manifest_dirs: String,
+    /// The name of the output script
+    #[clap(value_name = "FILE")]
+    #[clap(long, default_value = "install.sh")]
+    pub(crate) output_script: String,
+}
+
+impl Scripter {
+    /// Generates the actual installer script
+    pub fn run(self) -> Result<()> {
+        // Replace dashes in the product name and success message with spaces, like upstream's
+        // rust-installer does (its argument handling used to botch spaces).
+        let product_name = self.product_name.replace('-', " ");
+        let success_message = self.success_message.replace('-', " ");
+
+        let script = TEMPLATE
+            .replace("%%TEMPLATE_PRODUCT_NAME%%", &sh_quote(&product_name))
+            .replace("%%TEMPLATE_REL_MANIFEST_DIR%%", &self.rel_manifest_dir)
+            .replace("%%TEMPLATE_SUCCESS_MESSAGE%%", &sh_quote(&success_message))
+            .replace("%%TEMPLATE_LEGACY_MANIFEST_DIRS%%", &sh_quote(&self.legacy_manifest_dirs))
+            .replace(
+                "%%TEMPLATE_RUST_INSTALLER_VERSION%%",
+                &sh_quote(&crate::RUST_INSTALLER_VERSION),
+            );
+
+        create_new_executable(&self.output_script)?
+            .write_all(script.as_ref())
+            .with_context(|| format!("failed to write output script '{}'", self.output_script))?;
+
+        Ok(())
+    }
+}
+
+fn sh_quote<T: ToString>(s: &T) -> String {
+    // We'll single-quote the whole thing, so first replace single-quotes with
+    // '"'"' (leave quoting, double-quote one `'`, re-enter single-quoting)
+    format!("'{}'", s.to_string().replace('\'', r#"'"'"'"#))
+}
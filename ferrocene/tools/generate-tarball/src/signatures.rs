@@ -2,15 +2,22 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 // SPDX-FileCopyrightText: The Rust Project Developers (see https://thanks.rust-lang.org)
 
-use anyhow::{anyhow, Error};
-use criticaltrust::keys::{AwsKmsKeyPair, KeyPair, KeyRole};
+use anyhow::{anyhow, Context, Error};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use criticaltrust::keys::newtypes::{PayloadBytes, SignatureBytes};
+use criticaltrust::keys::{AwsKmsKeyPair, KeyAlgorithm, KeyPair, KeyRole, PublicKey};
 use criticaltrust::manifests::{ManifestVersion, Package, PackageFile, PackageManifest};
-use criticaltrust::signatures::SignedPayload;
+use criticaltrust::signatures::{Keychain, SignedPayload};
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey};
+use p256::pkcs8::{DecodePrivateKey, EncodePublicKey};
+use serde::Deserialize;
 use sha2::{Digest, Sha256};
 use std::collections::HashSet;
 use std::fs::File;
 use std::os::unix::prelude::MetadataExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::runtime::Runtime;
 
 pub(crate) struct SignatureContext<'a> {
@@ -33,6 +40,107 @@ pub(crate) fn sign_manifest_with_aws_kms(
     sign_manifest(ctx, &key)
 }
 
+pub(crate) fn sign_manifest_with_local_key(
+    ctx: &SignatureContext<'_>,
+    key_path: &Path,
+) -> Result<(), Error> {
+    let key = LocalKey::load(key_path)?;
+    if key.public.role != KeyRole::Packages {
+        anyhow::bail!("the key in {} is not a packages key", key_path.display());
+    }
+
+    let key = key
+        .into_key_pair()
+        .with_context(|| format!("the key in {} is not valid", key_path.display()))?;
+    sign_manifest(ctx, &key)
+}
+
+/// Key pair stored in a local file, used to sign the manifest without access to AWS KMS.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct LocalKey {
+    public: PublicKey,
+    /// Base64-encoded PKCS#8 DER private key.
+    private_key: String,
+}
+
+impl LocalKey {
+    fn load(path: &Path) -> Result<Self, Error> {
+        let contents = std::fs::read(path)
+            .with_context(|| format!("failed to read the key in {}", path.display()))?;
+        serde_json::from_slice(&contents)
+            .with_context(|| format!("failed to parse the key in {}", path.display()))
+    }
+
+    fn into_key_pair(self) -> Result<LocalKeyPair, Error> {
+        // criticaltrust only allows loading private keys stored in AWS KMS, so the PKCS#8 key is
+        // parsed here. The only algorithm it supports is ECDSA P-256 with SHA-256.
+        if self.public.algorithm != KeyAlgorithm::EcdsaP256Sha256Asn1SpkiDer {
+            anyhow::bail!("unsupported key algorithm {:?}", self.public.algorithm);
+        }
+
+        let private_key =
+            BASE64.decode(&self.private_key).context("the private key is not valid base64")?;
+        let signing_key = SigningKey::from_pkcs8_der(&private_key)
+            .map_err(|e| anyhow!("the private key is not valid PKCS#8: {e}"))?;
+
+        let derived_public = signing_key
+            .verifying_key()
+            .to_public_key_der()
+            .map_err(|e| anyhow!("failed to encode the public key: {e}"))?;
+        if derived_public.as_bytes() != self.public.public.as_bytes() {
+            anyhow::bail!("the private key does not match the public key");
+        }
+
+        Ok(LocalKeyPair { public: self.public, signing_key })
+    }
+}
+
+struct LocalKeyPair {
+    public: PublicKey,
+    signing_key: SigningKey,
+}
+
+impl KeyPair for LocalKeyPair {
+    fn public(&self) -> &PublicKey {
+        &self.public
+    }
+
+    fn sign(
+        &self,
+        data: &PayloadBytes<'_>,
+    ) -> Result<SignatureBytes<'static>, criticaltrust::Error> {
+        // Same encoding as the signatures produced by criticaltrust for this algorithm.
+        let signature: Signature = self.signing_key.sign(data.as_bytes());
+        Ok(SignatureBytes::owned(signature.to_der().as_bytes().to_vec()))
+    }
+}
+
+/// Keychain used to verify the manifests, in the same format pinned in ferrocene-self-test: the
+/// root key and the keys it signed.
+#[derive(Deserialize)]
+struct KeychainFile {
+    root: PublicKey,
+    keys: Vec<SignedPayload<PublicKey>>,
+}
+
+pub(crate) fn load_keychain(path: &Path) -> Result<Keychain, Error> {
+    let contents = std::fs::read(path)
+        .with_context(|| format!("failed to read the keychain in {}", path.display()))?;
+    let file: KeychainFile = serde_json::from_slice(&contents)
+        .with_context(|| format!("failed to parse the keychain in {}", path.display()))?;
+
+    let mut keychain = Keychain::new(&file.root)?;
+    for key in &file.keys {
+        keychain.load(key)?;
+    }
+    Ok(keychain)
+}
+
+pub(crate) fn manifests_dir(package_dir: &Path) -> PathBuf {
+    package_dir.join("share").join("criticaltrust").join("ferrocene")
+}
+
 fn sign_manifest(ctx: &SignatureContext<'_>, key_pair: &dyn KeyPair) -> Result<(), Error> {
     let mut package = Package {
         product: "ferrocene".into(),
@@ -51,7 +159,7 @@ fn sign_manifest(ctx: &SignatureContext<'_>, key_pair: &dyn KeyPair) -> Result<(
     let mut signed = SignedPayload::new(&package)?;
     signed.add_signature(key_pair)?;
 
-    let dest_dir = ctx.package_dir.join("share").join("criticaltrust").join("ferrocene");
+    let dest_dir = manifests_dir(ctx.package_dir);
     std::fs::create_dir_all(&dest_dir)?;
     std::fs::write(
         &dest_dir.join(format!("{}.json", ctx.component)),
@@ -88,7 +196,7 @@ fn collect_files(
     Ok(())
 }
 
-fn hash_file(path: &Path) -> Result<Vec<u8>, Error> {
+pub(crate) fn hash_file(path: &Path) -> Result<Vec<u8>, Error> {
     let mut sha256 = Sha256::new();
     let mut contents = File::open(path)?;
     std::io::copy(&mut contents, &mut sha256)?;
@@ -152,6 +260,48 @@ fn test_sign_manifest() -> Result<(), Error> {
         Ok(())
     }
 
+    #[test]
+    fn test_local_key_signatures_are_valid() -> Result<(), Error> {
+        let key = generate_local_key()?;
+        let key = key.into_key_pair()?;
+
+        let payload = PayloadBytes::borrowed(b"Hello world");
+        let signature = key.sign(&payload)?;
+        key.public().verify(KeyRole::Packages, &payload, &signature)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_local_key_must_match_public_key() -> Result<(), Error> {
+        let mut key = generate_local_key()?;
+        key.public = generate_local_key()?.public;
+
+        assert_eq!(
+            "the private key does not match the public key",
+            key.into_key_pair().err().unwrap().to_string()
+        );
+
+        Ok(())
+    }
+
+    fn generate_local_key() -> Result<LocalKey, Error> {
+        use p256::pkcs8::EncodePrivateKey;
+
+        let private_key = p256::SecretKey::random(&mut rand_core::OsRng);
+
+        let public = PublicKey {
+            role: KeyRole::Packages,
+            algorithm: KeyAlgorithm::EcdsaP256Sha256Asn1SpkiDer,
+            expiry: None,
+            public: criticaltrust::keys::newtypes::PublicKeyBytes::owned(
+                private_key.public_key().to_public_key_der().map_err(Error::msg)?.to_vec(),
+            ),
+        };
+        let private_key_der = private_key.to_pkcs8_der().map_err(Error::msg)?;
+        Ok(LocalKey { public, private_key: BASE64.encode(private_key_der.as_bytes()) })
+    }
+
     fn generate_key_and_keychain(role: KeyRole) -> Result<(EphemeralKeyPair, Keychain), Error> {
         const ALGORITHM: KeyAlgorithm = KeyAlgorithm::EcdsaP256Sha256Asn1SpkiDer;
 
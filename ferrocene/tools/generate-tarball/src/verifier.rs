@@ -0,0 +1,149 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use crate::signatures::{hash_file, load_keychain, manifests_dir};
+use crate::util::path_to_str;
+use anyhow::{Context, Result};
+use criticaltrust::manifests::{Package, PackageManifest};
+use criticaltrust::signatures::Keychain;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use walkdir::WalkDir;
+
+#[derive(Debug, clap::Args)]
+pub struct Verifier {
+    /// The directory containing the unpacked tarball
+    #[clap(value_name = "DIR")]
+    #[clap(long)]
+    input_dir: String,
+    /// The JSON keychain used to verify the manifests, containing the root key and the keys
+    /// signed by it
+    #[clap(value_name = "FILE")]
+    #[clap(long)]
+    keychain: String,
+}
+
+impl Verifier {
+    /// Verifies the unpacked tarball matches its signed manifests
+    pub fn run(self) -> Result<()> {
+        let input_dir = Path::new(&self.input_dir);
+        let keychain = load_keychain(self.keychain.as_ref())?;
+
+        let mut manifests = Vec::new();
+        let dir = manifests_dir(input_dir);
+        if dir.is_dir() {
+            for entry in std::fs::read_dir(&dir)
+                .with_context(|| format!("failed to read {}", dir.display()))?
+            {
+                let path = entry?.path();
+                if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                    manifests.push(path);
+                }
+            }
+        }
+        if manifests.is_empty() {
+            anyhow::bail!("no signed manifests found in {}", dir.display());
+        }
+        manifests.sort();
+
+        let mut problems = Vec::new();
+        let mut known_files = HashSet::new();
+        for manifest in &manifests {
+            let relative = path_to_str(manifest.strip_prefix(input_dir)?)?;
+            known_files.insert(relative.to_string());
+
+            let package = match load_manifest(manifest, &keychain) {
+                Ok(package) => package,
+                Err(err) => {
+                    problems.push(Problem::InvalidManifest {
+                        manifest: relative.to_string(),
+                        error: format!("{err:#}"),
+                    });
+                    continue;
+                }
+            };
+            for file in &package.files {
+                let path = input_dir.join(&file.path);
+                if !path.is_file() {
+                    problems.push(Problem::MissingFile(file.path.clone()));
+                } else if hash_file(&path)? != file.sha256 {
+                    problems.push(Problem::ModifiedFile(file.path.clone()));
+                }
+                known_files.insert(file.path.clone());
+            }
+        }
+
+        for entry in WalkDir::new(input_dir).sort_by_file_name() {
+            let entry = entry?;
+            if entry.file_type().is_dir() {
+                continue;
+            }
+            let relative = path_to_str(entry.path().strip_prefix(input_dir)?)?;
+            if !known_files.contains(relative) {
+                problems.push(Problem::UnexpectedFile(relative.to_string()));
+            }
+        }
+
+        if problems.is_empty() {
+            eprintln!(
+                "verified {} files in {} signed manifests",
+                known_files.len(),
+                manifests.len()
+            );
+            Ok(())
+        } else {
+            for problem in &problems {
+                eprintln!("error: {problem}");
+            }
+            Err(VerificationError { problems }.into())
+        }
+    }
+}
+
+/// Error returned when the unpacked tarball does not match its signed manifests.
+#[derive(Debug)]
+pub struct VerificationError {
+    pub problems: Vec<Problem>,
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the tarball does not match its signed manifests")
+    }
+}
+
+impl std::error::Error for VerificationError {}
+
+/// Mismatch between the unpacked tarball and its signed manifests.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Problem {
+    /// The manifest could not be loaded, or its signature is not valid.
+    InvalidManifest { manifest: String, error: String },
+    /// A file listed in a manifest is not present.
+    MissingFile(String),
+    /// The hash of a file doesn't match the one in its manifest.
+    ModifiedFile(String),
+    /// A file is not listed in any manifest.
+    UnexpectedFile(String),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::InvalidManifest { manifest, error } => write!(f, "{manifest}: {error}"),
+            Problem::MissingFile(path) => write!(f, "{path} is missing"),
+            Problem::ModifiedFile(path) => write!(f, "{path} was modified"),
+            Problem::UnexpectedFile(path) => {
+                write!(f, "{path} is not part of any signed manifest")
+            }
+        }
+    }
+}
+
+fn load_manifest(path: &Path, keychain: &Keychain) -> Result<Package> {
+    let contents = std::fs::read(path)?;
+    let manifest: PackageManifest =
+        serde_json::from_slice(&contents).context("failed to parse the manifest")?;
+    manifest.signed.into_verified(keychain).context("invalid signature")
+}
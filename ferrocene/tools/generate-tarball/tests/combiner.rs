@@ -0,0 +1,197 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use std::ffi::OsStr;
+use std::fs::{create_dir_all, write};
+use std::path::Path;
+use std::process::Command;
+
+use clap::Parser;
+use xz2::read::XzDecoder;
+
+#[test]
+fn works() -> Result<(), anyhow::Error> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_dir = temp_dir.path().to_str().unwrap();
+
+    let test_data = format!("{}/test_data", env!("CARGO_MANIFEST_DIR"));
+    let data_dir = format!("{test_data}/foo-package/x86_64-unknown-linux-gnu",);
+
+    generate(temp_dir, &format!("{data_dir}/image"), "foo")?;
+    create_dir_all(format!("{temp_dir}/baz-image"))?;
+    write(format!("{temp_dir}/baz-image/baz"), "baz")?;
+    generate(temp_dir, &format!("{temp_dir}/baz-image"), "baz")?;
+
+    combine(temp_dir, &format!("{data_dir}/overlay"), &["foo", "baz"])?;
+
+    let file = std::fs::File::open(format!("{temp_dir}/dist/combined.tar.xz"))?;
+    let mut res = tar::Archive::new(XzDecoder::new(file))
+        .entries()?
+        .map(|it| anyhow::Ok(it?.path()?.to_string_lossy().to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    res.sort();
+
+    let expected = vec![
+        "baz",
+        "baz/baz",
+        "baz/manifest.in",
+        "components",
+        "foo",
+        "foo-over",
+        "foo/bar",
+        "foo/bar/bar",
+        "foo/foo",
+        "foo/manifest.in",
+        "install.sh",
+        "rust-installer-version",
+    ];
+    assert_eq!(res, expected);
+
+    let work_dir = format!("{temp_dir}/work/combined");
+    assert_eq!("foo\nbaz\n", std::fs::read_to_string(format!("{work_dir}/components"))?);
+    assert_eq!(
+        "file:bar/bar\nfile:foo\n",
+        std::fs::read_to_string(format!("{work_dir}/foo/manifest.in"))?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn install_script_installs_the_components() -> Result<(), anyhow::Error> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_dir = temp_dir.path().to_str().unwrap();
+
+    let test_data = format!("{}/test_data", env!("CARGO_MANIFEST_DIR"));
+    let data_dir = format!("{test_data}/foo-package/x86_64-unknown-linux-gnu",);
+
+    generate(temp_dir, &format!("{data_dir}/image"), "foo")?;
+    create_dir_all(format!("{temp_dir}/baz-image"))?;
+    write(format!("{temp_dir}/baz-image/baz"), "baz")?;
+    generate(temp_dir, &format!("{temp_dir}/baz-image"), "baz")?;
+
+    combine(temp_dir, &format!("{data_dir}/overlay"), &["foo", "baz"])?;
+
+    let unpacked = format!("{temp_dir}/unpacked");
+    let file = std::fs::File::open(format!("{temp_dir}/dist/combined.tar.xz"))?;
+    tar::Archive::new(XzDecoder::new(file)).unpack(&unpacked)?;
+
+    let prefix = format!("{temp_dir}/prefix");
+    let output = Command::new("sh")
+        .arg(format!("{unpacked}/install.sh"))
+        .arg(format!("--prefix={prefix}"))
+        .arg("--disable-ldconfig")
+        .output()?;
+    assert!(output.status.success(), "install.sh failed: {output:?}");
+    // Dashes in the success message are replaced with spaces, like upstream does.
+    assert!(String::from_utf8(output.stdout)?.contains("ferrocene self test installed."));
+
+    // The files of the components are installed, the non-installed overlay is not.
+    assert_eq!(
+        vec![
+            "bar/bar",
+            "baz",
+            "foo",
+            "lib/rustlib/components",
+            "lib/rustlib/install.log",
+            "lib/rustlib/manifest-baz",
+            "lib/rustlib/manifest-foo",
+            "lib/rustlib/rust-installer-version",
+            "lib/rustlib/uninstall.sh",
+        ],
+        list_files(&prefix)?
+    );
+    assert_eq!("baz", std::fs::read_to_string(format!("{prefix}/baz"))?);
+    assert_eq!(
+        std::fs::read(format!("{data_dir}/image/bar/bar"))?,
+        std::fs::read(format!("{prefix}/bar/bar"))?
+    );
+    assert_eq!("foo\nbaz\n", std::fs::read_to_string(format!("{prefix}/lib/rustlib/components"))?);
+
+    // The generated uninstaller removes everything it installed.
+    let output = Command::new("sh").arg(format!("{prefix}/lib/rustlib/uninstall.sh")).output()?;
+    assert!(output.status.success(), "uninstall.sh failed: {output:?}");
+    assert!(list_files(&prefix)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn conflicting_files() -> Result<(), anyhow::Error> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_dir = temp_dir.path().to_str().unwrap();
+
+    for (component, contents) in [("first", "1"), ("second", "2")] {
+        let image = format!("{temp_dir}/{component}-image");
+        create_dir_all(&image)?;
+        write(format!("{image}/conflict"), contents)?;
+        generate(temp_dir, &image, component)?;
+    }
+
+    let err = combine(temp_dir, "", &["first", "second"]).unwrap_err();
+    assert_eq!(
+        "conflict is installed by both first and second with different contents",
+        err.root_cause().to_string()
+    );
+
+    Ok(())
+}
+
+fn generate(temp_dir: &str, image_dir: &str, package_name: &str) -> Result<(), anyhow::Error> {
+    let args = [
+        "this",
+        "generate",
+        "--image-dir",
+        image_dir,
+        "--package-name",
+        package_name,
+        "--output-dir",
+        &format!("{temp_dir}/dist"),
+        "--work-dir",
+        &format!("{temp_dir}/work"),
+        "--compression-profile",
+        "fast",
+        "--ferrocene-component",
+        package_name,
+    ];
+    generate_tarball::CommandLine::parse_from(args.into_iter().map(OsStr::new)).run()
+}
+
+fn combine(temp_dir: &str, overlay: &str, packages: &[&str]) -> Result<(), anyhow::Error> {
+    let input_tarballs =
+        packages.iter().map(|p| format!("{temp_dir}/dist/{p}.tar.xz")).collect::<Vec<_>>();
+    let args = [
+        "this",
+        "combine",
+        "--input-tarballs",
+        &input_tarballs.join(","),
+        "--rel-manifest-dir=rustlib",
+        "--legacy-manifest-dirs=rustlib,cargo",
+        "--product-name=Rust",
+        "--success-message=ferrocene-self-test installed.",
+        "--package-name=combined",
+        &format!("--non-installed-overlay={overlay}"),
+        "--output-dir",
+        &format!("{temp_dir}/dist"),
+        "--work-dir",
+        &format!("{temp_dir}/work"),
+        "--compression-profile",
+        "fast",
+        "--ferrocene-component",
+        "combined",
+    ];
+    generate_tarball::CommandLine::parse_from(args.into_iter().map(OsStr::new)).run()
+}
+
+fn list_files(dir: &str) -> Result<Vec<String>, anyhow::Error> {
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        if !entry.file_type().is_dir() {
+            let relative = entry.path().strip_prefix(Path::new(dir))?;
+            files.push(relative.to_string_lossy().into_owned());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
@@ -2,11 +2,14 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 use std::ffi::OsStr;
-use std::fs::{copy, create_dir, create_dir_all};
+use std::fs::{copy, create_dir, create_dir_all, File};
+use std::io::Read;
 use std::path::Path;
 
 use clap::Parser;
+use flate2::read::GzDecoder;
 use walkdir::WalkDir;
+use xz2::read::XzDecoder;
 
 #[test]
 fn works() -> Result<(), anyhow::Error> {
@@ -37,6 +40,54 @@ fn works() -> Result<(), anyhow::Error> {
     generate_tarball::CommandLine::parse_from(args.into_iter().map(OsStr::new)).run()
 }
 
+#[test]
+fn all_formats_contain_the_same_archive() -> Result<(), anyhow::Error> {
+    for profile in ["fast", "balanced", "best"] {
+        let temp_dir = tempfile::tempdir()?;
+        let temp_dir = temp_dir.path().to_str().unwrap();
+
+        let test_data = format!("{}/test_data", env!("CARGO_MANIFEST_DIR"));
+        let data_dir = format!("{test_data}/foo-package/x86_64-unknown-linux-gnu");
+
+        create_dir_all(format!("{temp_dir}/work/image"))?;
+        copy_recursive(
+            format!("{data_dir}/image").as_ref(),
+            format!("{temp_dir}/work/image").as_ref(),
+        )?;
+
+        let args = [
+            "this",
+            "tarball",
+            "--input",
+            &format!("{temp_dir}/work/image"),
+            "--output",
+            &format!("{temp_dir}/dist"),
+            "--work-dir",
+            &format!("{temp_dir}/work"),
+            "--compression-profile",
+            profile,
+            "--compression-formats",
+            "xz,gz,zst",
+        ];
+        generate_tarball::CommandLine::parse_from(args.into_iter().map(OsStr::new)).run()?;
+
+        let xz = decompress(XzDecoder::new(File::open(format!("{temp_dir}/dist.tar.xz"))?))?;
+        let gz = decompress(GzDecoder::new(File::open(format!("{temp_dir}/dist.tar.gz"))?))?;
+        let zst = decompress(zstd::Decoder::new(File::open(format!("{temp_dir}/dist.tar.zst"))?)?)?;
+
+        assert!(!xz.is_empty());
+        assert!(xz == gz, "the .tar.gz archive is different with the {profile} profile");
+        assert!(xz == zst, "the .tar.zst archive is different with the {profile} profile");
+    }
+    Ok(())
+}
+
+fn decompress(mut reader: impl Read) -> Result<Vec<u8>, anyhow::Error> {
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents)?;
+    Ok(contents)
+}
+
 fn copy_recursive(src: &Path, dst: &Path) -> anyhow::Result<()> {
     for entry in WalkDir::new(src).min_depth(1) {
         let entry = entry?;
@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use std::ffi::OsStr;
+use std::fs::{remove_file, write, File};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use clap::Parser;
+use criticaltrust::keys::newtypes::PublicKeyBytes;
+use criticaltrust::keys::{EphemeralKeyPair, KeyAlgorithm, KeyPair, KeyRole, PublicKey};
+use criticaltrust::signatures::SignedPayload;
+use generate_tarball::{Problem, VerificationError};
+use p256::pkcs8::{EncodePrivateKey, EncodePublicKey};
+use p256::SecretKey;
+use rand_core::OsRng;
+use xz2::read::XzDecoder;
+
+const ALGORITHM: KeyAlgorithm = KeyAlgorithm::EcdsaP256Sha256Asn1SpkiDer;
+
+#[test]
+fn works() -> Result<(), anyhow::Error> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_dir = temp_dir.path().to_str().unwrap();
+    generate_signed(temp_dir)?;
+
+    verify(temp_dir)
+}
+
+#[test]
+fn modified_file() -> Result<(), anyhow::Error> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_dir = temp_dir.path().to_str().unwrap();
+    generate_signed(temp_dir)?;
+
+    write(format!("{temp_dir}/unpacked/foo"), "modified")?;
+    assert_eq!(vec![Problem::ModifiedFile("foo".into())], verify_problems(temp_dir));
+
+    Ok(())
+}
+
+#[test]
+fn extra_file() -> Result<(), anyhow::Error> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_dir = temp_dir.path().to_str().unwrap();
+    generate_signed(temp_dir)?;
+
+    write(format!("{temp_dir}/unpacked/extra"), "extra")?;
+    assert_eq!(vec![Problem::UnexpectedFile("extra".into())], verify_problems(temp_dir));
+
+    Ok(())
+}
+
+#[test]
+fn missing_file() -> Result<(), anyhow::Error> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_dir = temp_dir.path().to_str().unwrap();
+    generate_signed(temp_dir)?;
+
+    remove_file(format!("{temp_dir}/unpacked/bar/bar"))?;
+    assert_eq!(vec![Problem::MissingFile("bar/bar".into())], verify_problems(temp_dir));
+
+    Ok(())
+}
+
+#[test]
+fn untrusted_key() -> Result<(), anyhow::Error> {
+    let temp_dir = tempfile::tempdir()?;
+    let temp_dir = temp_dir.path().to_str().unwrap();
+    generate_signed(temp_dir)?;
+
+    // Replace the keychain with one that doesn't contain the key used to sign the manifest.
+    let root = EphemeralKeyPair::generate(ALGORITHM, KeyRole::Root, None)?;
+    write(
+        format!("{temp_dir}/keychain.json"),
+        serde_json::to_vec(&serde_json::json!({ "root": root.public(), "keys": [] }))?,
+    )?;
+
+    // The files of a manifest that fails verification are not part of any trusted manifest.
+    let problems = verify_problems(temp_dir);
+    assert!(
+        matches!(
+            problems.as_slice(),
+            [
+                Problem::InvalidManifest { manifest, error },
+                Problem::UnexpectedFile(bar),
+                Problem::UnexpectedFile(foo),
+            ] if manifest == "share/criticaltrust/ferrocene/foo.json"
+                && error == "invalid signature: failed to verify signed data"
+                && bar == "bar/bar"
+                && foo == "foo"
+        ),
+        "unexpected problems: {problems:?}",
+    );
+
+    Ok(())
+}
+
+/// Generates a tarball signed with a local key, unpacks it in `unpacked/` and writes the keychain
+/// able to verify it in `keychain.json`.
+fn generate_signed(temp_dir: &str) -> Result<(), anyhow::Error> {
+    // criticaltrust doesn't expose the private key of ephemeral key pairs, so the local key is
+    // generated directly with p256.
+    let private_key = SecretKey::random(&mut OsRng);
+    let public = PublicKey {
+        role: KeyRole::Packages,
+        algorithm: ALGORITHM,
+        expiry: None,
+        public: PublicKeyBytes::owned(
+            private_key.public_key().to_public_key_der().map_err(anyhow::Error::msg)?.to_vec(),
+        ),
+    };
+
+    let root = EphemeralKeyPair::generate(ALGORITHM, KeyRole::Root, None)?;
+    let mut signed = SignedPayload::new(&public)?;
+    signed.add_signature(&root)?;
+
+    write(
+        format!("{temp_dir}/key.json"),
+        serde_json::to_vec(&serde_json::json!({
+            "public": public,
+            "private-key": BASE64.encode(
+                private_key.to_pkcs8_der().map_err(anyhow::Error::msg)?.as_bytes()
+            ),
+        }))?,
+    )?;
+    write(
+        format!("{temp_dir}/keychain.json"),
+        serde_json::to_vec(&serde_json::json!({ "root": root.public(), "keys": [signed] }))?,
+    )?;
+
+    let test_data = format!("{}/test_data", env!("CARGO_MANIFEST_DIR"));
+    let data_dir = format!("{test_data}/foo-package/x86_64-unknown-linux-gnu");
+
+    let args = [
+        "this",
+        "generate",
+        "--image-dir",
+        &format!("{data_dir}/image"),
+        "--package-name=signed",
+        "--output-dir",
+        &format!("{temp_dir}/dist"),
+        "--work-dir",
+        &format!("{temp_dir}/work"),
+        "--compression-profile",
+        "fast",
+        "--ferrocene-component",
+        "foo",
+        "--ferrocene-commit-sha",
+        "0000000",
+        "--ferrocene-signing-local-key",
+        &format!("{temp_dir}/key.json"),
+    ];
+    generate_tarball::CommandLine::parse_from(args.into_iter().map(OsStr::new)).run()?;
+
+    let file = File::open(format!("{temp_dir}/dist/signed.tar.xz"))?;
+    tar::Archive::new(XzDecoder::new(file)).unpack(format!("{temp_dir}/unpacked"))?;
+
+    Ok(())
+}
+
+fn verify(temp_dir: &str) -> Result<(), anyhow::Error> {
+    let args = [
+        "this",
+        "verify",
+        "--input-dir",
+        &format!("{temp_dir}/unpacked"),
+        "--keychain",
+        &format!("{temp_dir}/keychain.json"),
+    ];
+    generate_tarball::CommandLine::parse_from(args.into_iter().map(OsStr::new)).run()
+}
+
+fn verify_problems(temp_dir: &str) -> Vec<Problem> {
+    let err = verify(temp_dir).unwrap_err();
+    match err.downcast::<VerificationError>() {
+        Ok(err) => err.problems,
+        Err(err) => panic!("unexpected error: {err:?}"),
+    }
+}
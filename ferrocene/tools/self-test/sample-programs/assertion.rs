@@ -8,7 +8,11 @@
 use subtraction_sys::sub;
 
 pub fn main() {
-    assert_eq!(2, add(sub(2, 1), 1));
+    let result = add(sub(2, 1), 1);
+    assert_eq!(2, result);
+
+    #[cfg(not(selftest_no_std))]
+    println!("2 - 1 + 1 = {result}");
 }
 
 #[cfg(selftest_no_std)]
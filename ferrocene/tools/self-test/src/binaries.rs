@@ -2,16 +2,36 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 use crate::error::Error;
-use crate::report::Reporter;
+use crate::report::{Check, Failures, Reporter};
 use crate::utils::run_command;
 use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::process::Command;
 
-pub(crate) fn check(reporter: &dyn Reporter, sysroot: &Path) -> Result<(), Error> {
-    check_binary(reporter, sysroot, "rustc", CommitHashOf::Rust)?;
-    check_binary(reporter, sysroot, "rustdoc", CommitHashOf::Rust)?;
-    check_optional_binary(reporter, sysroot, "cargo", CommitHashOf::Cargo)?;
+pub(crate) static BINARIES: &[Binary] = &[
+    Binary { name: "rustc", hash: CommitHashOf::Rust, optional: false },
+    Binary { name: "rustdoc", hash: CommitHashOf::Rust, optional: false },
+    Binary { name: "cargo", hash: CommitHashOf::Cargo, optional: true },
+];
+
+pub(crate) struct Binary {
+    pub(crate) name: &'static str,
+    hash: CommitHashOf,
+    optional: bool,
+}
+
+pub(crate) fn check(
+    reporter: &dyn Reporter,
+    failures: &mut Failures<'_>,
+    sysroot: &Path,
+) -> Result<(), Error> {
+    for binary in BINARIES {
+        failures.collect(if binary.optional {
+            check_optional_binary(reporter, sysroot, binary.name, binary.hash)
+        } else {
+            check_binary(reporter, sysroot, binary.name, binary.hash)
+        })?;
+    }
 
     Ok(())
 }
@@ -60,7 +80,13 @@ fn check_binary(
         }
     }
 
-    reporter.success(&format!("binary {name} is valid"));
+    reporter.passed(&Check::Binary {
+        name,
+        path: &bin,
+        release: version.release,
+        host: version.host,
+        commit_hash: version.commit_hash,
+    });
     Ok(())
 }
 
@@ -87,7 +113,6 @@ fn parse_version_output(output: &str) -> Option<VersionOutput<'_>> {
 }
 
 #[derive(Debug)]
-#[allow(dead_code)]
 struct VersionOutput<'a> {
     release: &'a str,
     commit_hash: &'a str,
@@ -109,6 +134,7 @@ fn check_optional_binary(
     }
 }
 
+#[derive(Clone, Copy)]
 enum CommitHashOf {
     Rust,
     Cargo,
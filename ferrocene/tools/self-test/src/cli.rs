@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use crate::error::Error;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct CliOptions {
+    pub(crate) format: ReportFormat,
+    pub(crate) config: Option<PathBuf>,
+    pub(crate) only: Option<BTreeSet<CheckGroup>>,
+    pub(crate) targets: Vec<String>,
+    pub(crate) list: bool,
+    pub(crate) keep_going: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReportFormat {
+    Human,
+    Json,
+}
+
+/// Group of checks that can be selected with `--only`. Groups after `Binaries` depend on the ones
+/// before them: for example, compiling sample programs requires detecting targets and linkers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum CheckGroup {
+    Files,
+    Binaries,
+    Targets,
+    Linkers,
+    Compile,
+}
+
+impl CheckGroup {
+    fn parse(input: &str) -> Option<Self> {
+        match input {
+            "files" => Some(CheckGroup::Files),
+            "binaries" => Some(CheckGroup::Binaries),
+            "targets" => Some(CheckGroup::Targets),
+            "linkers" => Some(CheckGroup::Linkers),
+            "compile" => Some(CheckGroup::Compile),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CheckGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CheckGroup::Files => "files",
+            CheckGroup::Binaries => "binaries",
+            CheckGroup::Targets => "targets",
+            CheckGroup::Linkers => "linkers",
+            CheckGroup::Compile => "compile",
+        })
+    }
+}
+
+impl CliOptions {
+    /// Whether the checks in the group need to be executed, either because they were selected
+    /// or because a selected group depends on them.
+    pub(crate) fn needs(&self, group: CheckGroup) -> bool {
+        match &self.only {
+            Some(only) if group <= CheckGroup::Binaries => only.contains(&group),
+            Some(only) => only.iter().any(|selected| *selected >= group),
+            None => true,
+        }
+    }
+
+    pub(crate) fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Self, Error> {
+        let mut options = CliOptions::default();
+
+        let mut args = args.into_iter().map(|arg| arg.to_string_lossy().into_owned());
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| Error::MissingCliFlagValue { flag: flag.clone() })
+            };
+
+            match flag.as_str() {
+                "--format" => {
+                    options.format = match value()?.as_str() {
+                        "human" => ReportFormat::Human,
+                        "json" => ReportFormat::Json,
+                        other => return Err(Error::InvalidReportFormat { format: other.into() }),
+                    }
+                }
+                "--config" => options.config = Some(value()?.into()),
+                "--only" => {
+                    for group in value()?.split(',') {
+                        let group = CheckGroup::parse(group)
+                            .ok_or_else(|| Error::InvalidCheckGroup { group: group.into() })?;
+                        options.only.get_or_insert_with(BTreeSet::new).insert(group);
+                    }
+                }
+                "--target" => options.targets.push(value()?),
+                "--list" if inline_value.is_none() => options.list = true,
+                "--keep-going" if inline_value.is_none() => options.keep_going = true,
+                _ => return Err(Error::UnknownCliFlag { flag }),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        CliOptions {
+            format: ReportFormat::Human,
+            config: None,
+            only: None,
+            targets: Vec::new(),
+            list: false,
+            keep_going: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<CliOptions, Error> {
+        CliOptions::parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        assert_eq!(CliOptions::default(), parse(&[]).unwrap());
+    }
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!(
+            Some(PathBuf::from("/etc/self-test.toml")),
+            parse(&["--config", "/etc/self-test.toml"]).unwrap().config
+        );
+        assert_eq!(
+            Some(PathBuf::from("self-test.toml")),
+            parse(&["--config=self-test.toml"]).unwrap().config
+        );
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(ReportFormat::Json, parse(&["--format", "json"]).unwrap().format);
+        assert_eq!(ReportFormat::Json, parse(&["--format=json"]).unwrap().format);
+        assert_eq!(
+            ReportFormat::Human,
+            parse(&["--format=json", "--format=human"]).unwrap().format
+        );
+
+        match parse(&["--format", "xml"]) {
+            Err(Error::InvalidReportFormat { format }) => assert_eq!("xml", format),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        match parse(&["--format"]) {
+            Err(Error::MissingCliFlagValue { flag }) => assert_eq!("--format", flag),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_selection() {
+        let options =
+            parse(&["--only", "targets", "--target=aarch64-unknown-none", "--target", "x"])
+                .unwrap();
+        assert_eq!(Some([CheckGroup::Targets].into()), options.only);
+        assert_eq!(vec!["aarch64-unknown-none".to_string(), "x".to_string()], options.targets);
+
+        let options = parse(&["--only=binaries,compile", "--only", "linkers"]).unwrap();
+        assert_eq!(
+            Some([CheckGroup::Binaries, CheckGroup::Linkers, CheckGroup::Compile].into()),
+            options.only
+        );
+
+        match parse(&["--only", "targets,frobnicate"]) {
+            Err(Error::InvalidCheckGroup { group }) => assert_eq!("frobnicate", group),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_boolean_flags() {
+        let options = parse(&["--list", "--keep-going"]).unwrap();
+        assert!(options.list);
+        assert!(options.keep_going);
+
+        match parse(&["--list=yes"]) {
+            Err(Error::UnknownCliFlag { flag }) => assert_eq!("--list", flag),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_needs() {
+        let all = CliOptions::default();
+        let only = |groups: &[CheckGroup]| CliOptions {
+            only: Some(groups.iter().copied().collect()),
+            ..CliOptions::default()
+        };
+
+        for group in [
+            CheckGroup::Files,
+            CheckGroup::Binaries,
+            CheckGroup::Targets,
+            CheckGroup::Linkers,
+            CheckGroup::Compile,
+        ] {
+            assert!(all.needs(group));
+        }
+
+        let files = only(&[CheckGroup::Files]);
+        assert!(files.needs(CheckGroup::Files));
+        assert!(!files.needs(CheckGroup::Binaries));
+        assert!(!files.needs(CheckGroup::Targets));
+
+        let binaries = only(&[CheckGroup::Binaries]);
+        assert!(!binaries.needs(CheckGroup::Files));
+        assert!(binaries.needs(CheckGroup::Binaries));
+        assert!(!binaries.needs(CheckGroup::Targets));
+        assert!(!binaries.needs(CheckGroup::Linkers));
+        assert!(!binaries.needs(CheckGroup::Compile));
+
+        let linkers = only(&[CheckGroup::Linkers]);
+        assert!(!linkers.needs(CheckGroup::Binaries));
+        assert!(linkers.needs(CheckGroup::Targets));
+        assert!(linkers.needs(CheckGroup::Linkers));
+        assert!(!linkers.needs(CheckGroup::Compile));
+
+        let compile = only(&[CheckGroup::Compile]);
+        assert!(!compile.needs(CheckGroup::Files));
+        assert!(!compile.needs(CheckGroup::Binaries));
+        assert!(compile.needs(CheckGroup::Targets));
+        assert!(compile.needs(CheckGroup::Linkers));
+        assert!(compile.needs(CheckGroup::Compile));
+    }
+
+    #[test]
+    fn test_parse_unknown_flag() {
+        match parse(&["--frobnicate"]) {
+            Err(Error::UnknownCliFlag { flag }) => assert_eq!("--frobnicate", flag),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
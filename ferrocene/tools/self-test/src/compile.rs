@@ -1,33 +1,37 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
-use crate::error::Error;
-use crate::report::Reporter;
+use crate::error::{CommandError, CommandErrorKind, Error};
+use crate::report::{Check, Failures, Reporter};
 use crate::targets::Target;
 use crate::utils::run_command;
+use crate::Environment;
 use std::collections::HashSet;
 use std::ffi::OsString;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 
-static SAMPLE_PROGRAMS: &[SampleProgram] = &[
+pub(crate) static SAMPLE_PROGRAMS: &[SampleProgram] = &[
     SampleProgram {
         name: "addition.rs",
         contents: include_bytes!("../sample-programs/addition.rs"),
         rustflags: &["--crate-type", "lib", "--edition", "2021"],
         expected_artifacts: &["libaddition.rlib"],
+        execution: None,
     },
     SampleProgram {
         name: "subtraction.rs",
         contents: include_bytes!("../sample-programs/subtraction.rs"),
         rustflags: &["--crate-type", "staticlib", "--edition", "2021"],
         expected_artifacts: &["libsubtraction.a"],
+        execution: None,
     },
     SampleProgram {
         name: "subtraction-sys.rs",
         contents: include_bytes!("../sample-programs/subtraction-sys.rs"),
         rustflags: &["--crate-type", "lib", "--edition", "2021", "-l", "subtraction"],
         expected_artifacts: &["libsubtraction_sys.rlib"],
+        execution: None,
     },
     SampleProgram {
         name: "assertion.rs",
@@ -43,22 +47,31 @@
             "subtraction_sys",
         ],
         expected_artifacts: &["assertion"],
+        execution: Some(ExpectedExecution {
+            binary: "assertion",
+            exit_code: 0,
+            stdout: include_str!("../sample-programs/assertion.stdout"),
+        }),
     },
 ];
 
 pub(crate) fn check(
     reporter: &dyn Reporter,
+    failures: &mut Failures<'_>,
+    environment: &Environment,
     sysroot: &Path,
     targets: &[Target],
 ) -> Result<(), Error> {
+    // Sample programs depend on each other, so a failure stops the checks of its target only.
     for target in targets {
-        check_target(reporter, sysroot, target, SAMPLE_PROGRAMS)?;
+        failures.collect(check_target(reporter, environment, sysroot, target, SAMPLE_PROGRAMS))?;
     }
     Ok(())
 }
 
 fn check_target(
     reporter: &dyn Reporter,
+    environment: &Environment,
     sysroot: &Path,
     target: &Target,
     programs: &[SampleProgram],
@@ -74,6 +87,7 @@ fn check_target(
         temp_dir: temp.path().into(),
         source_dir: temp.path().join("src"),
         output_dir: temp.path().join("out"),
+        runner: runner_for(environment, target),
     };
 
     std::fs::create_dir_all(&ctx.source_dir)
@@ -87,11 +101,94 @@ fn check_target(
         compile(&ctx, program)?;
         expected_artifacts.check(program.name)?;
 
-        reporter.success(&format!(
-            "compiled sample program `{}` for target {}",
-            program.name, target.triple
-        ));
+        reporter.passed(&Check::SampleProgram { name: program.name, target: target.triple });
+
+        if let Some(expected) = &program.execution {
+            execute(reporter, &ctx, program, expected)?;
+        }
+    }
+    Ok(())
+}
+
+fn runner_for(environment: &Environment, target: &Target) -> Runner {
+    if !target.std {
+        // no_std sample programs don't have an environment to report their results to.
+        Runner::Unsupported { reason: "no_std target" }
+    } else if let Some(command) = environment.runner_for(target.triple) {
+        Runner::Command(command)
+    } else if target.triple == env!("SELFTEST_TARGET") {
+        Runner::Native
+    } else {
+        Runner::Unsupported { reason: "no runner configured" }
+    }
+}
+
+fn execute(
+    reporter: &dyn Reporter,
+    ctx: &Context<'_>,
+    program: &SampleProgram,
+    expected: &ExpectedExecution,
+) -> Result<(), Error> {
+    let binary = ctx.output_dir.join(expected.binary);
+    let mut cmd = match &ctx.runner {
+        Runner::Native => Command::new(&binary),
+        Runner::Command(runner) => {
+            let mut cmd = Command::new(&runner[0]);
+            cmd.args(&runner[1..]).arg(&binary);
+            cmd
+        }
+        Runner::Unsupported { reason } => {
+            reporter.skipped(&format!(
+                "execution of sample program `{}` for target {} ({reason})",
+                program.name, ctx.target.triple
+            ));
+            return Ok(());
+        }
+    };
+    cmd.current_dir(&ctx.output_dir);
+    cmd.stdin(Stdio::null());
+
+    let output = cmd.output().map_err(|error| Error::SampleProgramExecutionFailed {
+        name: program.name.into(),
+        target: ctx.target.triple.into(),
+        error: CommandError {
+            path: cmd.get_program().into(),
+            args: cmd.get_args().map(|arg| arg.into()).collect(),
+            kind: CommandErrorKind::StartupFailed { error },
+        },
+    })?;
+
+    if output.status.code() != Some(expected.exit_code) {
+        return Err(Error::SampleProgramWrongExitCode {
+            name: program.name.into(),
+            target: ctx.target.triple.into(),
+            expected: expected.exit_code,
+            error: CommandError {
+                path: cmd.get_program().into(),
+                args: cmd.get_args().map(|arg| arg.into()).collect(),
+                kind: CommandErrorKind::Failure { output },
+            },
+        });
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout != expected.stdout {
+        return Err(Error::SampleProgramWrongOutput {
+            name: program.name.into(),
+            target: ctx.target.triple.into(),
+            expected: expected.stdout.into(),
+            found: stdout.into(),
+        });
     }
+
+    reporter.passed(&Check::SampleProgramExecution {
+        name: program.name,
+        target: ctx.target.triple,
+        runner: match &ctx.runner {
+            Runner::Command(runner) => Some(&runner[0]),
+            _ => None,
+        },
+    });
     Ok(())
 }
 
@@ -181,13 +278,30 @@ struct Context<'a> {
     temp_dir: PathBuf,
     source_dir: PathBuf,
     output_dir: PathBuf,
+    runner: Runner,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Runner {
+    Native,
+    Command(Vec<String>),
+    Unsupported { reason: &'static str },
 }
 
-struct SampleProgram {
-    name: &'static str,
+pub(crate) struct SampleProgram {
+    pub(crate) name: &'static str,
     contents: &'static [u8],
     rustflags: &'static [&'static str],
     expected_artifacts: &'static [&'static str],
+    execution: Option<ExpectedExecution>,
+}
+
+/// How a sample program is expected to behave when executed. The expected standard output is
+/// stored in the `.stdout` file next to the sample program's source code.
+struct ExpectedExecution {
+    binary: &'static str,
+    exit_code: i32,
+    stdout: &'static str,
 }
 
 #[cfg(test)]
@@ -206,7 +320,15 @@ fn test_all_sample_program_source_files_are_registered() {
             .expect("sample-programs directory not found")
             .map(|entry| entry.unwrap().path().file_name().unwrap().to_str().unwrap().to_string())
             .collect::<Vec<_>>();
-        let mut registered = SAMPLE_PROGRAMS.iter().map(|p| p.name.to_string()).collect::<Vec<_>>();
+        let mut registered = Vec::new();
+        for program in SAMPLE_PROGRAMS {
+            registered.push(program.name.to_string());
+            if program.execution.is_some() {
+                let stem = program.name.strip_suffix(".rs").unwrap();
+                registered.push(format!("{stem}.stdout"));
+                registered.push(format!("{stem}.stdout.license"));
+            }
+        }
 
         actual.sort();
         registered.sort();
@@ -215,7 +337,7 @@ fn test_all_sample_program_source_files_are_registered() {
             registered, actual,
             "\n\nThe list of sample programs in src/compile.rs is different \
              than the list of files in the filesystem.\n\
-             Did you register all the sample programs?\n\n"
+             Did you register all the sample programs and their expected output?\n\n"
         );
     }
 
@@ -265,12 +387,14 @@ fn main() {
                 contents: b"pub fn foo() {}",
                 rustflags: &["--crate-type", "lib"],
                 expected_artifacts: &["libfoo.rlib"],
+                execution: None,
             },
             SampleProgram {
                 name: "bar.rs",
                 contents: b"fn main() {}",
                 rustflags: &["--crate-type", "bin"],
                 expected_artifacts: &["bar"],
+                execution: None,
             },
         ];
 
@@ -286,7 +410,8 @@ fn main() {
         let utils = TestUtils::new();
         utils.bin("rustc").program_source(RUSTC_SOURCE).create();
 
-        check_target(utils.reporter(), utils.sysroot(), &target, TEST_PROGRAMS).unwrap();
+        check_target(utils.reporter(), utils.env(), utils.sysroot(), &target, TEST_PROGRAMS)
+            .unwrap();
     }
 
     #[test]
@@ -354,12 +479,14 @@ fn test_compile_failed_to_write_program() {
             temp_dir: tempdir.path().into(),
             source_dir: tempdir.path().join("missing"),
             output_dir,
+            runner: Runner::Native,
         };
         let program = SampleProgram {
             name: "example.rs",
             contents: b"fn main() { println!(\"Hello world!\"); }\n",
             rustflags: &[],
             expected_artifacts: &[],
+            execution: None,
         };
 
         match compile(&context, &program) {
@@ -398,12 +525,14 @@ fn test_compile_failed_invocation() {
             temp_dir: tempdir.path().into(),
             source_dir,
             output_dir,
+            runner: Runner::Native,
         };
         let program = SampleProgram {
             name: "example.rs",
             contents: b"fn main() { println!(\"Hello world!\"); }\n",
             rustflags: &[],
             expected_artifacts: &[],
+            execution: None,
         };
 
         match compile(&context, &program) {
@@ -475,6 +604,7 @@ fn compile_success_inner(std: bool, extra_arg: &[&str]) {
             temp_dir: tempdir.path().into(),
             source_dir,
             output_dir,
+            runner: Runner::Native,
         };
 
         let program = SampleProgram {
@@ -482,8 +612,212 @@ fn compile_success_inner(std: bool, extra_arg: &[&str]) {
             contents: b"fn main() { println!(\"Hello world!\"); }\n",
             rustflags: &["--extern", "foo"],
             expected_artifacts: &["example"],
+            execution: None,
         };
 
         compile(&context, &program).unwrap();
     }
+
+    #[test]
+    fn test_execute_native() {
+        let utils = TestUtils::new();
+        let bin = utils.bin("example").stdout("Hello world!\n").exit(0).create();
+
+        let target = example_target();
+        let context = execution_context(&target, &bin, Runner::Native);
+        execute(utils.reporter(), &context, &EXAMPLE_PROGRAM, &example_execution("Hello world!\n"))
+            .unwrap();
+        utils.assert_report_success(
+            "executed sample program `example.rs` for target x86_64-unknown-linux-gnu",
+        );
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_execute_with_runner() {
+        let utils = TestUtils::new();
+        let bin = utils.bin("example").create();
+        let bin_str = bin.to_str().unwrap();
+        let runner = utils
+            .bin("qemu-x86_64")
+            .external()
+            .expected_args(&["-L", "/usr/x86_64-linux-gnu", bin_str])
+            .stdout("Hello world!\n")
+            .create();
+        let runner = runner.to_str().unwrap();
+
+        let target = example_target();
+        let context = execution_context(
+            &target,
+            &bin,
+            Runner::Command(vec![runner.into(), "-L".into(), "/usr/x86_64-linux-gnu".into()]),
+        );
+        execute(utils.reporter(), &context, &EXAMPLE_PROGRAM, &example_execution("Hello world!\n"))
+            .unwrap();
+        utils.assert_report_success(&format!(
+            "executed sample program `example.rs` for target x86_64-unknown-linux-gnu with {runner}"
+        ));
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_execute_unsupported() {
+        let utils = TestUtils::new();
+        let bin = utils.bin("example").exit(1).create();
+
+        let target = example_target();
+        let context = execution_context(
+            &target,
+            &bin,
+            Runner::Unsupported { reason: "no runner configured" },
+        );
+        execute(utils.reporter(), &context, &EXAMPLE_PROGRAM, &example_execution("")).unwrap();
+        utils.assert_report_skipped(
+            "execution of sample program `example.rs` for target x86_64-unknown-linux-gnu \
+             (no runner configured)",
+        );
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_execute_wrong_exit_code() {
+        let utils = TestUtils::new();
+        let bin = utils.bin("example").stdout("Hello world!\n").exit(101).create();
+
+        let target = example_target();
+        let context = execution_context(&target, &bin, Runner::Native);
+        match execute(
+            utils.reporter(),
+            &context,
+            &EXAMPLE_PROGRAM,
+            &example_execution("Hello world!\n"),
+        ) {
+            Err(Error::SampleProgramWrongExitCode {
+                name,
+                target,
+                expected,
+                error: CommandError { path, kind: CommandErrorKind::Failure { output }, .. },
+            }) => {
+                assert_eq!("example.rs", name);
+                assert_eq!("x86_64-unknown-linux-gnu", target);
+                assert_eq!(0, expected);
+                assert_eq!(bin, path);
+                assert_eq!(Some(101), output.status.code());
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_execute_wrong_output() {
+        let utils = TestUtils::new();
+        let bin = utils.bin("example").stdout("Goodbye world!\n").exit(0).create();
+
+        let target = example_target();
+        let context = execution_context(&target, &bin, Runner::Native);
+        match execute(
+            utils.reporter(),
+            &context,
+            &EXAMPLE_PROGRAM,
+            &example_execution("Hello world!\n"),
+        ) {
+            Err(Error::SampleProgramWrongOutput { name, target, expected, found }) => {
+                assert_eq!("example.rs", name);
+                assert_eq!("x86_64-unknown-linux-gnu", target);
+                assert_eq!("Hello world!\n", expected);
+                assert_eq!("Goodbye world!\n", found);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_execute_missing_binary() {
+        let utils = TestUtils::new();
+        let bin = utils.sysroot().join("example");
+
+        let target = example_target();
+        let context = execution_context(&target, &bin, Runner::Native);
+        match execute(utils.reporter(), &context, &EXAMPLE_PROGRAM, &example_execution("")) {
+            Err(Error::SampleProgramExecutionFailed {
+                name,
+                error: CommandError { path, kind: CommandErrorKind::StartupFailed { error }, .. },
+                ..
+            }) => {
+                assert_eq!("example.rs", name);
+                assert_eq!(bin, path);
+                assert_eq!(std::io::ErrorKind::NotFound, error.kind());
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_runner_for() {
+        let env = &Environment {
+            path: None,
+            runners: [(
+                "CARGO_TARGET_AARCH64_UNKNOWN_LINUX_GNU_RUNNER".to_string(),
+                "qemu-aarch64 -L /usr".to_string(),
+            )]
+            .into(),
+        };
+
+        let target = |triple: &'static str, std: bool| Target {
+            spec: Box::leak(Box::new(TargetSpec { triple, std, linker: Linker::BundledLld })),
+            rustflags: Vec::new(),
+        };
+
+        assert_eq!(Runner::Native, runner_for(env, &target(env!("SELFTEST_TARGET"), true)));
+        assert_eq!(
+            Runner::Command(vec!["qemu-aarch64".into(), "-L".into(), "/usr".into()]),
+            runner_for(env, &target("aarch64-unknown-linux-gnu", true))
+        );
+        assert_eq!(
+            Runner::Unsupported { reason: "no runner configured" },
+            runner_for(env, &target("riscv64gc-unknown-linux-gnu", true))
+        );
+        assert_eq!(
+            Runner::Unsupported { reason: "no_std target" },
+            runner_for(env, &target("aarch64-unknown-none", false))
+        );
+    }
+
+    const EXAMPLE_PROGRAM: SampleProgram = SampleProgram {
+        name: "example.rs",
+        contents: b"fn main() { println!(\"Hello world!\"); }\n",
+        rustflags: &[],
+        expected_artifacts: &["example"],
+        execution: None,
+    };
+
+    fn example_execution(stdout: &'static str) -> ExpectedExecution {
+        ExpectedExecution { binary: "example", exit_code: 0, stdout }
+    }
+
+    fn example_target() -> Target {
+        Target {
+            spec: &TargetSpec {
+                triple: "x86_64-unknown-linux-gnu",
+                std: true,
+                linker: Linker::BundledLld,
+            },
+            rustflags: Vec::new(),
+        }
+    }
+
+    fn execution_context<'a>(target: &'a Target, bin: &Path, runner: Runner) -> Context<'a> {
+        let output_dir = bin.parent().unwrap().to_path_buf();
+        Context {
+            target,
+            rustc: PathBuf::new(),
+            temp_dir: output_dir.clone(),
+            source_dir: output_dir.clone(),
+            output_dir,
+            runner,
+        }
+    }
 }
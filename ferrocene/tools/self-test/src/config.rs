@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use crate::error::Error;
+use crate::linkers::{ExternalLinker, GccMode, Linker, LinkerDriver};
+use crate::targets::TargetSpec;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Optional configuration file, allowing users to declare targets and linkers that are not
+/// known to the self-test tool out of the box.
+///
+/// ```toml
+/// [[targets]]
+/// triple = "aarch64-unknown-linux-gnu"
+/// std = true
+/// linker = { driver = "gcc", name = "aarch64-vendor-linux-gnu-gcc", version = ">=9, <12" }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    #[serde(default)]
+    targets: Vec<ConfigTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigTarget {
+    triple: String,
+    std: bool,
+    linker: ConfigLinker,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "driver", rename_all = "kebab-case")]
+enum ConfigLinker {
+    BundledLld,
+    Gcc(ExternalLinker),
+    Clang(ExternalLinker),
+    Lld(ExternalLinker),
+}
+
+impl Config {
+    pub(crate) fn load(path: &Path) -> Result<Self, Error> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| Error::ConfigReadFailed { path: path.into(), error })?;
+        toml::from_str(&contents)
+            .map_err(|error| Error::ConfigParseFailed { path: path.into(), error })
+    }
+
+    /// Convert the configured targets into target specs.
+    ///
+    /// Target specs are leaked, as the rest of the self-test tool relies on them being `'static`
+    /// (like the ones built into the tool), and they have to live until the process exits anyway.
+    pub(crate) fn into_target_specs(self) -> &'static [TargetSpec] {
+        let specs = self
+            .targets
+            .into_iter()
+            .map(|target| {
+                let external = |driver, linker: ExternalLinker| Linker::External {
+                    driver,
+                    linker: Box::leak(Box::new(linker)),
+                };
+                TargetSpec {
+                    triple: Box::leak(target.triple.into_boxed_str()),
+                    std: target.std,
+                    linker: match target.linker {
+                        ConfigLinker::BundledLld => Linker::BundledLld,
+                        ConfigLinker::Gcc(linker) => external(LinkerDriver::Gcc, linker),
+                        ConfigLinker::Clang(linker) => external(LinkerDriver::Clang, linker),
+                        ConfigLinker::Lld(linker) => external(LinkerDriver::Lld, linker),
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+        Box::leak(specs.into_boxed_slice())
+    }
+}
+
+impl ExternalLinker {
+    pub(crate) fn mode(&self) -> GccMode {
+        if self.bare_metal {
+            GccMode::BareMetal
+        } else {
+            GccMode::Normal
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linkers::VersionReq;
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("self-test.toml");
+        std::fs::write(
+            &path,
+            r#"
+                [[targets]]
+                triple = "aarch64-unknown-linux-gnu"
+                std = true
+                linker = { driver = "gcc", name = "aarch64-vendor-linux-gnu-gcc", version = "10.3" }
+
+                [[targets]]
+                triple = "x86_64-unknown-linux-gnu"
+                std = true
+                linker = { driver = "clang", name = "clang-14", version = ">=14, <16", link-args = ["-fuse-ld=lld"] }
+
+                [[targets]]
+                triple = "thumbv7em-none-eabihf"
+                std = false
+                linker = { driver = "bundled-lld" }
+
+                [[targets]]
+                triple = "x86_64-unknown-none"
+                std = false
+                linker = { driver = "lld", name = "ld.lld", version = "14.x", bare-metal = true }
+            "#,
+        )
+        .unwrap();
+
+        let specs = Config::load(&path).unwrap().into_target_specs();
+        assert_eq!(4, specs.len());
+
+        assert_eq!("aarch64-unknown-linux-gnu", specs[0].triple);
+        assert!(specs[0].std);
+        match specs[0].linker {
+            Linker::External { driver: LinkerDriver::Gcc, linker } => {
+                assert_eq!("aarch64-vendor-linux-gnu-gcc", linker.name);
+                assert_eq!(VersionReq::parse("=10.3").unwrap(), linker.version);
+                assert_eq!(GccMode::Normal, linker.mode());
+                assert!(linker.link_args.is_empty());
+            }
+            other => panic!("unexpected linker: {other:?}"),
+        }
+
+        assert_eq!("x86_64-unknown-linux-gnu", specs[1].triple);
+        match specs[1].linker {
+            Linker::External { driver: LinkerDriver::Clang, linker } => {
+                assert_eq!("clang-14", linker.name);
+                assert_eq!(">=14, <16", linker.version.to_string());
+                assert_eq!(&["-fuse-ld=lld".to_string()], &linker.link_args[..]);
+            }
+            other => panic!("unexpected linker: {other:?}"),
+        }
+
+        assert_eq!("thumbv7em-none-eabihf", specs[2].triple);
+        assert!(!specs[2].std);
+        assert_eq!(Linker::BundledLld, specs[2].linker);
+
+        match specs[3].linker {
+            Linker::External { driver: LinkerDriver::Lld, linker } => {
+                assert_eq!("ld.lld", linker.name);
+                assert_eq!(GccMode::BareMetal, linker.mode());
+            }
+            other => panic!("unexpected linker: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.toml");
+
+        match Config::load(&path) {
+            Err(Error::ConfigReadFailed { path: error_path, error }) => {
+                assert_eq!(path, error_path);
+                assert_eq!(std::io::ErrorKind::NotFound, error.kind());
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_load_invalid() {
+        let invalid = [
+            // Unknown driver
+            r#"[[targets]]
+               triple = "x86_64-unknown-linux-gnu"
+               std = true
+               linker = { driver = "msvc", name = "link.exe", version = "14" }"#,
+            // Invalid version constraint
+            r#"[[targets]]
+               triple = "x86_64-unknown-linux-gnu"
+               std = true
+               linker = { driver = "gcc", name = "gcc", version = "~>7" }"#,
+            // Missing std
+            r#"[[targets]]
+               triple = "x86_64-unknown-linux-gnu"
+               linker = { driver = "bundled-lld" }"#,
+            // Unknown key
+            r#"[[targets]]
+               triple = "x86_64-unknown-linux-gnu"
+               std = true
+               rustflags = []
+               linker = { driver = "bundled-lld" }"#,
+        ];
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("self-test.toml");
+        for contents in invalid {
+            std::fs::write(&path, contents).unwrap();
+            match Config::load(&path) {
+                Err(Error::ConfigParseFailed { path: error_path, .. }) => {
+                    assert_eq!(path, error_path)
+                }
+                other => panic!("unexpected result for {contents}: {other:?}"),
+            }
+        }
+    }
+}
@@ -2,35 +2,203 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 use crate::utils::DisplayList;
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
 use std::ffi::OsString;
 use std::fmt::Display;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Output;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(tag = "variant")]
 pub(crate) enum Error {
     NoSysroot,
-    MissingBinary { directory: PathBuf, name: String },
-    WrongBinaryPermissions { path: PathBuf },
-    MetadataFetchFailed { path: PathBuf, error: std::io::Error },
-    VersionFetchFailed { binary: String, error: CommandError },
-    VersionParseFailed { binary: String },
-    BinaryVersionMismatch { binary: String, field: String, expected: String, found: String },
-    TargetLibraryMissing { target: String, library: String },
-    DuplicateTargetLibrary { target: String, library: String },
-    TargetLibraryDiscoveryFailed { path: PathBuf, error: std::io::Error },
-    LinkerNotFound { targets: Vec<String>, name: String, error: FindBinaryInPathError },
-    LinkerVersionFetchFailed { targets: Vec<String>, name: String, error: CommandError },
-    LinkerVersionParseFailed { targets: Vec<String>, name: String },
-    UnsupportedLinkerVersion { targets: Vec<String>, name: String, expected: String, found: String },
-    BundledLinkerMissing { targets: Vec<String> },
-    NonUtf8Path { path: PathBuf },
-    TemporaryCompilationDirectoryCreationFailed { error: std::io::Error },
-    WritingSampleProgramFailed { name: String, dest: PathBuf, error: std::io::Error },
-    SampleProgramCompilationFailed { name: String, error: CommandError },
-    CompilationArtifactsListingFailed { path: PathBuf, error: std::io::Error },
-    MissingCompilationArtifact { name: String, after_compiling: String },
-    UnexpectedCompilationArtifact { name: String, after_compiling: String },
+    MissingBinary {
+        #[serde(serialize_with = "lossy_path")]
+        directory: PathBuf,
+        name: String,
+    },
+    WrongBinaryPermissions {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+    },
+    MetadataFetchFailed {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+        #[serde(serialize_with = "display")]
+        error: std::io::Error,
+    },
+    VersionFetchFailed {
+        binary: String,
+        error: CommandError,
+    },
+    VersionParseFailed {
+        binary: String,
+    },
+    BinaryVersionMismatch {
+        binary: String,
+        field: String,
+        expected: String,
+        found: String,
+    },
+    TargetLibraryMissing {
+        target: String,
+        library: String,
+    },
+    DuplicateTargetLibrary {
+        target: String,
+        library: String,
+    },
+    TargetLibraryDiscoveryFailed {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+        #[serde(serialize_with = "display")]
+        error: std::io::Error,
+    },
+    LinkerNotFound {
+        targets: Vec<String>,
+        name: String,
+        error: FindBinaryInPathError,
+    },
+    LinkerVersionFetchFailed {
+        targets: Vec<String>,
+        name: String,
+        error: CommandError,
+    },
+    LinkerVersionParseFailed {
+        targets: Vec<String>,
+        name: String,
+    },
+    UnsupportedLinkerVersion {
+        targets: Vec<String>,
+        name: String,
+        expected: String,
+        found: String,
+    },
+    BundledLinkerMissing {
+        targets: Vec<String>,
+    },
+    NonUtf8Path {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+    },
+    TemporaryCompilationDirectoryCreationFailed {
+        #[serde(serialize_with = "display")]
+        error: std::io::Error,
+    },
+    WritingSampleProgramFailed {
+        name: String,
+        #[serde(serialize_with = "lossy_path")]
+        dest: PathBuf,
+        #[serde(serialize_with = "display")]
+        error: std::io::Error,
+    },
+    SampleProgramCompilationFailed {
+        name: String,
+        error: CommandError,
+    },
+    CompilationArtifactsListingFailed {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+        #[serde(serialize_with = "display")]
+        error: std::io::Error,
+    },
+    MissingCompilationArtifact {
+        name: String,
+        after_compiling: String,
+    },
+    UnexpectedCompilationArtifact {
+        name: String,
+        after_compiling: String,
+    },
+    UnknownCliFlag {
+        flag: String,
+    },
+    MissingCliFlagValue {
+        flag: String,
+    },
+    InvalidReportFormat {
+        format: String,
+    },
+    SampleProgramExecutionFailed {
+        name: String,
+        target: String,
+        error: CommandError,
+    },
+    SampleProgramWrongExitCode {
+        name: String,
+        target: String,
+        expected: i32,
+        error: CommandError,
+    },
+    SampleProgramWrongOutput {
+        name: String,
+        target: String,
+        expected: String,
+        found: String,
+    },
+    ConfigReadFailed {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+        #[serde(serialize_with = "display")]
+        error: std::io::Error,
+    },
+    ConfigParseFailed {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+        #[serde(serialize_with = "display")]
+        error: toml::de::Error,
+    },
+    ChecksFailed {
+        count: usize,
+        codes: Vec<String>,
+    },
+    UnknownTarget {
+        target: String,
+    },
+    TargetNotInstalled {
+        target: String,
+    },
+    InvalidCheckGroup {
+        group: String,
+    },
+    InvalidPinnedKeychain {
+        error: String,
+    },
+    PackageManifestsMissing {
+        #[serde(serialize_with = "lossy_path")]
+        directory: PathBuf,
+    },
+    PackageManifestReadFailed {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+        #[serde(serialize_with = "display")]
+        error: std::io::Error,
+    },
+    PackageManifestParseFailed {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+        #[serde(serialize_with = "display")]
+        error: serde_json::Error,
+    },
+    PackageManifestSignatureInvalid {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+        error: String,
+    },
+    InstalledFileReadFailed {
+        #[serde(serialize_with = "lossy_path")]
+        path: PathBuf,
+        #[serde(serialize_with = "display")]
+        error: std::io::Error,
+    },
+    InstalledFilesMismatch {
+        package: String,
+        modified: Vec<String>,
+        missing: Vec<String>,
+        extra: Vec<String>,
+    },
+    NoPinnedKeychain,
 }
 
 impl Error {
@@ -58,6 +226,26 @@ pub(crate) fn code(&self) -> u8 {
             Error::CompilationArtifactsListingFailed { .. } => 20,
             Error::MissingCompilationArtifact { .. } => 21,
             Error::UnexpectedCompilationArtifact { .. } => 22,
+            Error::UnknownCliFlag { .. } => 23,
+            Error::MissingCliFlagValue { .. } => 24,
+            Error::InvalidReportFormat { .. } => 25,
+            Error::SampleProgramExecutionFailed { .. } => 26,
+            Error::SampleProgramWrongExitCode { .. } => 27,
+            Error::SampleProgramWrongOutput { .. } => 28,
+            Error::ConfigReadFailed { .. } => 29,
+            Error::ConfigParseFailed { .. } => 30,
+            Error::ChecksFailed { .. } => 31,
+            Error::UnknownTarget { .. } => 32,
+            Error::TargetNotInstalled { .. } => 33,
+            Error::InvalidCheckGroup { .. } => 34,
+            Error::InvalidPinnedKeychain { .. } => 35,
+            Error::PackageManifestsMissing { .. } => 36,
+            Error::PackageManifestReadFailed { .. } => 37,
+            Error::PackageManifestParseFailed { .. } => 38,
+            Error::PackageManifestSignatureInvalid { .. } => 39,
+            Error::InstalledFileReadFailed { .. } => 40,
+            Error::InstalledFilesMismatch { .. } => 41,
+            Error::NoPinnedKeychain => 42,
         }
     }
 }
@@ -87,6 +275,26 @@ fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
             Error::CompilationArtifactsListingFailed { error, .. } => Some(error),
             Error::MissingCompilationArtifact { .. } => None,
             Error::UnexpectedCompilationArtifact { .. } => None,
+            Error::UnknownCliFlag { .. } => None,
+            Error::MissingCliFlagValue { .. } => None,
+            Error::InvalidReportFormat { .. } => None,
+            Error::SampleProgramExecutionFailed { error, .. } => Some(error),
+            Error::SampleProgramWrongExitCode { error, .. } => Some(error),
+            Error::SampleProgramWrongOutput { .. } => None,
+            Error::ConfigReadFailed { error, .. } => Some(error),
+            Error::ConfigParseFailed { error, .. } => Some(error),
+            Error::ChecksFailed { .. } => None,
+            Error::UnknownTarget { .. } => None,
+            Error::TargetNotInstalled { .. } => None,
+            Error::InvalidCheckGroup { .. } => None,
+            Error::InvalidPinnedKeychain { .. } => None,
+            Error::PackageManifestsMissing { .. } => None,
+            Error::PackageManifestReadFailed { error, .. } => Some(error),
+            Error::PackageManifestParseFailed { error, .. } => Some(error),
+            Error::PackageManifestSignatureInvalid { .. } => None,
+            Error::InstalledFileReadFailed { error, .. } => Some(error),
+            Error::InstalledFilesMismatch { .. } => None,
+            Error::NoPinnedKeychain => None,
         }
     }
 }
@@ -193,6 +401,97 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                      after compiling sample program `{after_compiling}`"
                 )
             }
+            Error::UnknownCliFlag { flag } => {
+                write!(f, "unknown command line flag {flag}")
+            }
+            Error::MissingCliFlagValue { flag } => {
+                write!(f, "missing value for command line flag {flag}")
+            }
+            Error::InvalidReportFormat { format } => {
+                write!(f, "unsupported report format {format} (expected human or json)")
+            }
+            Error::SampleProgramExecutionFailed { name, target, .. } => {
+                write!(f, "failed to execute sample program `{name}` for target {target}")
+            }
+            Error::SampleProgramWrongExitCode { name, target, expected, .. } => {
+                write!(
+                    f,
+                    "sample program `{name}` for target {target} \
+                     did not exit with the expected code {expected}"
+                )
+            }
+            Error::SampleProgramWrongOutput { name, target, expected, found } => {
+                write!(
+                    f,
+                    "sample program `{name}` for target {target} produced the wrong output \
+                     (expected {expected:?}, found {found:?})"
+                )
+            }
+            Error::ConfigReadFailed { path, .. } => {
+                write!(f, "failed to read the configuration file {}", path.display())
+            }
+            Error::ConfigParseFailed { path, .. } => {
+                write!(f, "failed to parse the configuration file {}", path.display())
+            }
+            Error::ChecksFailed { count: 1, codes } => {
+                write!(f, "1 check failed (error code {})", DisplayList(codes))
+            }
+            Error::ChecksFailed { count, codes } => {
+                write!(f, "{count} checks failed (error codes {})", DisplayList(codes))
+            }
+            Error::UnknownTarget { target } => {
+                write!(f, "target {target} is neither supported nor configured")
+            }
+            Error::TargetNotInstalled { target } => {
+                write!(f, "target {target} was requested but is not installed")
+            }
+            Error::InvalidCheckGroup { group } => {
+                write!(
+                    f,
+                    "unknown group of checks {group} \
+                     (expected files, binaries, targets, linkers or compile)"
+                )
+            }
+            Error::InvalidPinnedKeychain { error } => {
+                write!(f, "the keychain pinned in this build is not valid: {error}")
+            }
+            Error::PackageManifestsMissing { directory } => {
+                write!(f, "no signed package manifest found in {}", directory.display())
+            }
+            Error::PackageManifestReadFailed { path, .. } => {
+                write!(f, "failed to read the package manifest {}", path.display())
+            }
+            Error::PackageManifestParseFailed { path, .. } => {
+                write!(f, "failed to parse the package manifest {}", path.display())
+            }
+            Error::PackageManifestSignatureInvalid { path, error } => {
+                write!(
+                    f,
+                    "the signature of the package manifest {} is not valid: {error}",
+                    path.display()
+                )
+            }
+            Error::InstalledFileReadFailed { path, .. } => {
+                write!(f, "failed to read the installed file {}", path.display())
+            }
+            Error::InstalledFilesMismatch { package, modified, missing, extra } => {
+                write!(f, "installed files of package {package} don't match the signed manifest")?;
+                for (kind, files) in
+                    [("modified", modified), ("missing", missing), ("extra", extra)]
+                {
+                    if !files.is_empty() {
+                        write!(f, "; {kind}: {}", DisplayList(files))?;
+                    }
+                }
+                Ok(())
+            }
+            Error::NoPinnedKeychain => {
+                write!(
+                    f,
+                    "no keychain was pinned when this tool was built, \
+                     so the installed files cannot be verified"
+                )
+            }
         }
     }
 }
@@ -250,6 +549,37 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
+impl Serialize for CommandError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("CommandError", 6)?;
+        state.serialize_field("path", &self.path.to_string_lossy())?;
+        state.serialize_field(
+            "args",
+            &self.args.iter().map(|arg| arg.to_string_lossy()).collect::<Vec<_>>(),
+        )?;
+        match &self.kind {
+            CommandErrorKind::StartupFailed { error } => {
+                state.serialize_field("kind", "startup-failed")?;
+                state.serialize_field("error", &error.to_string())?;
+            }
+            CommandErrorKind::WaitFailed { error } => {
+                state.serialize_field("kind", "wait-failed")?;
+                state.serialize_field("error", &error.to_string())?;
+            }
+            CommandErrorKind::Failure { output } => {
+                state.serialize_field("kind", "failure")?;
+                state.serialize_field("status", &output.status.code())?;
+                state.serialize_field("stdout", &String::from_utf8_lossy(&output.stdout))?;
+                state.serialize_field("stderr", &String::from_utf8_lossy(&output.stderr))?;
+            }
+            CommandErrorKind::NonUtf8Output => {
+                state.serialize_field("kind", "non-utf8-output")?;
+            }
+        }
+        state.end()
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum CommandErrorKind {
     StartupFailed { error: std::io::Error },
@@ -258,7 +588,8 @@ pub(crate) enum CommandErrorKind {
     NonUtf8Output,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(tag = "variant")]
 pub(crate) enum FindBinaryInPathError {
     NoEnvironmentVariable,
     MissingBinary { name: String },
@@ -278,3 +609,15 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         }
     }
 }
+
+pub(crate) fn lossy_path<P, S>(path: &P, serializer: S) -> Result<S::Ok, S::Error>
+where
+    P: AsRef<Path>,
+    S: Serializer,
+{
+    serializer.serialize_str(&path.as_ref().to_string_lossy())
+}
+
+fn display<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
@@ -0,0 +1,495 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use crate::error::Error;
+use crate::report::{Check, Failures, Reporter};
+use criticaltrust::keys::PublicKey;
+use criticaltrust::manifests::{Package, PackageManifest};
+use criticaltrust::signatures::{Keychain, SignedPayload};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Keychain used to verify the package manifests, pinned when the tool is built. It contains the
+/// root key and the keys it signed, in the same format used by criticalup.
+#[derive(Deserialize)]
+struct PinnedKeychain {
+    root: PublicKey,
+    keys: Vec<SignedPayload<PublicKey>>,
+}
+
+pub(crate) fn check(
+    reporter: &dyn Reporter,
+    failures: &mut Failures<'_>,
+    sysroot: &Path,
+) -> Result<(), Error> {
+    check_with_pinned(reporter, failures, sysroot, option_env!("SELFTEST_KEYCHAIN"))
+}
+
+fn check_with_pinned(
+    reporter: &dyn Reporter,
+    failures: &mut Failures<'_>,
+    sysroot: &Path,
+    pinned: Option<&str>,
+) -> Result<(), Error> {
+    let keychain = match pinned {
+        Some(pinned) => load_keychain(pinned),
+        None => Err(Error::NoPinnedKeychain),
+    };
+    if let Some(keychain) = failures.collect(keychain)? {
+        check_with_keychain(reporter, failures, sysroot, &keychain)?;
+    }
+    Ok(())
+}
+
+fn load_keychain(pinned: &str) -> Result<Keychain, Error> {
+    let pinned: PinnedKeychain = serde_json::from_str(pinned)
+        .map_err(|error| Error::InvalidPinnedKeychain { error: error.to_string() })?;
+
+    let mut keychain = Keychain::new(&pinned.root)
+        .map_err(|error| Error::InvalidPinnedKeychain { error: error.to_string() })?;
+    for key in &pinned.keys {
+        keychain
+            .load(key)
+            .map_err(|error| Error::InvalidPinnedKeychain { error: error.to_string() })?;
+    }
+    Ok(keychain)
+}
+
+fn check_with_keychain(
+    reporter: &dyn Reporter,
+    failures: &mut Failures<'_>,
+    sysroot: &Path,
+    keychain: &Keychain,
+) -> Result<(), Error> {
+    let manifests = find_manifests(sysroot).and_then(|manifests| {
+        if manifests.is_empty() {
+            Err(Error::PackageManifestsMissing { directory: manifests_dir(sysroot) })
+        } else {
+            Ok(manifests)
+        }
+    });
+    let Some(manifests) = failures.collect(manifests)? else {
+        return Ok(());
+    };
+
+    let mut packages = Vec::new();
+    for path in &manifests {
+        if let Some(package) = failures.collect(load_manifest(path, keychain))? {
+            packages.push(package);
+        }
+    }
+
+    // Files are allowed in a managed prefix only if they are part of one of the packages, as
+    // multiple packages (for example the standard library of different targets) can share the
+    // same prefix.
+    let mut known_files = packages
+        .iter()
+        .flat_map(|package| package.files.iter().map(|file| PathBuf::from(&file.path)))
+        .collect::<HashSet<_>>();
+    known_files.extend(manifests.iter().map(|path| path.strip_prefix(sysroot).unwrap().into()));
+
+    for package in &packages {
+        failures.collect(check_package(reporter, sysroot, package, &known_files))?;
+    }
+    Ok(())
+}
+
+fn check_package(
+    reporter: &dyn Reporter,
+    sysroot: &Path,
+    package: &Package,
+    known_files: &HashSet<PathBuf>,
+) -> Result<(), Error> {
+    let mut modified = Vec::new();
+    let mut missing = Vec::new();
+    for file in &package.files {
+        let path = sysroot.join(&file.path);
+        // Symlinks are not followed: a file replaced by a symlink is reported as modified, even
+        // if the file it points to has the expected contents.
+        match std::fs::symlink_metadata(&path) {
+            Err(error) if error.kind() == ErrorKind::NotFound => missing.push(file.path.clone()),
+            Err(error) => return Err(Error::InstalledFileReadFailed { path, error }),
+            Ok(metadata) if !metadata.is_file() => modified.push(file.path.clone()),
+            Ok(_) if hash_file(&path)? != file.sha256 => modified.push(file.path.clone()),
+            Ok(_) => {}
+        }
+    }
+
+    let mut extra = Vec::new();
+    for prefix in &package.managed_prefixes {
+        find_extra_files(sysroot, &sysroot.join(prefix), known_files, &mut extra)?;
+    }
+    extra.sort();
+
+    if modified.is_empty() && missing.is_empty() && extra.is_empty() {
+        reporter.passed(&Check::PackageFiles {
+            package: &package.package,
+            commit: &package.commit,
+            files: package.files.len(),
+        });
+        Ok(())
+    } else {
+        Err(Error::InstalledFilesMismatch {
+            package: package.package.clone(),
+            modified,
+            missing,
+            extra,
+        })
+    }
+}
+
+/// Return the paths of the signed package manifests shipped in the sysroot, sorted by name.
+pub(crate) fn find_manifests(sysroot: &Path) -> Result<Vec<PathBuf>, Error> {
+    let dir = manifests_dir(sysroot);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut manifests = Vec::new();
+    let entries = std::fs::read_dir(&dir)
+        .map_err(|error| Error::PackageManifestReadFailed { path: dir.clone(), error })?;
+    for entry in entries {
+        let path = entry
+            .map_err(|error| Error::PackageManifestReadFailed { path: dir.clone(), error })?
+            .path();
+        if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+            manifests.push(path);
+        }
+    }
+    manifests.sort();
+    Ok(manifests)
+}
+
+fn manifests_dir(sysroot: &Path) -> PathBuf {
+    sysroot.join("share").join("criticaltrust").join("ferrocene")
+}
+
+fn load_manifest(path: &Path, keychain: &Keychain) -> Result<Package, Error> {
+    let contents = std::fs::read(path)
+        .map_err(|error| Error::PackageManifestReadFailed { path: path.into(), error })?;
+    let manifest: PackageManifest = serde_json::from_slice(&contents)
+        .map_err(|error| Error::PackageManifestParseFailed { path: path.into(), error })?;
+    manifest.signed.into_verified(keychain).map_err(|error| {
+        Error::PackageManifestSignatureInvalid { path: path.into(), error: error.to_string() }
+    })
+}
+
+fn find_extra_files(
+    sysroot: &Path,
+    dir: &Path,
+    known_files: &HashSet<PathBuf>,
+    extra: &mut Vec<String>,
+) -> Result<(), Error> {
+    match std::fs::symlink_metadata(dir) {
+        Ok(metadata) if metadata.is_dir() => {}
+        _ => return Ok(()),
+    }
+    let map_err = |error| Error::InstalledFileReadFailed { path: dir.into(), error };
+    for entry in std::fs::read_dir(dir).map_err(map_err)? {
+        let entry = entry.map_err(map_err)?;
+        let path = entry.path();
+        // The file type of a directory entry doesn't follow symlinks, so symlinks to directories
+        // are reported as extra files rather than walked into.
+        if entry.file_type().map_err(map_err)?.is_dir() {
+            find_extra_files(sysroot, &path, known_files, extra)?;
+        } else {
+            let relative = path.strip_prefix(sysroot).unwrap();
+            if !known_files.contains(relative) {
+                extra.push(relative.to_string_lossy().into_owned());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn hash_file(path: &Path) -> Result<Vec<u8>, Error> {
+    let map_err = |error| Error::InstalledFileReadFailed { path: path.into(), error };
+
+    let mut sha256 = Sha256::new();
+    let mut contents = File::open(path).map_err(map_err)?;
+    std::io::copy(&mut contents, &mut sha256).map_err(map_err)?;
+    Ok(sha256.finalize().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestUtils;
+    use criticaltrust::keys::{EphemeralKeyPair, KeyAlgorithm, KeyPair, KeyRole};
+    use criticaltrust::manifests::{ManifestVersion, PackageFile};
+
+    const ALGORITHM: KeyAlgorithm = KeyAlgorithm::EcdsaP256Sha256Asn1SpkiDer;
+
+    struct Signer {
+        key: EphemeralKeyPair,
+        keychain: Keychain,
+    }
+
+    impl Signer {
+        fn new() -> Self {
+            let root = EphemeralKeyPair::generate(ALGORITHM, KeyRole::Root, None).unwrap();
+            let key = EphemeralKeyPair::generate(ALGORITHM, KeyRole::Packages, None).unwrap();
+
+            let mut signed = SignedPayload::new(key.public()).unwrap();
+            signed.add_signature(&root).unwrap();
+
+            let pinned = serde_json::json!({ "root": root.public(), "keys": [signed] });
+            Self { key, keychain: load_keychain(&pinned.to_string()).unwrap() }
+        }
+
+        fn sign(&self, utils: &TestUtils, name: &str, files: &[(&str, &[u8])], prefixes: &[&str]) {
+            let package = Package {
+                product: "ferrocene".into(),
+                package: name.into(),
+                commit: "0000000".into(),
+                files: files
+                    .iter()
+                    .map(|(path, contents)| PackageFile {
+                        path: path.to_string(),
+                        posix_mode: 0o644,
+                        sha256: Sha256::digest(contents).to_vec(),
+                        needs_proxy: false,
+                    })
+                    .collect(),
+                managed_prefixes: prefixes.iter().map(|p| p.to_string()).collect(),
+            };
+
+            let mut signed = SignedPayload::new(&package).unwrap();
+            signed.add_signature(&self.key).unwrap();
+
+            let dir = manifests_dir(utils.sysroot());
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(
+                dir.join(format!("{name}.json")),
+                serde_json::to_vec(&PackageManifest { version: ManifestVersion::<1>, signed })
+                    .unwrap(),
+            )
+            .unwrap();
+        }
+    }
+
+    fn create_file(utils: &TestUtils, path: &str, contents: &[u8]) {
+        let path = utils.sysroot().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_check_valid_installation() {
+        let utils = TestUtils::new();
+        let signer = Signer::new();
+
+        create_file(&utils, "bin/rustc", b"rustc");
+        create_file(&utils, "lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib", b"std");
+        create_file(&utils, "lib/rustlib/aarch64-unknown-none/lib/libcore.rlib", b"core");
+        // Files outside of the managed prefixes are allowed.
+        create_file(&utils, "lib/rustlib/uninstall.sh", b"#!/bin/sh");
+
+        signer.sign(&utils, "rustc", &[("bin/rustc", b"rustc")], &[]);
+        signer.sign(
+            &utils,
+            "rust-std-x86_64-unknown-linux-gnu",
+            &[("lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib", b"std")],
+            &["lib/rustlib/x86_64-unknown-linux-gnu/"],
+        );
+        signer.sign(
+            &utils,
+            "rust-std-aarch64-unknown-none",
+            &[("lib/rustlib/aarch64-unknown-none/lib/libcore.rlib", b"core")],
+            &["lib/rustlib/aarch64-unknown-none/"],
+        );
+
+        check_with_keychain(
+            utils.reporter(),
+            &mut utils.failures(false),
+            utils.sysroot(),
+            &signer.keychain,
+        )
+        .unwrap();
+
+        utils.assert_report_success(
+            "installed files of package rustc (commit 0000000) match the signed manifest (1 files)",
+        );
+        utils.assert_report_success(
+            "installed files of package rust-std-x86_64-unknown-linux-gnu (commit 0000000) \
+             match the signed manifest (1 files)",
+        );
+        utils.assert_report_success(
+            "installed files of package rust-std-aarch64-unknown-none (commit 0000000) \
+             match the signed manifest (1 files)",
+        );
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_check_modified_missing_and_extra_files() {
+        let utils = TestUtils::new();
+        let signer = Signer::new();
+
+        create_file(&utils, "lib/rustlib/x86_64-unknown-linux-gnu/lib/libcore.rlib", b"core");
+        create_file(&utils, "lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib", b"patched");
+        create_file(&utils, "lib/rustlib/x86_64-unknown-linux-gnu/lib/libextra.rlib", b"extra");
+
+        signer.sign(
+            &utils,
+            "rust-std-x86_64-unknown-linux-gnu",
+            &[
+                ("lib/rustlib/x86_64-unknown-linux-gnu/lib/liballoc.rlib", b"alloc"),
+                ("lib/rustlib/x86_64-unknown-linux-gnu/lib/libcore.rlib", b"core"),
+                ("lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib", b"std"),
+            ],
+            &["lib/rustlib/x86_64-unknown-linux-gnu/"],
+        );
+
+        let result = check_with_keychain(
+            utils.reporter(),
+            &mut utils.failures(false),
+            utils.sysroot(),
+            &signer.keychain,
+        );
+        match result {
+            Err(Error::InstalledFilesMismatch { package, modified, missing, extra }) => {
+                assert_eq!("rust-std-x86_64-unknown-linux-gnu", package);
+                assert_eq!(vec!["lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib"], modified);
+                assert_eq!(vec!["lib/rustlib/x86_64-unknown-linux-gnu/lib/liballoc.rlib"], missing);
+                assert_eq!(vec!["lib/rustlib/x86_64-unknown-linux-gnu/lib/libextra.rlib"], extra);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_check_untrusted_signature() {
+        let utils = TestUtils::new();
+        let signer = Signer::new();
+        let other_signer = Signer::new();
+
+        create_file(&utils, "bin/rustc", b"rustc");
+        other_signer.sign(&utils, "rustc", &[("bin/rustc", b"rustc")], &[]);
+
+        let result = check_with_keychain(
+            utils.reporter(),
+            &mut utils.failures(false),
+            utils.sysroot(),
+            &signer.keychain,
+        );
+        match result {
+            Err(Error::PackageManifestSignatureInvalid { path, .. }) => {
+                assert!(path.ends_with("share/criticaltrust/ferrocene/rustc.json"));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_check_no_manifests() {
+        let utils = TestUtils::new();
+        let signer = Signer::new();
+
+        match check_with_keychain(
+            utils.reporter(),
+            &mut utils.failures(false),
+            utils.sysroot(),
+            &signer.keychain,
+        ) {
+            Err(Error::PackageManifestsMissing { directory }) => {
+                assert_eq!(manifests_dir(utils.sysroot()), directory);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_check_no_manifests_keep_going() {
+        let utils = TestUtils::new();
+        let signer = Signer::new();
+
+        let mut failures = utils.failures(true);
+        check_with_keychain(utils.reporter(), &mut failures, utils.sysroot(), &signer.keychain)
+            .unwrap();
+        assert!(matches!(failures.finish(), Err(Error::ChecksFailed { count: 1, .. })));
+
+        utils.assert_report_error();
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_check_symlinks_are_not_followed() {
+        let utils = TestUtils::new();
+        let signer = Signer::new();
+
+        // A signed file replaced by a symlink to a file with the same contents.
+        create_file(&utils, "lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib.orig", b"std");
+        std::os::unix::fs::symlink(
+            "libstd.rlib.orig",
+            utils.sysroot().join("lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib"),
+        )
+        .unwrap();
+        // A symlink to a directory outside of the managed prefix.
+        create_file(&utils, "outside/libextra.rlib", b"extra");
+        std::os::unix::fs::symlink(
+            utils.sysroot().join("outside"),
+            utils.sysroot().join("lib/rustlib/x86_64-unknown-linux-gnu/lib/extra"),
+        )
+        .unwrap();
+
+        signer.sign(
+            &utils,
+            "rust-std-x86_64-unknown-linux-gnu",
+            &[
+                ("lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib", b"std"),
+                ("lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib.orig", b"std"),
+            ],
+            &["lib/rustlib/x86_64-unknown-linux-gnu/"],
+        );
+
+        let result = check_with_keychain(
+            utils.reporter(),
+            &mut utils.failures(false),
+            utils.sysroot(),
+            &signer.keychain,
+        );
+        match result {
+            Err(Error::InstalledFilesMismatch { modified, missing, extra, .. }) => {
+                assert_eq!(vec!["lib/rustlib/x86_64-unknown-linux-gnu/lib/libstd.rlib"], modified);
+                assert!(missing.is_empty());
+                assert_eq!(vec!["lib/rustlib/x86_64-unknown-linux-gnu/lib/extra"], extra);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_check_without_pinned_keychain() {
+        let utils = TestUtils::new();
+
+        let result =
+            check_with_pinned(utils.reporter(), &mut utils.failures(false), utils.sysroot(), None);
+        assert!(matches!(result, Err(Error::NoPinnedKeychain)));
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_check_without_pinned_keychain_keep_going() {
+        let utils = TestUtils::new();
+
+        let mut failures = utils.failures(true);
+        check_with_pinned(utils.reporter(), &mut failures, utils.sysroot(), None).unwrap();
+        assert!(matches!(failures.finish(), Err(Error::ChecksFailed { count: 1, .. })));
+
+        utils.assert_report_error();
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_load_keychain_invalid() {
+        assert!(matches!(load_keychain("{}"), Err(Error::InvalidPinnedKeychain { .. })));
+    }
+}
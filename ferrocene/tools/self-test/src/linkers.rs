@@ -2,11 +2,13 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 use crate::error::Error;
-use crate::report::Reporter;
+use crate::report::{Check, Failures, Reporter};
 use crate::targets::Target;
-use crate::utils::{find_binary_in_path, run_command, DisplayList};
+use crate::utils::{find_binary_in_path, run_command};
 use crate::Environment;
-use std::collections::BTreeMap;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Display;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -14,53 +16,108 @@
 pub(crate) enum Linker {
     BundledLld,
     GccUbuntu18 { target: &'static str, mode: GccMode },
+    External { driver: LinkerDriver, linker: &'static ExternalLinker },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub(crate) enum GccMode {
     Normal,
-    #[cfg_attr(not(test), allow(unused))]
     BareMetal,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum LinkerDriver {
+    Gcc,
+    Clang,
+    Lld,
+}
+
+/// Linker declared by the user in the configuration file, rather than built into the tool.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct ExternalLinker {
+    pub(crate) name: String,
+    pub(crate) version: VersionReq,
+    #[serde(default)]
+    pub(crate) bare_metal: bool,
+    #[serde(default)]
+    pub(crate) link_args: Vec<String>,
+}
+
+/// Check the linkers of all the targets, adding the flags needed to use them to the targets.
+///
+/// Targets whose linker failed the check are removed from the list when `--keep-going` is used,
+/// as no sample program could be compiled for them anyway.
 pub(crate) fn check_and_add_rustflags(
     reporter: &dyn Reporter,
+    failures: &mut Failures<'_>,
     environment: &Environment,
     sysroot: &Path,
-    targets: &mut [Target],
+    targets: &mut Vec<Target>,
 ) -> Result<(), Error> {
     // Multiple installed targets might use the same linker, deduplicate them.
     let mut linkers = BTreeMap::new();
-    for target in targets {
+    for target in targets.iter_mut() {
         linkers.entry(target.linker).or_insert_with(Vec::new).push(target);
     }
 
+    let mut failed = HashSet::new();
     for (linker, targets) in linkers {
         let triples = targets.iter().map(|t| t.triple.to_string()).collect::<Vec<_>>();
-        let (bin, flavor, extra_flags): (_, _, &[&str]) = match linker {
-            Linker::BundledLld => (check_bundled_lld(reporter, sysroot, &triples)?, "ld.lld", &[]),
-            Linker::GccUbuntu18 { target: gcc_target, mode } => (
-                check_gcc(reporter, environment, &triples, gcc_target, mode, [7, 5])?,
-                "gcc",
-                match mode {
-                    GccMode::Normal => &[],
-                    GccMode::BareMetal => &["-ffreestanding", "-nostdlib"],
-                },
-            ),
+        let Some((bin, flavor, extra_flags)) =
+            failures.collect(check_linker(reporter, environment, sysroot, linker, &triples))?
+        else {
+            failed.extend(targets.iter().map(|t| t.triple));
+            continue;
         };
-        let bin = bin.to_str().ok_or_else(|| Error::NonUtf8Path { path: bin.clone() })?;
         for target in targets {
             target.rustflags.push(format!("-Clinker={bin}"));
             target.rustflags.push(format!("-Clinker-flavor={flavor}"));
-            for flag in extra_flags {
+            for flag in &extra_flags {
                 target.rustflags.push(format!("-Clink-arg={flag}"));
             }
         }
     }
+    targets.retain(|target| !failed.contains(target.triple));
 
     Ok(())
 }
 
+fn check_linker(
+    reporter: &dyn Reporter,
+    environment: &Environment,
+    sysroot: &Path,
+    linker: Linker,
+    triples: &[String],
+) -> Result<(String, &'static str, Vec<&'static str>), Error> {
+    let (bin, flavor, extra_flags): (_, _, Vec<&str>) = match linker {
+        Linker::BundledLld => {
+            (check_bundled_lld(reporter, sysroot, triples)?, "ld.lld", Vec::new())
+        }
+        Linker::GccUbuntu18 { target: gcc_target, mode } => (
+            check_gcc(reporter, environment, triples, gcc_target, mode, [7, 5])?,
+            "gcc",
+            mode.link_args().to_vec(),
+        ),
+        Linker::External { driver, linker } => (
+            check_external(reporter, environment, triples, driver, linker)?,
+            driver.flavor(),
+            match driver {
+                LinkerDriver::Gcc | LinkerDriver::Clang => linker.mode().link_args().to_vec(),
+                LinkerDriver::Lld => Vec::new(),
+            }
+            .into_iter()
+            .chain(linker.link_args.iter().map(|arg| arg.as_str()))
+            .collect(),
+        ),
+    };
+    let bin = bin
+        .into_os_string()
+        .into_string()
+        .map_err(|bin| Error::NonUtf8Path { path: bin.into() })?;
+    Ok((bin, flavor, extra_flags))
+}
+
 fn check_bundled_lld(
     reporter: &dyn Reporter,
     sysroot: &Path,
@@ -74,7 +131,7 @@ fn check_bundled_lld(
         .join("rust-lld");
 
     if path.is_file() {
-        reporter.success(&format!("bundled linker detected, for target {}", DisplayList(targets)));
+        reporter.passed(&Check::BundledLinker { targets, path: &path });
         Ok(path)
     } else {
         Err(Error::BundledLinkerMissing { targets: targets.into() })
@@ -89,46 +146,114 @@ fn check_gcc(
     gcc_mode: GccMode,
     expected_version: [u8; 2],
 ) -> Result<PathBuf, Error> {
-    let name = format!("{gcc_target}-gcc");
-    let bin = find_binary_in_path(environment, &name).map_err(|error| Error::LinkerNotFound {
+    check_linker_binary(
+        reporter,
+        environment,
+        targets,
+        &format!("{gcc_target}-gcc"),
+        LinkerDriver::Gcc,
+        gcc_mode,
+        &VersionReq::exact(&expected_version),
+    )
+}
+
+fn check_external(
+    reporter: &dyn Reporter,
+    environment: &Environment,
+    targets: &[String],
+    driver: LinkerDriver,
+    linker: &ExternalLinker,
+) -> Result<PathBuf, Error> {
+    check_linker_binary(
+        reporter,
+        environment,
+        targets,
+        &linker.name,
+        driver,
+        linker.mode(),
+        &linker.version,
+    )
+}
+
+fn check_linker_binary(
+    reporter: &dyn Reporter,
+    environment: &Environment,
+    targets: &[String],
+    name: &str,
+    driver: LinkerDriver,
+    mode: GccMode,
+    expected_version: &VersionReq,
+) -> Result<PathBuf, Error> {
+    let bin = find_binary_in_path(environment, name).map_err(|error| Error::LinkerNotFound {
         targets: targets.into(),
-        name: name.clone(),
+        name: name.into(),
         error,
     })?;
 
     let version_output = run_command(Command::new(&bin).arg("--version")).map_err(|error| {
-        Error::LinkerVersionFetchFailed { targets: targets.into(), name: name.clone(), error }
+        Error::LinkerVersionFetchFailed { targets: targets.into(), name: name.into(), error }
     })?;
 
-    let parsed_version = extract_gcc_version(&name, &version_output.stdout).ok_or_else(|| {
-        Error::LinkerVersionParseFailed { targets: targets.into(), name: name.clone() }
+    let parsed_version = match driver {
+        LinkerDriver::Gcc => extract_gcc_version(name, &version_output.stdout),
+        LinkerDriver::Clang => extract_clang_version(&version_output.stdout),
+        LinkerDriver::Lld => extract_lld_version(&version_output.stdout),
+    }
+    .ok_or_else(|| Error::LinkerVersionParseFailed {
+        targets: targets.into(),
+        name: name.into(),
     })?;
 
-    if &parsed_version.parsed[..2] == &expected_version {
-        let suffix = match gcc_mode {
-            GccMode::Normal => "",
-            GccMode::BareMetal => " (bare metal)",
-        };
-        reporter.success(&format!(
-            "linker {name} {}{suffix} detected, for target {}",
-            parsed_version.raw,
-            DisplayList(targets)
-        ));
+    if expected_version.matches(&parsed_version.parsed) {
+        reporter.passed(&Check::Linker {
+            targets,
+            name,
+            path: &bin,
+            version: &parsed_version.raw,
+            bare_metal: mode == GccMode::BareMetal,
+        });
         Ok(bin)
     } else {
         Err(Error::UnsupportedLinkerVersion {
             targets: targets.into(),
             name: name.into(),
-            expected: format!(
-                "{}.x",
-                expected_version.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(".")
-            ),
+            expected: expected_version.to_string(),
             found: parsed_version.raw,
         })
     }
 }
 
-fn extract_gcc_version(binary_name: &str, output: &str) -> Option<GccVersion> {
+impl Linker {
+    /// Human-readable name of the linker, used when listing the checks that would be executed.
+    pub(crate) fn name(&self) -> String {
+        match self {
+            Linker::BundledLld => "bundled rust-lld".into(),
+            Linker::GccUbuntu18 { target, .. } => format!("{target}-gcc"),
+            Linker::External { linker, .. } => linker.name.clone(),
+        }
+    }
+}
+
+impl GccMode {
+    fn link_args(self) -> &'static [&'static str] {
+        match self {
+            GccMode::Normal => &[],
+            GccMode::BareMetal => &["-ffreestanding", "-nostdlib"],
+        }
+    }
+}
+
+impl LinkerDriver {
+    fn flavor(self) -> &'static str {
+        match self {
+            // Clang accepts the same command line interface as GCC when used as a linker driver.
+            LinkerDriver::Gcc | LinkerDriver::Clang => "gcc",
+            LinkerDriver::Lld => "ld.lld",
+        }
+    }
+}
+
+fn extract_gcc_version(binary_name: &str, output: &str) -> Option<LinkerVersion> {
     let first_line = output.lines().next()?;
     let mut segments = first_line.split(' ');
 
@@ -141,18 +266,150 @@ fn extract_gcc_version(binary_name: &str, output: &str) -> Option<GccVersion> {
     }
 
     // Parse the version number at the end of the first line
-    let raw = segments.next_back()?;
-    Some(GccVersion {
-        parsed: raw.split('.').map(|n| n.parse().ok()).collect::<Option<_>>()?,
-        raw: raw.into(),
-    })
+    LinkerVersion::parse(segments.next_back()?)
+}
+
+fn extract_clang_version(output: &str) -> Option<LinkerVersion> {
+    // Clang's version output starts with a line like "clang version 14.0.0-1ubuntu1", optionally
+    // prefixed by the name of the vendor (for example "Ubuntu clang version 14.0.0-1ubuntu1").
+    let (_, rest) = output.lines().next()?.split_once("clang version ")?;
+    let raw = rest.split(' ').next()?;
+    LinkerVersion::parse(raw.split_once('-').map(|(version, _)| version).unwrap_or(raw))
 }
 
-struct GccVersion {
+fn extract_lld_version(output: &str) -> Option<LinkerVersion> {
+    // LLD's version output is a line like "LLD 14.0.0 (compatible with GNU linkers)", optionally
+    // prefixed by the name of the vendor (for example "Ubuntu LLD 14.0.0").
+    let mut segments = output.lines().next()?.split(' ');
+    segments.find(|segment| *segment == "LLD")?;
+    LinkerVersion::parse(segments.next()?)
+}
+
+struct LinkerVersion {
     raw: String,
     parsed: Vec<u8>,
 }
 
+impl LinkerVersion {
+    fn parse(raw: &str) -> Option<Self> {
+        Some(LinkerVersion {
+            parsed: raw.split('.').map(|n| n.parse().ok()).collect::<Option<_>>()?,
+            raw: raw.into(),
+        })
+    }
+}
+
+/// Comma-separated list of version constraints, all of which must be satisfied.
+///
+/// Each constraint is a version optionally prefixed by one of the `=`, `>`, `>=`, `<` or `<=`
+/// operators. Constraints without an operator (or with `=`) match all versions starting with the
+/// provided components: `7.5`, `=7.5` and `7.5.x` all accept both `7.5.0` and `7.5.3`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(try_from = "String")]
+pub(crate) struct VersionReq {
+    constraints: Vec<VersionConstraint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct VersionConstraint {
+    op: VersionOp,
+    version: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum VersionOp {
+    Exact,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl VersionReq {
+    pub(crate) fn parse(input: &str) -> Option<Self> {
+        let mut constraints = Vec::new();
+        for constraint in input.split(',') {
+            let constraint = constraint.trim();
+            let (op, version) = if let Some(version) = constraint.strip_prefix(">=") {
+                (VersionOp::GreaterOrEqual, version)
+            } else if let Some(version) = constraint.strip_prefix("<=") {
+                (VersionOp::LessOrEqual, version)
+            } else if let Some(version) = constraint.strip_prefix('>') {
+                (VersionOp::Greater, version)
+            } else if let Some(version) = constraint.strip_prefix('<') {
+                (VersionOp::Less, version)
+            } else if let Some(version) = constraint.strip_prefix('=') {
+                (VersionOp::Exact, version)
+            } else {
+                (VersionOp::Exact, constraint)
+            };
+
+            let version = version.trim();
+            let version = version.strip_suffix(".x").unwrap_or(version);
+            constraints.push(VersionConstraint {
+                op,
+                version: version.split('.').map(|n| n.parse().ok()).collect::<Option<_>>()?,
+            });
+        }
+        Some(VersionReq { constraints })
+    }
+
+    fn exact(version: &[u8]) -> Self {
+        VersionReq {
+            constraints: vec![VersionConstraint { op: VersionOp::Exact, version: version.into() }],
+        }
+    }
+
+    fn matches(&self, version: &[u8]) -> bool {
+        self.constraints.iter().all(|constraint| {
+            let expected = &constraint.version;
+
+            // Missing components are treated as zeroes, so that 7.5 is the same as 7.5.0.
+            let len = version.len().max(expected.len());
+            let pad = |v: &[u8]| -> Vec<u8> {
+                v.iter().copied().chain(std::iter::repeat(0)).take(len).collect()
+            };
+            let ordering = pad(version).cmp(&pad(expected));
+
+            match constraint.op {
+                VersionOp::Exact => version.starts_with(expected),
+                VersionOp::Greater => ordering.is_gt(),
+                VersionOp::GreaterOrEqual => ordering.is_ge(),
+                VersionOp::Less => ordering.is_lt(),
+                VersionOp::LessOrEqual => ordering.is_le(),
+            }
+        })
+    }
+}
+
+impl TryFrom<String> for VersionReq {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        VersionReq::parse(&value).ok_or_else(|| format!("invalid version constraint: {value}"))
+    }
+}
+
+impl Display for VersionReq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (idx, constraint) in self.constraints.iter().enumerate() {
+            if idx != 0 {
+                f.write_str(", ")?;
+            }
+            let version =
+                constraint.version.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(".");
+            match constraint.op {
+                VersionOp::Exact => write!(f, "{version}.x")?,
+                VersionOp::Greater => write!(f, ">{version}")?,
+                VersionOp::GreaterOrEqual => write!(f, ">={version}")?,
+                VersionOp::Less => write!(f, "<{version}")?,
+                VersionOp::LessOrEqual => write!(f, "<={version}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,7 +449,7 @@ fn test_check() {
             .unwrap()
             .to_string();
 
-        let mut targets = [
+        let mut targets = vec![
             Target {
                 spec: &TargetSpec {
                     triple: "x86_64-unknown-linux-gnu",
@@ -255,8 +512,14 @@ fn test_check() {
             },
         ];
 
-        check_and_add_rustflags(utils.reporter(), utils.env(), utils.sysroot(), &mut targets)
-            .unwrap();
+        check_and_add_rustflags(
+            utils.reporter(),
+            &mut utils.failures(false),
+            utils.env(),
+            utils.sysroot(),
+            &mut targets,
+        )
+        .unwrap();
 
         assert_eq!(
             &[format!("-Clinker={gcc_x86_64}"), format!("-Clinker-flavor=gcc")],
@@ -304,6 +567,64 @@ fn test_check() {
         utils.assert_no_reports();
     }
 
+    #[test]
+    fn test_check_keep_going() {
+        let utils = TestUtils::new();
+        let gcc = utils
+            .bin("x86_64-linux-gnu-gcc")
+            .stdout("x86_64-linux-gnu-gcc 7.5.0")
+            .expected_args(&["--version"])
+            .external()
+            .create();
+
+        let mut targets = vec![
+            Target {
+                spec: &TargetSpec {
+                    triple: "aarch64-unknown-none",
+                    std: false,
+                    linker: Linker::BundledLld,
+                },
+                rustflags: Vec::new(),
+            },
+            Target {
+                spec: &TargetSpec {
+                    triple: "x86_64-unknown-linux-gnu",
+                    std: true,
+                    linker: Linker::GccUbuntu18 {
+                        target: "x86_64-linux-gnu",
+                        mode: GccMode::Normal,
+                    },
+                },
+                rustflags: Vec::new(),
+            },
+        ];
+
+        let mut failures = utils.failures(true);
+        check_and_add_rustflags(
+            utils.reporter(),
+            &mut failures,
+            utils.env(),
+            utils.sysroot(),
+            &mut targets,
+        )
+        .unwrap();
+
+        // The target without a working linker is removed, the other one is still configured.
+        assert_eq!(1, targets.len());
+        assert_eq!("x86_64-unknown-linux-gnu", targets[0].triple);
+        assert_eq!(
+            &[format!("-Clinker={}", gcc.display()), "-Clinker-flavor=gcc".to_string()],
+            &targets[0].rustflags[..]
+        );
+        assert!(matches!(failures.finish(), Err(Error::ChecksFailed { .. })));
+
+        utils.assert_report_success(
+            "linker x86_64-linux-gnu-gcc 7.5.0 detected, for target x86_64-unknown-linux-gnu",
+        );
+        utils.assert_report_error();
+        utils.assert_no_reports();
+    }
+
     #[test]
     fn test_check_bundled_lld() {
         let utils = TestUtils::new();
@@ -488,4 +809,161 @@ fn test_extract_gcc_version() {
         assert!(extract_gcc_version("gcc", "x86_64-linux-gnu-gcc 1.0.0").is_none());
         assert!(extract_gcc_version("gcc", "gcc 1.foo.0").is_none());
     }
+
+    #[test]
+    fn test_check_external() {
+        let utils = TestUtils::new();
+        let clang = utils
+            .bin("clang-14")
+            .stdout("Ubuntu clang version 14.0.0-1ubuntu1\nTarget: x86_64-pc-linux-gnu\n")
+            .expected_args(&["--version"])
+            .external()
+            .create()
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        // Linkers and targets from the configuration file are leaked to make them 'static.
+        let linker = Box::leak(Box::new(ExternalLinker {
+            name: "clang-14".into(),
+            version: VersionReq::parse(">=14, <16").unwrap(),
+            bare_metal: true,
+            link_args: vec!["--target=x86_64-unknown-none".into()],
+        }));
+        let mut targets = vec![Target {
+            spec: Box::leak(Box::new(TargetSpec {
+                triple: "x86_64-unknown-none",
+                std: false,
+                linker: Linker::External { driver: LinkerDriver::Clang, linker },
+            })),
+            rustflags: Vec::new(),
+        }];
+
+        check_and_add_rustflags(
+            utils.reporter(),
+            &mut utils.failures(false),
+            utils.env(),
+            utils.sysroot(),
+            &mut targets,
+        )
+        .unwrap();
+
+        assert_eq!(
+            &[
+                format!("-Clinker={clang}"),
+                "-Clinker-flavor=gcc".into(),
+                "-Clink-arg=-ffreestanding".into(),
+                "-Clink-arg=-nostdlib".into(),
+                "-Clink-arg=--target=x86_64-unknown-none".into(),
+            ],
+            &targets[0].rustflags[..]
+        );
+        utils.assert_report_success(
+            "linker clang-14 14.0.0 (bare metal) detected, for target x86_64-unknown-none",
+        );
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_check_external_unsupported_version() {
+        let utils = TestUtils::new();
+        utils
+            .bin("ld.lld")
+            .stdout("LLD 12.0.1 (compatible with GNU linkers)")
+            .expected_args(&["--version"])
+            .external()
+            .create();
+
+        let linker = ExternalLinker {
+            name: "ld.lld".into(),
+            version: VersionReq::parse(">=14").unwrap(),
+            bare_metal: false,
+            link_args: Vec::new(),
+        };
+
+        match check_external(
+            utils.reporter(),
+            utils.env(),
+            &["aarch64-unknown-none".into()],
+            LinkerDriver::Lld,
+            &linker,
+        ) {
+            Err(Error::UnsupportedLinkerVersion { targets, name, expected, found }) => {
+                assert_eq!(&["aarch64-unknown-none".to_string()], &targets[..]);
+                assert_eq!("ld.lld", name);
+                assert_eq!(">=14", expected);
+                assert_eq!("12.0.1", found);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_extract_clang_version() {
+        let valid_outputs = [
+            "clang version 14.0.6",
+            "Ubuntu clang version 14.0.6-1ubuntu1\nTarget: x86_64-pc-linux-gnu\n",
+            "Vendor clang version 14.0.6 (https://github.com/llvm/llvm-project abcdef)",
+        ];
+        for output in valid_outputs {
+            let result = extract_clang_version(output).unwrap();
+            assert_eq!("14.0.6", result.raw);
+            assert_eq!([14, 0, 6], result.parsed[..]);
+        }
+
+        // Invalid outputs
+        assert!(extract_clang_version("").is_none());
+        assert!(extract_clang_version("gcc 11.3.0").is_none());
+        assert!(extract_clang_version("clang version foo").is_none());
+    }
+
+    #[test]
+    fn test_extract_lld_version() {
+        let valid_outputs = ["LLD 14.0.6 (compatible with GNU linkers)", "Ubuntu LLD 14.0.6"];
+        for output in valid_outputs {
+            let result = extract_lld_version(output).unwrap();
+            assert_eq!("14.0.6", result.raw);
+            assert_eq!([14, 0, 6], result.parsed[..]);
+        }
+
+        // Invalid outputs
+        assert!(extract_lld_version("").is_none());
+        assert!(extract_lld_version("GNU ld (GNU Binutils) 2.38").is_none());
+        assert!(extract_lld_version("LLD").is_none());
+    }
+
+    #[test]
+    fn test_version_req() {
+        let assert_matches = |req: &str, version: &[u8], expected: bool| {
+            assert_eq!(
+                expected,
+                VersionReq::parse(req).unwrap().matches(version),
+                "{req} matching {version:?}"
+            );
+        };
+
+        assert_matches("7.5", &[7, 5, 0], true);
+        assert_matches("7.5.x", &[7, 5, 3], true);
+        assert_matches("=7.5", &[7, 5], true);
+        assert_matches("7.5", &[7, 6, 0], false);
+        assert_matches("7.5", &[7], false);
+        assert_matches(">=9", &[9, 0, 0], true);
+        assert_matches(">=9", &[8, 9, 9], false);
+        assert_matches(">9", &[9, 0, 0], false);
+        assert_matches(">9", &[9, 0, 1], true);
+        assert_matches("<12", &[11, 9], true);
+        assert_matches("<12", &[12], false);
+        assert_matches("<=12", &[12, 0, 0], true);
+        assert_matches(">=9.2, <12", &[10, 3, 1], true);
+        assert_matches(">=9.2, <12", &[9, 1, 0], false);
+        assert_matches(">=9.2, <12", &[12, 1, 0], false);
+
+        assert_eq!("7.5.x", VersionReq::parse("=7.5").unwrap().to_string());
+        assert_eq!(">=9.2, <12", VersionReq::parse(" >= 9.2 ,<12").unwrap().to_string());
+
+        assert!(VersionReq::parse("").is_none());
+        assert!(VersionReq::parse("~7").is_none());
+        assert!(VersionReq::parse(">=9,").is_none());
+    }
 }
@@ -2,9 +2,13 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 mod binaries;
+mod cli;
 mod compile;
+mod config;
 mod error;
+mod files;
 mod linkers;
+mod plan;
 mod report;
 mod targets;
 mod utils;
@@ -12,18 +16,40 @@
 #[cfg(test)]
 mod test_utils;
 
+use crate::cli::{CheckGroup, CliOptions, ReportFormat};
+use crate::config::Config;
 use crate::error::Error;
-use crate::report::{Reporter, StderrReporter};
+use crate::report::{Failures, JsonReporter, Reporter, StderrReporter};
+use std::collections::HashMap;
 use std::ffi::OsString;
 use std::path::PathBuf;
 
 struct Environment {
     path: Option<OsString>,
+    runners: HashMap<String, String>,
 }
 
 impl Environment {
     fn gather() -> Self {
-        Self { path: std::env::var_os("PATH") }
+        Self {
+            path: std::env::var_os("PATH"),
+            runners: std::env::vars()
+                .filter(|(key, _)| key.starts_with("CARGO_TARGET_") && key.ends_with("_RUNNER"))
+                .collect(),
+        }
+    }
+
+    /// Return the command used to execute binaries of the given target, reading the same
+    /// environment variables as Cargo (`CARGO_TARGET_<TRIPLE>_RUNNER`).
+    fn runner_for(&self, target: &str) -> Option<Vec<String>> {
+        let key = format!("CARGO_TARGET_{}_RUNNER", target.to_uppercase().replace(['-', '.'], "_"));
+        let runner =
+            self.runners.get(&key)?.split_whitespace().map(String::from).collect::<Vec<_>>();
+        if runner.is_empty() {
+            None
+        } else {
+            Some(runner)
+        }
     }
 }
 
@@ -32,29 +58,80 @@ fn get_sysroot() -> Option<PathBuf> {
     Some(current_exe.parent()?.parent()?.to_path_buf())
 }
 
-fn main_inner(reporter: &dyn Reporter) -> Result<(), Error> {
+fn main_inner(
+    reporter: &dyn Reporter,
+    options: CliOptions,
+    sysroot: Option<PathBuf>,
+) -> Result<(), Error> {
     let environment = Environment::gather();
 
-    let sysroot = get_sysroot().ok_or(Error::NoSysroot)?;
+    let sysroot = sysroot.ok_or(Error::NoSysroot)?;
     reporter.info(&format!("using sysroot {}", sysroot.display()));
 
-    binaries::check(reporter, &sysroot)?;
-    let mut targets = targets::check(reporter, &sysroot)?;
-    linkers::check_and_add_rustflags(reporter, &environment, &sysroot, &mut targets)?;
-    compile::check(reporter, &sysroot, &targets)?;
+    let config = match &options.config {
+        Some(path) => {
+            reporter.info(&format!("using configuration file {}", path.display()));
+            Config::load(path)?
+        }
+        None => Config::default(),
+    };
+
+    let configured_targets = config.into_target_specs();
+
+    if options.list {
+        for check in plan::plan(&options, &sysroot, configured_targets)? {
+            reporter.planned(&check);
+        }
+        return Ok(());
+    }
+
+    let mut failures = Failures::new(reporter, options.keep_going);
+    if options.needs(CheckGroup::Files) {
+        files::check(reporter, &mut failures, &sysroot)?;
+    }
+    if options.needs(CheckGroup::Binaries) {
+        binaries::check(reporter, &mut failures, &sysroot)?;
+    }
+    if options.needs(CheckGroup::Targets) {
+        let mut targets = targets::check(
+            reporter,
+            &mut failures,
+            &sysroot,
+            configured_targets,
+            &options.targets,
+        )?;
+        if options.needs(CheckGroup::Linkers) {
+            linkers::check_and_add_rustflags(
+                reporter,
+                &mut failures,
+                &environment,
+                &sysroot,
+                &mut targets,
+            )?;
+        }
+        if options.needs(CheckGroup::Compile) {
+            compile::check(reporter, &mut failures, &environment, &sysroot, &targets)?;
+        }
+    }
+    failures.finish()?;
 
     reporter.success("Ferrocene self-check completed!");
     Ok(())
 }
 
 fn main() {
-    let reporter = if atty::is(atty::Stream::Stderr) {
-        StderrReporter::color()
-    } else {
-        StderrReporter::plain()
+    let sysroot = get_sysroot();
+    let options = CliOptions::parse(std::env::args_os().skip(1));
+
+    let reporter: Box<dyn Reporter> = match &options {
+        Ok(CliOptions { format: ReportFormat::Json, .. }) => {
+            Box::new(JsonReporter::new(std::io::stdout(), sysroot.clone()))
+        }
+        _ if atty::is(atty::Stream::Stderr) => Box::new(StderrReporter::color()),
+        _ => Box::new(StderrReporter::plain()),
     };
 
-    match main_inner(&reporter) {
+    match options.and_then(|options| main_inner(&*reporter, options, sysroot)) {
         Ok(()) => {}
         Err(err) => {
             reporter.error(&err);
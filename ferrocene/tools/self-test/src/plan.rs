@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use crate::binaries::BINARIES;
+use crate::cli::{CheckGroup, CliOptions};
+use crate::compile::SAMPLE_PROGRAMS;
+use crate::error::Error;
+use crate::files;
+use crate::targets::{self, TargetSpec};
+use crate::utils::DisplayList;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+
+/// Check that would be executed by the current invocation, as shown by `--list`.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct PlannedCheck {
+    pub(crate) group: CheckGroup,
+    pub(crate) name: String,
+    pub(crate) targets: Vec<&'static str>,
+}
+
+impl Display for PlannedCheck {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.group, self.name)?;
+        if !self.targets.is_empty() {
+            write!(f, " (for target {})", DisplayList(&self.targets))?;
+        }
+        Ok(())
+    }
+}
+
+/// Return the checks that would be executed with the provided options, without executing them.
+///
+/// Targets missing from the sysroot are skipped when no target was explicitly requested, as the
+/// checks would do the same. Requested targets are always listed, as checking them would fail.
+pub(crate) fn plan(
+    options: &CliOptions,
+    sysroot: &Path,
+    configured_targets: &'static [TargetSpec],
+) -> Result<Vec<PlannedCheck>, Error> {
+    let mut planned = Vec::new();
+
+    if options.needs(CheckGroup::Files) {
+        for manifest in files::find_manifests(sysroot)? {
+            planned.push(PlannedCheck {
+                group: CheckGroup::Files,
+                name: manifest.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+                targets: Vec::new(),
+            });
+        }
+    }
+
+    if options.needs(CheckGroup::Binaries) {
+        for binary in BINARIES {
+            planned.push(PlannedCheck {
+                group: CheckGroup::Binaries,
+                name: binary.name.into(),
+                targets: Vec::new(),
+            });
+        }
+    }
+
+    if !options.needs(CheckGroup::Targets) {
+        return Ok(planned);
+    }
+    let targets = targets::candidates(configured_targets, &options.targets)?
+        .into_iter()
+        .filter(|target| !options.targets.is_empty() || targets::is_installed(sysroot, target))
+        .collect::<Vec<_>>();
+
+    for target in &targets {
+        planned.push(PlannedCheck {
+            group: CheckGroup::Targets,
+            name: "standard library".into(),
+            targets: vec![target.triple],
+        });
+    }
+
+    if options.needs(CheckGroup::Linkers) {
+        let mut linkers = BTreeMap::new();
+        for target in &targets {
+            linkers.entry(target.linker).or_insert_with(Vec::new).push(target.triple);
+        }
+        for (linker, triples) in linkers {
+            planned.push(PlannedCheck {
+                group: CheckGroup::Linkers,
+                name: linker.name(),
+                targets: triples,
+            });
+        }
+    }
+
+    if options.needs(CheckGroup::Compile) {
+        for target in &targets {
+            for program in SAMPLE_PROGRAMS {
+                planned.push(PlannedCheck {
+                    group: CheckGroup::Compile,
+                    name: program.name.into(),
+                    targets: vec![target.triple],
+                });
+            }
+        }
+    }
+
+    Ok(planned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestUtils;
+
+    fn names(planned: &[PlannedCheck]) -> Vec<String> {
+        planned.iter().map(|check| check.to_string()).collect()
+    }
+
+    #[test]
+    fn test_plan_everything() {
+        let utils = TestUtils::new();
+        utils.target("aarch64-unknown-none").create();
+        let manifests = utils.sysroot().join("share/criticaltrust/ferrocene");
+        std::fs::create_dir_all(&manifests).unwrap();
+        std::fs::write(manifests.join("rustc.json"), b"{}").unwrap();
+
+        let planned = plan(&CliOptions::default(), utils.sysroot(), &[]).unwrap();
+        assert_eq!(
+            vec![
+                "files: rustc",
+                "binaries: rustc",
+                "binaries: rustdoc",
+                "binaries: cargo",
+                "targets: standard library (for target aarch64-unknown-none)",
+                "linkers: bundled rust-lld (for target aarch64-unknown-none)",
+                "compile: addition.rs (for target aarch64-unknown-none)",
+                "compile: subtraction.rs (for target aarch64-unknown-none)",
+                "compile: subtraction-sys.rs (for target aarch64-unknown-none)",
+                "compile: assertion.rs (for target aarch64-unknown-none)",
+            ],
+            names(&planned)
+        );
+    }
+
+    #[test]
+    fn test_plan_selection() {
+        let utils = TestUtils::new();
+        utils.target("aarch64-unknown-none").create();
+        utils.target("x86_64-unknown-linux-gnu").create();
+
+        let options = CliOptions {
+            only: Some([CheckGroup::Linkers].into()),
+            targets: vec!["aarch64-unknown-linux-gnu".into(), "aarch64-unknown-none".into()],
+            ..CliOptions::default()
+        };
+        let planned = plan(&options, utils.sysroot(), &[]).unwrap();
+        assert_eq!(
+            vec![
+                "targets: standard library (for target aarch64-unknown-linux-gnu)",
+                "targets: standard library (for target aarch64-unknown-none)",
+                "linkers: bundled rust-lld (for target aarch64-unknown-none)",
+                "linkers: aarch64-linux-gnu-gcc (for target aarch64-unknown-linux-gnu)",
+            ],
+            names(&planned)
+        );
+    }
+
+    #[test]
+    fn test_plan_unknown_target() {
+        let utils = TestUtils::new();
+        let options =
+            CliOptions { targets: vec!["mips-unknown-none".into()], ..CliOptions::default() };
+
+        match plan(&options, utils.sysroot(), &[]) {
+            Err(Error::UnknownTarget { target }) => assert_eq!("mips-unknown-none", target),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
@@ -1,12 +1,21 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
-use crate::error::{CommandError, CommandErrorKind, Error};
+use crate::error::{lossy_path, CommandError, CommandErrorKind, Error};
+use crate::plan::PlannedCheck;
+use crate::utils::DisplayList;
+use serde::Serialize;
+use std::cell::RefCell;
 use std::error::Error as _;
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 const TERMINAL_WIDTH: usize = 79;
 
 pub(crate) trait Reporter {
+    fn passed(&self, check: &Check<'_>);
+    fn planned(&self, check: &PlannedCheck);
     fn success(&self, message: &str);
     fn skipped(&self, message: &str);
     fn note(&self, message: &str);
@@ -67,6 +76,14 @@ fn render_command_output(&self, heading: &str, content: &[u8]) {
 }
 
 impl Reporter for StderrReporter {
+    fn passed(&self, check: &Check<'_>) {
+        self.success(&check.to_string());
+    }
+
+    fn planned(&self, check: &PlannedCheck) {
+        eprintln!("{} Planned:{} {check}", self.color_bold_cyan, self.color_reset);
+    }
+
     fn success(&self, message: &str) {
         eprintln!("{} Success:{} {message}", self.color_bold_green, self.color_reset);
     }
@@ -118,3 +135,393 @@ fn error(&self, error: &Error) {
         }
     }
 }
+
+/// Outcome of a successful check, carrying the data needed by both the human-readable and the
+/// machine-readable reporters.
+#[derive(Debug, Serialize)]
+#[serde(tag = "check", rename_all = "kebab-case")]
+pub(crate) enum Check<'a> {
+    Binary {
+        name: &'a str,
+        #[serde(serialize_with = "lossy_path")]
+        path: &'a Path,
+        release: &'a str,
+        host: &'a str,
+        commit_hash: &'a str,
+    },
+    Target {
+        triple: &'a str,
+    },
+    BundledLinker {
+        targets: &'a [String],
+        #[serde(serialize_with = "lossy_path")]
+        path: &'a Path,
+    },
+    Linker {
+        targets: &'a [String],
+        name: &'a str,
+        #[serde(serialize_with = "lossy_path")]
+        path: &'a Path,
+        version: &'a str,
+        bare_metal: bool,
+    },
+    SampleProgram {
+        name: &'a str,
+        target: &'a str,
+    },
+    SampleProgramExecution {
+        name: &'a str,
+        target: &'a str,
+        runner: Option<&'a str>,
+    },
+    PackageFiles {
+        package: &'a str,
+        commit: &'a str,
+        files: usize,
+    },
+}
+
+impl Display for Check<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Check::Binary { name, .. } => write!(f, "binary {name} is valid"),
+            Check::Target { triple } => write!(f, "target installed correctly: {triple}"),
+            Check::BundledLinker { targets, .. } => {
+                write!(f, "bundled linker detected, for target {}", DisplayList(targets))
+            }
+            Check::Linker { targets, name, version, bare_metal, .. } => {
+                let suffix = if *bare_metal { " (bare metal)" } else { "" };
+                write!(
+                    f,
+                    "linker {name} {version}{suffix} detected, for target {}",
+                    DisplayList(targets)
+                )
+            }
+            Check::SampleProgram { name, target } => {
+                write!(f, "compiled sample program `{name}` for target {target}")
+            }
+            Check::SampleProgramExecution { name, target, runner: None } => {
+                write!(f, "executed sample program `{name}` for target {target}")
+            }
+            Check::SampleProgramExecution { name, target, runner: Some(runner) } => {
+                write!(f, "executed sample program `{name}` for target {target} with {runner}")
+            }
+            Check::PackageFiles { package, commit, files } => write!(
+                f,
+                "installed files of package {package} (commit {commit}) \
+                 match the signed manifest ({files} files)"
+            ),
+        }
+    }
+}
+
+/// Reporter emitting one JSON object per line, meant to be archived or consumed by other tools
+/// rather than read by humans.
+pub(crate) struct JsonReporter<W: Write> {
+    writer: RefCell<W>,
+    sysroot: Option<PathBuf>,
+}
+
+impl<W: Write> JsonReporter<W> {
+    pub(crate) fn new(writer: W, sysroot: Option<PathBuf>) -> Self {
+        Self { writer: RefCell::new(writer), sysroot }
+    }
+
+    fn emit(&self, record: Record<'_>) {
+        let line = JsonLine { sysroot: self.sysroot.as_deref(), record };
+
+        let mut writer = self.writer.borrow_mut();
+        // There is no sensible way to report a failure to write the report itself, as the report
+        // is the only channel we have to communicate with the user.
+        let _ = serde_json::to_writer(&mut *writer, &line);
+        let _ = writer.write_all(b"\n");
+        let _ = writer.flush();
+    }
+}
+
+impl<W: Write> Reporter for JsonReporter<W> {
+    fn passed(&self, check: &Check<'_>) {
+        self.emit(Record::Check { message: check.to_string(), check });
+    }
+
+    fn planned(&self, check: &PlannedCheck) {
+        self.emit(Record::Planned { message: check.to_string(), check });
+    }
+
+    fn success(&self, message: &str) {
+        self.emit(Record::Success { message });
+    }
+
+    fn skipped(&self, message: &str) {
+        self.emit(Record::Skipped { message });
+    }
+
+    fn note(&self, message: &str) {
+        self.emit(Record::Note { message });
+    }
+
+    fn info(&self, message: &str) {
+        self.emit(Record::Info { message });
+    }
+
+    fn error(&self, error: &Error) {
+        let mut causes = Vec::new();
+        let mut source = error.source();
+        while let Some(s) = source {
+            causes.push(s.to_string());
+            source = s.source();
+        }
+
+        self.emit(Record::Error {
+            code: format!("FST_{:0>3}", error.code()),
+            exit_code: error.code(),
+            message: error.to_string(),
+            causes,
+            error,
+        });
+    }
+}
+
+#[derive(Serialize)]
+struct JsonLine<'a> {
+    #[serde(serialize_with = "serialize_sysroot")]
+    sysroot: Option<&'a Path>,
+    #[serde(flatten)]
+    record: Record<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+enum Record<'a> {
+    Check {
+        message: String,
+        #[serde(flatten)]
+        check: &'a Check<'a>,
+    },
+    Planned {
+        message: String,
+        #[serde(flatten)]
+        check: &'a PlannedCheck,
+    },
+    Success {
+        message: &'a str,
+    },
+    Skipped {
+        message: &'a str,
+    },
+    Note {
+        message: &'a str,
+    },
+    Info {
+        message: &'a str,
+    },
+    Error {
+        code: String,
+        exit_code: u8,
+        message: String,
+        causes: Vec<String>,
+        error: &'a Error,
+    },
+}
+
+fn serialize_sysroot<S>(sysroot: &Option<&Path>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match sysroot {
+        Some(path) => lossy_path(path, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Decides what happens when a check fails: by default the first failure aborts the whole run,
+/// while with `--keep-going` failures are reported as they happen and the run continues.
+pub(crate) struct Failures<'a> {
+    reporter: &'a dyn Reporter,
+    keep_going: bool,
+    count: usize,
+    codes: Vec<String>,
+}
+
+impl<'a> Failures<'a> {
+    pub(crate) fn new(reporter: &'a dyn Reporter, keep_going: bool) -> Self {
+        Self { reporter, keep_going, count: 0, codes: Vec::new() }
+    }
+
+    /// Return `Ok(None)` if the check failed but execution should continue with the next check.
+    pub(crate) fn collect<T>(&mut self, result: Result<T, Error>) -> Result<Option<T>, Error> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if self.keep_going => {
+                self.reporter.error(&err);
+                let code = format!("FST_{:0>3}", err.code());
+                if !self.codes.contains(&code) {
+                    self.codes.push(code);
+                }
+                self.count += 1;
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Result<(), Error> {
+        if self.count == 0 {
+            Ok(())
+        } else {
+            Err(Error::ChecksFailed { count: self.count, codes: self.codes })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn records(reporter: JsonReporter<Vec<u8>>) -> Vec<Value> {
+        String::from_utf8(reporter.writer.into_inner())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_reporter_checks() {
+        let reporter = JsonReporter::new(Vec::new(), Some("/opt/ferrocene".into()));
+        reporter.info("using sysroot /opt/ferrocene");
+        reporter.passed(&Check::Binary {
+            name: "rustc",
+            path: Path::new("/opt/ferrocene/bin/rustc"),
+            release: "1.0.0",
+            host: "x86_64-unknown-linux-gnu",
+            commit_hash: "unknown",
+        });
+        reporter.passed(&Check::Linker {
+            targets: &["x86_64-unknown-linux-gnu".into()],
+            name: "x86_64-linux-gnu-gcc",
+            path: Path::new("/usr/bin/x86_64-linux-gnu-gcc"),
+            version: "7.5.0",
+            bare_metal: false,
+        });
+        reporter
+            .passed(&Check::SampleProgram { name: "addition.rs", target: "aarch64-unknown-none" });
+        reporter.success("Ferrocene self-check completed!");
+
+        assert_eq!(
+            vec![
+                json!({
+                    "sysroot": "/opt/ferrocene",
+                    "event": "info",
+                    "message": "using sysroot /opt/ferrocene",
+                }),
+                json!({
+                    "sysroot": "/opt/ferrocene",
+                    "event": "check",
+                    "check": "binary",
+                    "message": "binary rustc is valid",
+                    "name": "rustc",
+                    "path": "/opt/ferrocene/bin/rustc",
+                    "release": "1.0.0",
+                    "host": "x86_64-unknown-linux-gnu",
+                    "commit_hash": "unknown",
+                }),
+                json!({
+                    "sysroot": "/opt/ferrocene",
+                    "event": "check",
+                    "check": "linker",
+                    "message": "linker x86_64-linux-gnu-gcc 7.5.0 detected, \
+                                for target x86_64-unknown-linux-gnu",
+                    "targets": ["x86_64-unknown-linux-gnu"],
+                    "name": "x86_64-linux-gnu-gcc",
+                    "path": "/usr/bin/x86_64-linux-gnu-gcc",
+                    "version": "7.5.0",
+                    "bare_metal": false,
+                }),
+                json!({
+                    "sysroot": "/opt/ferrocene",
+                    "event": "check",
+                    "check": "sample-program",
+                    "message": "compiled sample program `addition.rs` for target aarch64-unknown-none",
+                    "name": "addition.rs",
+                    "target": "aarch64-unknown-none",
+                }),
+                json!({
+                    "sysroot": "/opt/ferrocene",
+                    "event": "success",
+                    "message": "Ferrocene self-check completed!",
+                }),
+            ],
+            records(reporter)
+        );
+    }
+
+    #[test]
+    fn test_json_reporter_error() {
+        let reporter = JsonReporter::new(Vec::new(), None);
+        reporter.error(&Error::TargetLibraryDiscoveryFailed {
+            path: "/opt/ferrocene/lib".into(),
+            error: std::io::Error::new(std::io::ErrorKind::NotFound, "not found"),
+        });
+
+        assert_eq!(
+            vec![json!({
+                "sysroot": null,
+                "event": "error",
+                "code": "FST_010",
+                "exit_code": 10,
+                "message": "failed to access /opt/ferrocene/lib while discovering target libraries",
+                "causes": ["not found"],
+                "error": {
+                    "variant": "TargetLibraryDiscoveryFailed",
+                    "path": "/opt/ferrocene/lib",
+                    "error": "not found",
+                },
+            })],
+            records(reporter)
+        );
+    }
+
+    #[test]
+    fn test_failures_abort_by_default() {
+        let reporter = JsonReporter::new(Vec::new(), None);
+        let mut failures = Failures::new(&reporter, false);
+
+        assert_eq!(Some(42), failures.collect(Ok(42)).unwrap());
+        match failures.collect::<()>(Err(Error::NoSysroot)) {
+            Err(Error::NoSysroot) => {}
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(failures.finish().is_ok());
+        assert!(records(reporter).is_empty());
+    }
+
+    #[test]
+    fn test_failures_keep_going() {
+        let reporter = JsonReporter::new(Vec::new(), None);
+        let mut failures = Failures::new(&reporter, true);
+
+        assert_eq!(None, failures.collect::<()>(Err(Error::NoSysroot)).unwrap());
+        assert_eq!(Some(()), failures.collect(Ok(())).unwrap());
+        assert_eq!(
+            None,
+            failures
+                .collect::<()>(Err(Error::TargetNotInstalled {
+                    target: "x86_64-unknown-none".into()
+                }))
+                .unwrap()
+        );
+        assert_eq!(None, failures.collect::<()>(Err(Error::NoSysroot)).unwrap());
+        match failures.finish() {
+            Err(err @ Error::ChecksFailed { .. }) => {
+                assert_eq!(31, err.code());
+                assert_eq!("3 checks failed (error codes FST_001 and FST_033)", err.to_string());
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        let codes = records(reporter).into_iter().map(|r| r["code"].clone()).collect::<Vec<_>>();
+        assert_eq!(vec![json!("FST_001"), json!("FST_033"), json!("FST_001")], codes);
+    }
+}
@@ -3,10 +3,10 @@
 
 use crate::error::Error;
 use crate::linkers::{GccMode, Linker};
-use crate::report::Reporter;
+use crate::report::{Check, Failures, Reporter};
 use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 static SUPPORTED_TARGETS: &[TargetSpec] = &[
     TargetSpec {
@@ -43,23 +43,69 @@ fn deref(&self) -> &Self::Target {
     }
 }
 
-pub(crate) fn check(reporter: &dyn Reporter, sysroot: &Path) -> Result<Vec<Target>, Error> {
+pub(crate) fn check(
+    reporter: &dyn Reporter,
+    failures: &mut Failures<'_>,
+    sysroot: &Path,
+    configured_targets: &'static [TargetSpec],
+    requested: &[String],
+) -> Result<Vec<Target>, Error> {
     let mut found = Vec::new();
-    for target in SUPPORTED_TARGETS {
-        match check_target(reporter, sysroot, target)? {
-            CheckTargetOutcome::Missing => {}
-            CheckTargetOutcome::Found => found.push(Target { spec: target, rustflags: Vec::new() }),
+    for target in candidates(configured_targets, requested)? {
+        match failures.collect(check_target(reporter, sysroot, target))? {
+            Some(CheckTargetOutcome::Found) => {
+                found.push(Target { spec: target, rustflags: Vec::new() })
+            }
+            Some(CheckTargetOutcome::Missing) if requested.is_empty() => {}
+            Some(CheckTargetOutcome::Missing) => {
+                failures.collect::<()>(Err(Error::TargetNotInstalled {
+                    target: target.triple.into(),
+                }))?;
+            }
+            None => {}
         }
     }
     Ok(found)
 }
 
+/// Return the targets that could be checked, restricted to the `requested` ones if any were
+/// passed on the command line.
+pub(crate) fn candidates(
+    configured_targets: &'static [TargetSpec],
+    requested: &[String],
+) -> Result<Vec<&'static TargetSpec>, Error> {
+    // Targets declared in the configuration file replace the built-in ones with the same triple.
+    let builtin_targets = SUPPORTED_TARGETS
+        .iter()
+        .filter(|builtin| !configured_targets.iter().any(|c| c.triple == builtin.triple));
+    let all = builtin_targets.chain(configured_targets).collect::<Vec<_>>();
+
+    if requested.is_empty() {
+        return Ok(all);
+    }
+    for triple in requested {
+        if !all.iter().any(|target| target.triple == triple) {
+            return Err(Error::UnknownTarget { target: triple.clone() });
+        }
+    }
+    Ok(all.into_iter().filter(|target| requested.iter().any(|r| r == target.triple)).collect())
+}
+
+/// Whether the standard library for the target is installed in the sysroot.
+pub(crate) fn is_installed(sysroot: &Path, target: &TargetSpec) -> bool {
+    target_dir(sysroot, target).is_dir()
+}
+
+fn target_dir(sysroot: &Path, target: &TargetSpec) -> PathBuf {
+    sysroot.join("lib").join("rustlib").join(target.triple)
+}
+
 fn check_target(
     reporter: &dyn Reporter,
     sysroot: &Path,
     target: &TargetSpec,
 ) -> Result<CheckTargetOutcome, Error> {
-    let target_dir = sysroot.join("lib").join("rustlib").join(&target.triple);
+    let target_dir = target_dir(sysroot, target);
     if !target_dir.is_dir() {
         // Target not present, ignore it.
         return Ok(CheckTargetOutcome::Missing);
@@ -75,7 +121,7 @@ fn check_target(
         },
     )?;
 
-    reporter.success(&format!("target installed correctly: {}", target.triple));
+    reporter.passed(&Check::Target { triple: target.triple });
     Ok(CheckTargetOutcome::Found)
 }
 
@@ -248,6 +294,94 @@ fn test_check_target_duplicate_other_library() {
         utils.assert_no_reports();
     }
 
+    #[test]
+    fn test_check_configured_targets() {
+        static CONFIGURED: &[TargetSpec] = &[
+            TargetSpec { triple: "x86_64-unknown-linux-gnu", std: true, linker: BundledLld },
+            TargetSpec { triple: "thumbv7em-none-eabihf", std: false, linker: BundledLld },
+        ];
+
+        let utils = TestUtils::new();
+        for target in ["x86_64-unknown-linux-gnu", "thumbv7em-none-eabihf"] {
+            utils
+                .target(target)
+                .lib("core", "0123456789abcdef")
+                .lib("alloc", "0123456789abcdef")
+                .lib("std", "0123456789abcdef")
+                .lib("test", "0123456789abcdef")
+                .lib("proc_macro", "0123456789abcdef")
+                .create();
+        }
+
+        let mut failures = utils.failures(false);
+        let found =
+            check(utils.reporter(), &mut failures, utils.sysroot(), CONFIGURED, &[]).unwrap();
+        assert_eq!(2, found.len());
+        // The configured x86_64-unknown-linux-gnu replaces the built-in one.
+        assert!(std::ptr::eq(&CONFIGURED[0], found[0].spec));
+        assert!(std::ptr::eq(&CONFIGURED[1], found[1].spec));
+
+        utils.assert_report_success("target installed correctly: thumbv7em-none-eabihf");
+        utils.assert_report_success("target installed correctly: x86_64-unknown-linux-gnu");
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_check_requested_targets() {
+        let utils = TestUtils::new();
+        utils
+            .target("aarch64-unknown-none")
+            .lib("core", "0123456789abcdef")
+            .lib("alloc", "0123456789abcdef")
+            .create();
+        utils
+            .target("x86_64-unknown-linux-gnu")
+            .lib("core", "0123456789abcdef")
+            .lib("alloc", "0123456789abcdef")
+            .lib("std", "0123456789abcdef")
+            .lib("test", "0123456789abcdef")
+            .lib("proc_macro", "0123456789abcdef")
+            .create();
+
+        let mut failures = utils.failures(false);
+        let requested = ["aarch64-unknown-none".to_string()];
+        let found =
+            check(utils.reporter(), &mut failures, utils.sysroot(), &[], &requested).unwrap();
+        assert_eq!(1, found.len());
+        assert_eq!("aarch64-unknown-none", found[0].triple);
+        utils.assert_report_success("target installed correctly: aarch64-unknown-none");
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_check_requested_target_not_installed() {
+        let utils = TestUtils::new();
+        let requested = ["aarch64-unknown-none".to_string()];
+
+        let mut failures = utils.failures(false);
+        match check(utils.reporter(), &mut failures, utils.sysroot(), &[], &requested) {
+            Err(Error::TargetNotInstalled { target }) => assert_eq!("aarch64-unknown-none", target),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        utils.assert_no_reports();
+
+        let mut failures = utils.failures(true);
+        let found =
+            check(utils.reporter(), &mut failures, utils.sysroot(), &[], &requested).unwrap();
+        assert!(found.is_empty());
+        assert!(matches!(failures.finish(), Err(Error::ChecksFailed { .. })));
+        utils.assert_report_error();
+        utils.assert_no_reports();
+    }
+
+    #[test]
+    fn test_candidates_unknown_target() {
+        match candidates(&[], &["aarch64-unknown-none".into(), "mips-unknown-none".into()]) {
+            Err(Error::UnknownTarget { target }) => assert_eq!("mips-unknown-none", target),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
     #[test]
     fn test_find_libraries_in() {
         let dir = tempfile::tempdir().unwrap();
@@ -2,9 +2,11 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 use crate::error::Error;
-use crate::report::Reporter;
+use crate::plan::PlannedCheck;
+use crate::report::{Check, Failures, Reporter};
 use crate::Environment;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::io::Write;
 use std::os::unix::prelude::PermissionsExt;
 use std::path::{Path, PathBuf};
@@ -32,6 +34,7 @@ pub(crate) fn new() -> Self {
                     ])
                     .unwrap(),
                 ),
+                runners: HashMap::new(),
             },
             sysroot,
             external_binaries_dir,
@@ -65,6 +68,10 @@ pub(crate) fn reporter(&self) -> &dyn Reporter {
         &self.reports
     }
 
+    pub(crate) fn failures(&self, keep_going: bool) -> Failures<'_> {
+        Failures::new(&self.reports, keep_going)
+    }
+
     pub(crate) fn env(&self) -> &Environment {
         &self.environment
     }
@@ -85,6 +92,14 @@ pub(crate) fn assert_report_skipped(&self, message: &str) {
         );
     }
 
+    #[track_caller]
+    pub(crate) fn assert_report_error(&self) {
+        assert_eq!(
+            Report::Error,
+            self.reports.reports.borrow_mut().pop().expect("no reports left")
+        );
+    }
+
     #[track_caller]
     pub(crate) fn assert_no_reports(&self) {
         assert!(self.reports.reports.borrow_mut().is_empty());
@@ -294,6 +309,14 @@ struct ReportsCollector {
 }
 
 impl Reporter for ReportsCollector {
+    fn passed(&self, check: &Check<'_>) {
+        self.reports.borrow_mut().push(Report::Success(check.to_string()));
+    }
+
+    fn planned(&self, check: &PlannedCheck) {
+        self.reports.borrow_mut().push(Report::Planned(check.to_string()));
+    }
+
     fn success(&self, message: &str) {
         self.reports.borrow_mut().push(Report::Success(message.into()));
     }
@@ -318,6 +341,7 @@ fn error(&self, _: &Error) {
 #[derive(Debug, PartialEq, Eq)]
 enum Report {
     Success(String),
+    Planned(String),
     Skipped(String),
     Note(String),
     Info(String),
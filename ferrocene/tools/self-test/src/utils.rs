@@ -86,6 +86,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
     use std::path::Path;
 
     #[test]
@@ -170,7 +171,7 @@ fn test_find_binary_in_path_two_paths_two_binaries() {
     }
 
     fn path_env(paths: &[&Path]) -> Environment {
-        Environment { path: Some(std::env::join_paths(paths).unwrap()) }
+        Environment { path: Some(std::env::join_paths(paths).unwrap()), runners: HashMap::new() }
     }
 
     #[test]
@@ -3,10 +3,11 @@
 
 use crate::test_outcomes::TestOutcomes;
 use anyhow::Error;
+use serde::Serialize;
 use std::collections::{BTreeMap, BTreeSet};
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub(crate) struct AnnotatedFile {
     pub(crate) test: PathBuf,
     pub(crate) source: AnnotationSource,
@@ -25,14 +26,45 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize)]
 pub(crate) struct Targets {
-    pub(crate) executed: DisplayCommaSeparatedSet,
+    pub(crate) passed: DisplayCommaSeparatedSet,
+    pub(crate) failed: DisplayCommaSeparatedSet,
     pub(crate) ignored: DisplayCommaSeparatedSet,
+    pub(crate) excluded: DisplayCommaSeparatedSet,
+    pub(crate) not_run: DisplayCommaSeparatedSet,
+}
+
+impl Targets {
+    /// Targets on which the test was not executed, for any reason.
+    pub(crate) fn untested(&self) -> impl Iterator<Item = &String> {
+        self.ignored.0.iter().chain(self.excluded.0.iter()).chain(self.not_run.0.iter())
+    }
+
+    /// Describe the outcomes of the test, or return an empty string if the test passed on all the
+    /// targets it was supposed to run on.
+    pub(crate) fn details(&self) -> String {
+        if self.failed.0.is_empty() && self.untested().next().is_none() {
+            return String::new();
+        }
+        [
+            ("passed on", &self.passed),
+            ("failed on", &self.failed),
+            ("ignored on", &self.ignored),
+            ("excluded on", &self.excluded),
+            ("not run on", &self.not_run),
+        ]
+        .into_iter()
+        .filter(|(_, targets)| !targets.0.is_empty())
+        .map(|(outcome, targets)| format!("{outcome}: {targets}"))
+        .collect::<Vec<_>>()
+        .join("; ")
+    }
 }
 
 // created only so as to impl Display for Targets fields
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize)]
+#[serde(transparent)]
 pub(crate) struct DisplayCommaSeparatedSet(pub BTreeSet<String>);
 
 impl std::fmt::Display for DisplayCommaSeparatedSet {
@@ -42,7 +74,8 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
 pub(crate) enum AnnotationSource {
     TestItself,
     ParentDirectory { bulk_file: PathBuf },
@@ -54,6 +87,7 @@ pub(crate) struct Annotations {
     pub(crate) ids: BTreeMap<String, BTreeSet<AnnotatedFile>>,
     pub(crate) ignored_tests: BTreeMap<String, BTreeSet<String>>,
     pub(crate) considers_ignored_tests: bool,
+    pub(crate) targets: BTreeSet<String>,
 }
 
 impl Annotations {
@@ -62,6 +96,7 @@ pub(crate) fn new() -> Self {
             ids: BTreeMap::new(),
             ignored_tests: BTreeMap::new(),
             considers_ignored_tests: true,
+            targets: BTreeSet::new(),
         }
     }
 
@@ -131,18 +166,24 @@ struct JsonAnnotation {
                 .to_string();
             let tmp_binding;
             let outcomes = if let Some(outcomes) = test_outcomes {
-                // Do not consider annotations from ignored tests.
-                if !outcomes.executed_tests.contains_key(&relative_file) {
+                // Do not consider annotations from tests that were never executed.
+                if !outcomes.passed_tests.contains_key(&relative_file)
+                    && !outcomes.failed_tests.contains_key(&relative_file)
+                {
                     if file.annotations.is_empty() {
                         unreachable!("source files should already be annotated")
                     }
-                    if let Some(tests) = outcomes.ignored_tests.get(&relative_file) {
-                        self.ignored_tests.insert(relative_file.clone(), tests.clone());
+                    let ignored = outcomes.ignored_tests.get(&relative_file);
+                    let excluded = outcomes.excluded_targets(&relative_file);
+                    if ignored.is_some() || !excluded.is_empty() {
+                        let targets = ignored.into_iter().flatten().cloned().chain(excluded);
+                        self.ignored_tests.entry(relative_file).or_default().extend(targets);
                     } else {
                         unknown_tests.insert(relative_file);
                     }
                     continue;
                 }
+                self.targets.extend(outcomes.targets.iter().cloned());
                 outcomes
             } else {
                 tmp_binding = TestOutcomes::default();
@@ -165,21 +206,31 @@ struct JsonAnnotation {
                     );
                 };
 
-                let ignored = DisplayCommaSeparatedSet(
-                    outcomes
-                        .ignored_tests
-                        .get(&relative_file)
-                        .unwrap_or(&BTreeSet::new())
-                        .to_owned(),
-                );
-                let executed = DisplayCommaSeparatedSet(
-                    outcomes
-                        .executed_tests
-                        .get(&relative_file)
-                        .unwrap_or(&BTreeSet::new())
-                        .to_owned(),
-                );
-                let targets = Targets { executed, ignored };
+                let targets_of = |tests: &BTreeMap<String, BTreeSet<String>>| {
+                    tests.get(&relative_file).cloned().unwrap_or_default()
+                };
+                let passed = targets_of(&outcomes.passed_tests);
+                let failed = targets_of(&outcomes.failed_tests);
+                let ignored = targets_of(&outcomes.ignored_tests);
+                let excluded = outcomes.excluded_targets(&relative_file);
+                let not_run = outcomes
+                    .targets
+                    .iter()
+                    .filter(|t| {
+                        !passed.contains(*t)
+                            && !failed.contains(*t)
+                            && !ignored.contains(*t)
+                            && !excluded.contains(*t)
+                    })
+                    .cloned()
+                    .collect();
+                let targets = Targets {
+                    passed: DisplayCommaSeparatedSet(passed),
+                    failed: DisplayCommaSeparatedSet(failed),
+                    ignored: DisplayCommaSeparatedSet(ignored),
+                    excluded: DisplayCommaSeparatedSet(excluded),
+                    not_run: DisplayCommaSeparatedSet(not_run),
+                };
                 let annotated_file =
                     AnnotatedFile { test: shrink_path(&file.file), source, targets };
                 self.ids.entry(annotation.id.clone()).or_default().insert(annotated_file);
@@ -202,6 +253,7 @@ struct JsonAnnotation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_outcomes::ExcludedTests;
     use tempfile::{NamedTempFile, TempDir};
 
     #[test]
@@ -210,7 +262,7 @@ fn test_load_file() -> Result<(), Error> {
         std::fs::write(file.path(), annotations_file_1()?)?;
 
         let outcomes = Some(TestOutcomes {
-            executed_tests: BTreeMap::from([
+            passed_tests: BTreeMap::from([
                 ("example/foo.rs".into(), BTreeSet::default()),
                 ("example/bar.rs".into(), BTreeSet::default()),
                 ("example/foobar.rs".into(), BTreeSet::default()),
@@ -219,6 +271,7 @@ fn test_load_file() -> Result<(), Error> {
                 ("example/ignored.rs".into(), BTreeSet::default()),
                 ("example/ignored-without-annotations.rs".into(), BTreeSet::default()),
             ]),
+            ..TestOutcomes::default()
         });
 
         let mut annotations = Annotations::new();
@@ -262,7 +315,7 @@ fn test_load_directory() -> Result<(), Error> {
         std::fs::write(dir.path().join("sub/quux.json"), annotations_file_3()?)?;
 
         let outcomes = Some(TestOutcomes {
-            executed_tests: BTreeMap::from([
+            passed_tests: BTreeMap::from([
                 ("example/foo.rs".into(), BTreeSet::default()),
                 ("example/bar.rs".into(), BTreeSet::default()),
                 ("example/baz.rs".into(), BTreeSet::default()),
@@ -272,6 +325,7 @@ fn test_load_directory() -> Result<(), Error> {
                 ("example/ignored.rs".into(), BTreeSet::default()),
                 ("example/ignored-without-annotations.rs".into(), BTreeSet::default()),
             ]),
+            ..TestOutcomes::default()
         });
 
         let mut annotations = Annotations::new();
@@ -309,6 +363,54 @@ fn test_load_directory() -> Result<(), Error> {
         Ok(())
     }
 
+    #[test]
+    fn test_load_file_targets() -> Result<(), Error> {
+        let file = NamedTempFile::new()?;
+        std::fs::write(file.path(), annotations_file_1()?)?;
+
+        let targets = |targets: &[&str]| targets.iter().map(|t| t.to_string()).collect();
+        let outcomes = Some(TestOutcomes {
+            targets: targets(&["x", "y", "z"]),
+            passed_tests: BTreeMap::from([
+                ("example/foo.rs".into(), targets(&["x"])),
+                ("example/bar.rs".into(), targets(&["x"])),
+                ("example/foobar.rs".into(), targets(&["x", "y", "z"])),
+            ]),
+            failed_tests: BTreeMap::from([("example/foo.rs".into(), targets(&["y"]))]),
+            ignored_tests: BTreeMap::new(),
+            excluded_tests: vec![
+                ExcludedTests {
+                    tests: vec!["example/foo.rs".into()],
+                    targets: targets(&["z"]),
+                    reason: "Not supported".into(),
+                },
+                ExcludedTests {
+                    tests: vec!["example/ignored.rs".into()],
+                    targets: targets(&["x", "y", "z"]),
+                    reason: "Not supported".into(),
+                },
+            ],
+        });
+
+        let mut annotations = Annotations::new();
+        annotations.load_file(file.path(), Path::new("/base"), outcomes.as_ref())?;
+
+        let details = |id: &str, test: &str| {
+            let files = &annotations.ids[id];
+            files.iter().find(|f| f.test == Path::new(test)).unwrap().targets.details()
+        };
+        assert_eq!("passed on: x; failed on: y; excluded on: z", details("foo", "example/foo.rs"));
+        assert_eq!("passed on: x; not run on: y, z", details("bar", "example/bar.rs"));
+        assert_eq!("", details("foobar", "example/foobar.rs"));
+
+        assert!(!annotations.ids.contains_key("ignored"));
+        let expected = BTreeMap::from([("example/ignored.rs".into(), targets(&["x", "y", "z"]))]);
+        assert_eq!(expected, annotations.ignored_tests);
+        assert_eq!(targets(&["x", "y", "z"]), annotations.targets);
+
+        Ok(())
+    }
+
     fn test_itself(path: impl AsRef<Path>) -> AnnotatedFile {
         AnnotatedFile {
             test: path.as_ref().into(),
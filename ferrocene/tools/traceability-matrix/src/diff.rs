@@ -0,0 +1,394 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Comparison between the traceability matrices of two releases, reviewed as part of each release
+//! to spot regressions in the traceability coverage.
+
+use crate::annotations::Annotations;
+use crate::matrix::{Element, ElementKind, MatrixAnalysis, TraceabilityMatrix};
+use crate::utils::capitalize;
+use anyhow::Error;
+use askama::Template;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+pub(crate) struct MatrixDiff {
+    pub(crate) kinds: Vec<KindDiff>,
+    pub(crate) changed_tests: Vec<ChangedTest>,
+}
+
+impl MatrixDiff {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.changed_tests.is_empty() && self.kinds.iter().all(|kind| kind.is_empty())
+    }
+}
+
+pub(crate) struct KindDiff {
+    pub(crate) kind: &'static ElementKind,
+    pub(crate) old_coverage: Coverage,
+    pub(crate) new_coverage: Coverage,
+    /// Elements linked in the new release, but not in the old one (either because they were not
+    /// linked or because they did not exist). Only fully linked elements count as linked, as in
+    /// the coverage percentages.
+    pub(crate) newly_linked: BTreeSet<Element>,
+    /// Elements not linked in the new release, but linked in the old one. This includes elements
+    /// whose tests now fail or are not run on some targets. Elements added in the new release
+    /// without being linked are also included.
+    pub(crate) newly_unlinked: BTreeSet<Element>,
+    /// Elements of the old release whose id is not present anymore in the new release.
+    pub(crate) removed: BTreeSet<Element>,
+}
+
+impl KindDiff {
+    fn is_empty(&self) -> bool {
+        self.newly_linked.is_empty() && self.newly_unlinked.is_empty() && self.removed.is_empty()
+    }
+
+    /// Return the changed elements grouped by the kind of change, alongside the title and the
+    /// color used to display them.
+    fn changes(&self) -> [(&'static str, &'static str, &BTreeSet<Element>); 3] {
+        [
+            ("Newly unlinked", "red", &self.newly_unlinked),
+            ("Newly linked", "green", &self.newly_linked),
+            ("Removed", "orange", &self.removed),
+        ]
+    }
+
+    fn coverages(&self) -> [&Coverage; 2] {
+        [&self.old_coverage, &self.new_coverage]
+    }
+}
+
+pub(crate) struct Coverage {
+    pub(crate) linked: usize,
+    pub(crate) total: usize,
+}
+
+impl Coverage {
+    fn of(analysis: &MatrixAnalysis) -> Self {
+        Coverage {
+            linked: analysis.linked.len(),
+            total: analysis.linked.len()
+                + analysis.partially_linked.len()
+                + analysis.unlinked.len(),
+        }
+    }
+
+    pub(crate) fn percentage(&self) -> f32 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.linked as f32 * 100.0 / self.total as f32
+        }
+    }
+}
+
+impl std::fmt::Display for Coverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{} linked ({:.2}%)", self.linked, self.total, self.percentage())
+    }
+}
+
+/// Test whose annotations differ between the two releases. Tests that were added or removed are
+/// included too, with all their annotations respectively added or removed.
+pub(crate) struct ChangedTest {
+    pub(crate) test: PathBuf,
+    pub(crate) added: BTreeSet<String>,
+    pub(crate) removed: BTreeSet<String>,
+}
+
+impl ChangedTest {
+    fn columns(&self) -> [&BTreeSet<String>; 2] {
+        [&self.added, &self.removed]
+    }
+}
+
+pub(crate) fn compute(
+    old_annotations: &Annotations,
+    old_matrix: &TraceabilityMatrix,
+    new_annotations: &Annotations,
+    new_matrix: &TraceabilityMatrix,
+) -> MatrixDiff {
+    let kinds = old_matrix
+        .analyses_by_kind()
+        .zip(new_matrix.analyses_by_kind())
+        .map(|(old, new)| compute_kind(old, new))
+        .collect();
+
+    let old_tests = annotations_by_test(old_annotations);
+    let new_tests = annotations_by_test(new_annotations);
+    let empty = BTreeSet::new();
+
+    let mut changed_tests = Vec::new();
+    for test in old_tests.keys().chain(new_tests.keys()).collect::<BTreeSet<_>>() {
+        let old_ids = old_tests.get(test).unwrap_or(&empty);
+        let new_ids = new_tests.get(test).unwrap_or(&empty);
+        if old_ids != new_ids {
+            changed_tests.push(ChangedTest {
+                test: test.clone(),
+                added: new_ids.difference(old_ids).map(|id| id.to_string()).collect(),
+                removed: old_ids.difference(new_ids).map(|id| id.to_string()).collect(),
+            });
+        }
+    }
+
+    MatrixDiff { kinds, changed_tests }
+}
+
+fn compute_kind(old: &MatrixAnalysis, new: &MatrixAnalysis) -> KindDiff {
+    let old_elements = elements_by_id(old);
+    let new_elements = elements_by_id(new);
+
+    let mut diff = KindDiff {
+        kind: new.kind,
+        old_coverage: Coverage::of(old),
+        new_coverage: Coverage::of(new),
+        newly_linked: BTreeSet::new(),
+        newly_unlinked: BTreeSet::new(),
+        removed: BTreeSet::new(),
+    };
+
+    for (id, &(element, linked)) in &new_elements {
+        let was_linked = old_elements.get(id).map(|&(_, linked)| linked);
+        match (was_linked, linked) {
+            (Some(false) | None, true) => diff.newly_linked.insert(element.clone()),
+            (Some(true) | None, false) => diff.newly_unlinked.insert(element.clone()),
+            (Some(true), true) | (Some(false), false) => continue,
+        };
+    }
+    for (id, &(element, _)) in &old_elements {
+        if !new_elements.contains_key(id) {
+            diff.removed.insert(element.clone());
+        }
+    }
+
+    diff
+}
+
+/// Index the elements of an analysis by their id, alongside whether they are linked, using the
+/// same definition as [`Coverage::of`] (partially linked elements are not considered linked).
+fn elements_by_id(analysis: &MatrixAnalysis) -> BTreeMap<&str, (&Element, bool)> {
+    let mut elements = BTreeMap::new();
+    for link in &analysis.linked {
+        elements.insert(link.id.as_str(), (&link.element, true));
+    }
+    for link in &analysis.partially_linked {
+        elements.insert(link.id.as_str(), (&link.element, false));
+    }
+    for element in &analysis.unlinked {
+        elements.insert(element.id.as_str(), (element, false));
+    }
+    elements
+}
+
+fn annotations_by_test(annotations: &Annotations) -> BTreeMap<PathBuf, BTreeSet<&str>> {
+    let mut tests: BTreeMap<PathBuf, BTreeSet<&str>> = BTreeMap::new();
+    for (id, files) in &annotations.ids {
+        for file in files {
+            tests.entry(file.test.clone()).or_default().insert(id);
+        }
+    }
+    tests
+}
+
+pub(crate) fn text(diff: &MatrixDiff) -> String {
+    let mut output = String::new();
+    output.push_str("Traceability matrix changes\n");
+    output.push_str("===========================\n\n");
+
+    for kind in &diff.kinds {
+        writeln!(
+            output,
+            "{}: {} -> {}",
+            capitalize(kind.kind.plural),
+            kind.old_coverage,
+            kind.new_coverage
+        )
+        .unwrap();
+    }
+
+    if diff.is_empty() {
+        output.push_str("\nNo element or test changed.\n");
+        return output;
+    }
+
+    for kind in &diff.kinds {
+        for (title, _, elements) in kind.changes() {
+            if elements.is_empty() {
+                continue;
+            }
+            writeln!(output, "\n{title} {}:", kind.kind.plural).unwrap();
+            for element in elements {
+                writeln!(
+                    output,
+                    "- {}: {} {} ({})",
+                    element.page.documentation,
+                    element.page.name,
+                    element.name(),
+                    element.id
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    if !diff.changed_tests.is_empty() {
+        output.push_str("\nTests with changed annotations:\n");
+        for test in &diff.changed_tests {
+            let changes = test
+                .added
+                .iter()
+                .map(|id| format!("+{id}"))
+                .chain(test.removed.iter().map(|id| format!("-{id}")))
+                .collect::<Vec<_>>();
+            writeln!(output, "- {}: {}", test.test.display(), changes.join(", ")).unwrap();
+        }
+    }
+
+    output
+}
+
+#[derive(Template)]
+#[template(path = "diff.html")]
+struct DiffReport<'a> {
+    diff: &'a MatrixDiff,
+}
+
+pub(crate) fn html(diff: &MatrixDiff) -> Result<String, Error> {
+    Ok(DiffReport { diff }.render()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{release, Release};
+
+    fn compute_releases(old: &Release, new: &Release) -> MatrixDiff {
+        compute(&old.annotations, &old.matrix, &new.annotations, &new.matrix)
+    }
+
+    fn ids(elements: &BTreeSet<Element>) -> Vec<&str> {
+        elements.iter().map(|element| element.id.as_str()).collect()
+    }
+
+    fn example_diff() -> MatrixDiff {
+        let old = release(
+            &["fls_a", "fls_b", "fls_c", "fls_d"],
+            &[("fls_a", "ui/a.rs"), ("fls_b", "ui/b.rs"), ("fls_d", "ui/a.rs")],
+        );
+        let new = release(
+            &["fls_a", "fls_b", "fls_c", "fls_e", "fls_f"],
+            &[("fls_a", "ui/a.rs"), ("fls_c", "ui/b.rs"), ("fls_e", "ui/c.rs")],
+        );
+        compute_releases(&old, &new)
+    }
+
+    #[test]
+    fn test_compute() {
+        let diff = example_diff();
+
+        let paragraphs = &diff.kinds[1];
+        assert_eq!("paragraphs", paragraphs.kind.plural);
+        assert_eq!((3, 4), (paragraphs.old_coverage.linked, paragraphs.old_coverage.total));
+        assert_eq!((3, 5), (paragraphs.new_coverage.linked, paragraphs.new_coverage.total));
+        assert_eq!(vec!["fls_c", "fls_e"], ids(&paragraphs.newly_linked));
+        assert_eq!(vec!["fls_b", "fls_f"], ids(&paragraphs.newly_unlinked));
+        assert_eq!(vec!["fls_d"], ids(&paragraphs.removed));
+
+        let changed = diff
+            .changed_tests
+            .iter()
+            .map(|test| (test.test.to_str().unwrap(), &test.added, &test.removed))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("ui/a.rs", &BTreeSet::new(), &BTreeSet::from(["fls_d".into()])),
+                ("ui/b.rs", &BTreeSet::from(["fls_c".into()]), &BTreeSet::from(["fls_b".into()])),
+                ("ui/c.rs", &BTreeSet::from(["fls_e".into()]), &BTreeSet::new()),
+            ],
+            changed
+        );
+    }
+
+    #[test]
+    fn test_compute_partially_linked() {
+        let old = release(&["fls_a", "fls_b"], &[("fls_a", "ui/a.rs"), ("fls_b", "ui/b.rs")]);
+        let mut new = release(&["fls_a", "fls_b"], &[("fls_a", "ui/a.rs"), ("fls_b", "ui/b.rs")]);
+        // The test of `fls_b` now fails on a target.
+        let paragraphs = &mut new.matrix.paragraphs;
+        let mut link = paragraphs.linked.iter().find(|link| link.id == "fls_b").unwrap().clone();
+        paragraphs.linked.remove(&link);
+        link.failing_targets.insert("x86_64-unknown-linux-gnu".into());
+        paragraphs.partially_linked.insert(link);
+        let diff = compute_releases(&old, &new);
+
+        let paragraphs = &diff.kinds[1];
+        assert_eq!((2, 2), (paragraphs.old_coverage.linked, paragraphs.old_coverage.total));
+        assert_eq!((1, 2), (paragraphs.new_coverage.linked, paragraphs.new_coverage.total));
+        assert!(paragraphs.newly_linked.is_empty());
+        assert_eq!(vec!["fls_b"], ids(&paragraphs.newly_unlinked));
+        assert!(paragraphs.removed.is_empty());
+
+        // Fixing the test links the paragraph again.
+        let diff = compute_releases(&new, &old);
+        assert_eq!(vec!["fls_b"], ids(&diff.kinds[1].newly_linked));
+        assert!(diff.kinds[1].newly_unlinked.is_empty());
+    }
+
+    #[test]
+    fn test_compute_no_changes() {
+        let old = release(&["fls_a", "fls_b"], &[("fls_a", "ui/a.rs")]);
+        let new = release(&["fls_a", "fls_b"], &[("fls_a", "ui/a.rs")]);
+        let diff = compute_releases(&old, &new);
+
+        assert!(diff.is_empty());
+        assert_eq!(
+            "Traceability matrix changes\n\
+             ===========================\n\
+             \n\
+             Sections: 0/1 linked (0.00%) -> 0/1 linked (0.00%)\n\
+             Paragraphs: 1/2 linked (50.00%) -> 1/2 linked (50.00%)\n\
+             Command line options: 0/0 linked (100.00%) -> 0/0 linked (100.00%)\n\
+             \n\
+             No element or test changed.\n",
+            text(&diff)
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(
+            "Traceability matrix changes\n\
+             ===========================\n\
+             \n\
+             Sections: 0/1 linked (0.00%) -> 0/1 linked (0.00%)\n\
+             Paragraphs: 3/4 linked (75.00%) -> 3/5 linked (60.00%)\n\
+             Command line options: 0/0 linked (100.00%) -> 0/0 linked (100.00%)\n\
+             \n\
+             Newly unlinked paragraphs:\n\
+             - FLS: Example document 1:2 (fls_b)\n\
+             - FLS: Example document 1:5 (fls_f)\n\
+             \n\
+             Newly linked paragraphs:\n\
+             - FLS: Example document 1:3 (fls_c)\n\
+             - FLS: Example document 1:4 (fls_e)\n\
+             \n\
+             Removed paragraphs:\n\
+             - FLS: Example document 1:4 (fls_d)\n\
+             \n\
+             Tests with changed annotations:\n\
+             - ui/a.rs: -fls_d\n\
+             - ui/b.rs: +fls_c, -fls_b\n\
+             - ui/c.rs: +fls_e\n",
+            text(&example_diff())
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let html = html(&example_diff()).unwrap();
+        assert!(html.contains(r##"<a href="../fls/example.html#fls_f">"##));
+        assert!(html.contains("<code>ui/b.rs</code>"));
+    }
+}
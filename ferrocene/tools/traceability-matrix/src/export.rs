@@ -0,0 +1,247 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+//! Machine-readable exports of the traceability matrix, meant to be imported in requirement
+//! management tools rather than read by humans.
+
+use crate::matrix::{Element, Link, TraceabilityMatrix};
+use anyhow::Error;
+use std::fmt::Write;
+
+pub(crate) fn json(matrix: &TraceabilityMatrix) -> Result<String, Error> {
+    Ok(serde_json::to_string_pretty(matrix)?)
+}
+
+const CSV_HEADER: &[&str] = &[
+    "kind",
+    "status",
+    "documentation",
+    "page",
+    "number",
+    "title",
+    "id",
+    "link",
+    "tests",
+    "untested targets",
+    "failing targets",
+];
+
+/// Generate a CSV file with one row for each element of the documentations. Lists (like the
+/// tests linked to an element) are joined with newlines, which spreadsheets show within the cell.
+pub(crate) fn csv(matrix: &TraceabilityMatrix) -> String {
+    let mut output = String::new();
+    write_csv_row(&mut output, CSV_HEADER.iter().copied());
+
+    for analysis in matrix.analyses_by_kind() {
+        for (status, links) in
+            [("linked", &analysis.linked), ("partially linked", &analysis.partially_linked)]
+        {
+            for link in links {
+                write_csv_element(&mut output, status, link, Some(link));
+            }
+        }
+        for element in &analysis.unlinked {
+            write_csv_element(&mut output, "unlinked", element, None);
+        }
+    }
+
+    output
+}
+
+fn write_csv_element(output: &mut String, status: &str, element: &Element, link: Option<&Link>) {
+    let number = element.number.as_ref().map(|n| n.to_string()).unwrap_or_default();
+    let (tests, untested_targets, failing_targets) = match link {
+        Some(link) => (
+            link.tests.iter().map(|t| t.to_string()).collect::<Vec<_>>().join("\n"),
+            link.untested_targets.iter().cloned().collect::<Vec<_>>().join("\n"),
+            link.failing_targets.iter().cloned().collect::<Vec<_>>().join("\n"),
+        ),
+        None => (String::new(), String::new(), String::new()),
+    };
+
+    write_csv_row(
+        output,
+        [
+            element.kind.singular,
+            status,
+            &element.page.documentation,
+            &element.page.name,
+            &number,
+            element.title.as_deref().unwrap_or(""),
+            &element.id,
+            &element.link,
+            &tests,
+            &untested_targets,
+            &failing_targets,
+        ],
+    );
+}
+
+fn write_csv_row<'a>(output: &mut String, fields: impl IntoIterator<Item = &'a str>) {
+    for (idx, field) in fields.into_iter().enumerate() {
+        if idx != 0 {
+            output.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(output, "\"{}\"", field.replace('"', "\"\"")).unwrap();
+        } else {
+            output.push_str(field);
+        }
+    }
+    output.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::annotations::{AnnotatedFile, AnnotationSource, Annotations};
+    use crate::documentations::{Document, Documentation, Paragraph, Section, TraceabilityIds};
+    use crate::matrix;
+    use serde_json::json;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn example_matrix() -> TraceabilityMatrix {
+        let documentations = [Documentation {
+            name: "FLS".into(),
+            url: "../fls".into(),
+            ids: TraceabilityIds {
+                documents: vec![Document {
+                    title: "Example document".into(),
+                    link: "example.html".into(),
+                    informational: false,
+                    sections: vec![Section {
+                        id: "fls_01".into(),
+                        number: "12".into(),
+                        title: "Example, with a comma".into(),
+                        link: "example.html#example".into(),
+                        informational: false,
+                        paragraphs: vec![
+                            Paragraph {
+                                id: "fls_02".into(),
+                                number: "12:1".into(),
+                                link: "example.html#fls_02".into(),
+                            },
+                            Paragraph {
+                                id: "fls_03".into(),
+                                number: "12:2".into(),
+                                link: "example.html#fls_03".into(),
+                            },
+                        ],
+                    }],
+                    options: Vec::new(),
+                }],
+            },
+        }];
+
+        let annotated = |path: &str| AnnotatedFile {
+            test: path.into(),
+            source: AnnotationSource::TestItself,
+            targets: Default::default(),
+        };
+        let annotations = Annotations {
+            ids: BTreeMap::from([
+                ("fls_02".into(), BTreeSet::from([annotated("ui/foo.rs"), annotated("ui/bar.rs")])),
+                ("fls_99".into(), BTreeSet::from([annotated("ui/baz.rs")])),
+            ]),
+            ignored_tests: BTreeMap::new(),
+            considers_ignored_tests: true,
+            targets: BTreeSet::new(),
+        };
+
+        matrix::prepare(&documentations, &annotations).unwrap()
+    }
+
+    #[test]
+    fn test_json() {
+        let exported: serde_json::Value =
+            serde_json::from_str(&json(&example_matrix()).unwrap()).unwrap();
+
+        assert_eq!(
+            json!({ "singular": "section", "plural": "sections" }),
+            exported["sections"]["kind"]
+        );
+        assert_eq!(json!([]), exported["sections"]["linked"]);
+        assert_eq!(json!("fls_01"), exported["sections"]["unlinked"][0]["id"]);
+        assert_eq!(
+            json!({
+                "number": "12:1",
+                "page": {
+                    "documentation": "FLS",
+                    "name": "Example document",
+                    "link": "../fls/example.html",
+                },
+                "title": null,
+                "id": "fls_02",
+                "link": "../fls/example.html#fls_02",
+                "tests": [
+                    {
+                        "kind": "file",
+                        "test": "ui/bar.rs",
+                        "source": { "kind": "test-itself" },
+                        "targets": {
+                            "passed": [],
+                            "failed": [],
+                            "ignored": [],
+                            "excluded": [],
+                            "not_run": [],
+                        },
+                    },
+                    {
+                        "kind": "file",
+                        "test": "ui/foo.rs",
+                        "source": { "kind": "test-itself" },
+                        "targets": {
+                            "passed": [],
+                            "failed": [],
+                            "ignored": [],
+                            "excluded": [],
+                            "not_run": [],
+                        },
+                    },
+                ],
+                "untested_targets": [],
+                "failing_targets": [],
+            }),
+            exported["paragraphs"]["linked"][0]
+        );
+        assert_eq!(json!("fls_03"), exported["paragraphs"]["unlinked"][0]["id"]);
+        assert_eq!(
+            json!([{
+                "annotation": "fls_99",
+                "file": {
+                    "test": "ui/baz.rs",
+                    "source": { "kind": "test-itself" },
+                    "targets": {
+                            "passed": [],
+                            "failed": [],
+                            "ignored": [],
+                            "excluded": [],
+                            "not_run": [],
+                        },
+                },
+            }]),
+            exported["unknown_annotations"]
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            "kind,status,documentation,page,number,title,id,link,tests,untested targets,\
+             failing targets\r\n\
+             section,unlinked,FLS,Example document,12,\"Example, with a comma\",fls_01,\
+             ../fls/example.html#example,,,\r\n\
+             paragraph,linked,FLS,Example document,12:1,,fls_02,../fls/example.html#fls_02,\
+             \"ui/bar.rs\nui/foo.rs\",,\r\n\
+             paragraph,unlinked,FLS,Example document,12:2,,fls_03,../fls/example.html#fls_03,,,\r\n",
+            csv(&example_matrix())
+        );
+    }
+
+    #[test]
+    fn test_write_csv_row_escaping() {
+        let mut output = String::new();
+        write_csv_row(&mut output, ["plain", "with \"quotes\"", "multi\nline", ""]);
+        assert_eq!("plain,\"with \"\"quotes\"\"\",\"multi\nline\",\r\n", output);
+    }
+}
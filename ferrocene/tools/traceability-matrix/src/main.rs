@@ -2,52 +2,272 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 mod annotations;
+mod diff;
 mod documentations;
+mod export;
 mod matrix;
+mod policy;
 mod report;
 mod test_outcomes;
+#[cfg(test)]
+mod test_utils;
 mod utils;
 
 use crate::annotations::Annotations;
 use crate::matrix::TraceabilityMatrix;
+use crate::policy::Policy;
 use crate::report::Urls;
 use crate::test_outcomes::TestOutcomes;
 use crate::utils::capitalize;
 use anyhow::Error;
-use std::path::PathBuf;
+use clap::{Args, Parser};
+use std::path::{Path, PathBuf};
 
-fn main() -> Result<(), Error> {
-    let annotations_path = env_path("ANNOTATIONS");
-    let html_out = env_path("HTML_OUT");
-    let src_base = env_path("SRC_BASE");
-    let test_outcomes_dir = maybe_env_path("TEST_OUTCOMES_DIR");
+#[derive(Debug, Parser)]
+enum Cli {
+    /// Generate the traceability matrix of the current source tree
+    Generate(GenerateArgs),
+    /// Compare the traceability matrices of two releases
+    Diff(DiffArgs),
+}
+
+#[derive(Debug, Args)]
+struct GenerateArgs {
+    /// Directory containing the test annotations collected by compiletest
+    #[clap(long, value_name = "DIR")]
+    annotations: PathBuf,
+    /// Base directory of the test suites, stripped from the paths of the tests
+    #[clap(long, value_name = "DIR")]
+    src_base: PathBuf,
+    /// URL of the source code, used to link to the tests in the HTML report
+    #[clap(long, value_name = "URL")]
+    src_url: String,
+    /// Path of the traceability ids of the Ferrocene Language Specification
+    #[clap(long, value_name = "PATH")]
+    fls_ids: PathBuf,
+    /// URL of the Ferrocene Language Specification
+    #[clap(long, value_name = "URL")]
+    fls_url: String,
+    /// Path of the traceability ids of the User Manual
+    #[clap(long, value_name = "PATH")]
+    um_ids: PathBuf,
+    /// URL of the User Manual
+    #[clap(long, value_name = "URL")]
+    um_url: String,
+    /// Directory containing the build metrics of the test executions
+    #[clap(long, value_name = "DIR")]
+    test_outcomes_dir: Option<PathBuf>,
+    /// List of tests excluded from the test suites (ferrocene/ignored-tests.toml)
+    #[clap(long, value_name = "PATH", requires = "test_outcomes_dir")]
+    ignored_tests: Option<PathBuf>,
+    /// Policy file with the minimum coverage the matrix must have
+    #[clap(long, value_name = "PATH")]
+    policy: Option<PathBuf>,
+    /// Where to write the HTML report
+    #[clap(long, value_name = "PATH")]
+    html_out: Option<PathBuf>,
+    /// Where to write the JSON export of the matrix
+    #[clap(long, value_name = "PATH")]
+    json_out: Option<PathBuf>,
+    /// Where to write the CSV export of the matrix
+    #[clap(long, value_name = "PATH")]
+    csv_out: Option<PathBuf>,
+}
 
-    let urls = Urls { src: env_str("SRC_URL") };
+#[derive(Debug, Args)]
+struct DiffArgs {
+    /// Directory containing the test annotations of the old release
+    #[clap(long, value_name = "DIR")]
+    old_annotations: PathBuf,
+    /// Base directory of the test suites of the old release
+    #[clap(long, value_name = "DIR")]
+    old_src_base: PathBuf,
+    /// Path of the FLS traceability ids of the old release
+    #[clap(long, value_name = "PATH")]
+    old_fls_ids: PathBuf,
+    /// URL of the FLS of the old release
+    #[clap(long, value_name = "URL")]
+    old_fls_url: String,
+    /// Path of the User Manual traceability ids of the old release
+    #[clap(long, value_name = "PATH")]
+    old_um_ids: PathBuf,
+    /// URL of the User Manual of the old release
+    #[clap(long, value_name = "URL")]
+    old_um_url: String,
+    /// Directory containing the build metrics of the test executions of the old release
+    #[clap(long, value_name = "DIR")]
+    old_test_outcomes_dir: Option<PathBuf>,
+    /// List of tests excluded from the test suites of the old release
+    #[clap(long, value_name = "PATH", requires = "old_test_outcomes_dir")]
+    old_ignored_tests: Option<PathBuf>,
+    /// Directory containing the test annotations of the new release
+    #[clap(long, value_name = "DIR")]
+    new_annotations: PathBuf,
+    /// Base directory of the test suites of the new release
+    #[clap(long, value_name = "DIR")]
+    new_src_base: PathBuf,
+    /// Path of the FLS traceability ids of the new release
+    #[clap(long, value_name = "PATH")]
+    new_fls_ids: PathBuf,
+    /// URL of the FLS of the new release
+    #[clap(long, value_name = "URL")]
+    new_fls_url: String,
+    /// Path of the User Manual traceability ids of the new release
+    #[clap(long, value_name = "PATH")]
+    new_um_ids: PathBuf,
+    /// URL of the User Manual of the new release
+    #[clap(long, value_name = "URL")]
+    new_um_url: String,
+    /// Directory containing the build metrics of the test executions of the new release
+    #[clap(long, value_name = "DIR")]
+    new_test_outcomes_dir: Option<PathBuf>,
+    /// List of tests excluded from the test suites of the new release
+    #[clap(long, value_name = "PATH", requires = "new_test_outcomes_dir")]
+    new_ignored_tests: Option<PathBuf>,
+    /// Where to write the HTML report of the changes
+    #[clap(long, value_name = "PATH")]
+    html_out: Option<PathBuf>,
+    /// Where to write the textual report of the changes (printed to stdout if missing)
+    #[clap(long, value_name = "PATH")]
+    text_out: Option<PathBuf>,
+}
 
-    let documentations = [
-        documentations::load("FLS", &env_path("FLS_IDS"), &env_str("FLS_URL"))?,
-        documentations::load("UM", &env_path("UM_IDS"), &env_str("UM_URL"))?,
-    ];
+/// Inputs needed to build the traceability matrix of a release.
+struct Release<'a> {
+    annotations: &'a Path,
+    src_base: &'a Path,
+    fls_ids: &'a Path,
+    fls_url: &'a str,
+    um_ids: &'a Path,
+    um_url: &'a str,
+    test_outcomes_dir: Option<&'a Path>,
+    ignored_tests: Option<&'a Path>,
+}
+
+impl Release<'_> {
+    fn load(&self) -> Result<(Annotations, TraceabilityMatrix), Error> {
+        let documentations = [
+            documentations::load("FLS", self.fls_ids, self.fls_url)?,
+            documentations::load("UM", self.um_ids, self.um_url)?,
+        ];
+
+        let test_outcomes = if let Some(dir) = self.test_outcomes_dir {
+            let mut test_outcomes = TestOutcomes::load(dir)?;
+            if let Some(ignored_tests) = self.ignored_tests {
+                test_outcomes.load_excluded_tests(ignored_tests)?;
+            }
+            Some(test_outcomes)
+        } else {
+            None
+        };
+
+        let mut annotations = Annotations::new();
+        annotations.load_directory(self.annotations, self.src_base, test_outcomes.as_ref())?;
+
+        let matrix = matrix::prepare(&documentations, &annotations)?;
+        Ok((annotations, matrix))
+    }
+}
 
-    let test_outcomes =
-        if let Some(dir) = test_outcomes_dir { Some(TestOutcomes::load(&dir)?) } else { None };
+fn main() -> Result<(), Error> {
+    match Cli::parse() {
+        Cli::Generate(args) => generate(args),
+        Cli::Diff(args) => diff(args),
+    }
+}
 
-    let mut annotations = Annotations::new();
-    annotations.load_directory(&annotations_path, &src_base, test_outcomes.as_ref())?;
+fn generate(args: GenerateArgs) -> Result<(), Error> {
+    if args.html_out.is_none() && args.json_out.is_none() && args.csv_out.is_none() {
+        anyhow::bail!("at least one of --html-out, --json-out or --csv-out must be provided");
+    }
+    let policy = args.policy.as_deref().map(Policy::load).transpose()?;
 
-    let matrix = matrix::prepare(&documentations, &annotations)?;
+    let (annotations, matrix) = Release {
+        annotations: &args.annotations,
+        src_base: &args.src_base,
+        fls_ids: &args.fls_ids,
+        fls_url: &args.fls_url,
+        um_ids: &args.um_ids,
+        um_url: &args.um_url,
+        test_outcomes_dir: args.test_outcomes_dir.as_deref(),
+        ignored_tests: args.ignored_tests.as_deref(),
+    }
+    .load()?;
     cli_summary(&matrix);
 
-    let report = report::generate(&annotations, &matrix, urls)?;
-    std::fs::write(&html_out, report.as_bytes())?;
+    if let Some(json_out) = &args.json_out {
+        std::fs::write(json_out, export::json(&matrix)?.as_bytes())?;
+        eprintln!("JSON export: {}", json_out.display());
+    }
+    if let Some(csv_out) = &args.csv_out {
+        std::fs::write(csv_out, export::csv(&matrix).as_bytes())?;
+        eprintln!("CSV export: {}", csv_out.display());
+    }
+    if let Some(html_out) = &args.html_out {
+        let report = report::generate(&annotations, &matrix, Urls { src: args.src_url })?;
+        std::fs::write(html_out, report.as_bytes())?;
 
-    // The file:// link is hopefully clickable in terminals.
-    eprintln!("Full report: file://{}", std::fs::canonicalize(&html_out)?.display());
+        // The file:// link is hopefully clickable in terminals.
+        eprintln!("Full report: file://{}", std::fs::canonicalize(html_out)?.display());
+    }
     eprintln!();
 
+    let violations = policy.map(|policy| policy.check(&matrix)).unwrap_or_default();
+    if !violations.is_empty() {
+        eprintln!("Traceability policy violations:");
+        for violation in &violations {
+            eprintln!("- {violation}");
+        }
+        eprintln!();
+    }
+
     if !matrix.unknown_annotations.is_empty() {
         anyhow::bail!("some tests have unknown annotations");
     }
+    if !violations.is_empty() {
+        anyhow::bail!("the traceability matrix does not satisfy the policy");
+    }
+
+    Ok(())
+}
+
+fn diff(args: DiffArgs) -> Result<(), Error> {
+    let old = Release {
+        annotations: &args.old_annotations,
+        src_base: &args.old_src_base,
+        fls_ids: &args.old_fls_ids,
+        fls_url: &args.old_fls_url,
+        um_ids: &args.old_um_ids,
+        um_url: &args.old_um_url,
+        test_outcomes_dir: args.old_test_outcomes_dir.as_deref(),
+        ignored_tests: args.old_ignored_tests.as_deref(),
+    }
+    .load()?;
+    let new = Release {
+        annotations: &args.new_annotations,
+        src_base: &args.new_src_base,
+        fls_ids: &args.new_fls_ids,
+        fls_url: &args.new_fls_url,
+        um_ids: &args.new_um_ids,
+        um_url: &args.new_um_url,
+        test_outcomes_dir: args.new_test_outcomes_dir.as_deref(),
+        ignored_tests: args.new_ignored_tests.as_deref(),
+    }
+    .load()?;
+
+    let diff = diff::compute(&old.0, &old.1, &new.0, &new.1);
+
+    match &args.text_out {
+        Some(text_out) => {
+            std::fs::write(text_out, diff::text(&diff).as_bytes())?;
+            eprintln!("Text report: {}", text_out.display());
+        }
+        None => print!("{}", diff::text(&diff)),
+    }
+    if let Some(html_out) = &args.html_out {
+        std::fs::write(html_out, diff::html(&diff)?.as_bytes())?;
+        eprintln!("Full report: file://{}", std::fs::canonicalize(html_out)?.display());
+    }
 
     Ok(())
 }
@@ -78,26 +298,3 @@ fn cli_summary(matrix: &TraceabilityMatrix) {
         eprintln!();
     }
 }
-
-fn env_str(var: &str) -> String {
-    let var = format!("TRACEABILITY_MATRIX_{var}");
-    if let Ok(content) = std::env::var(&var) {
-        content
-    } else {
-        panic!("missing enviroment variable {var}");
-    }
-}
-
-fn env_path(var: &str) -> PathBuf {
-    let var = format!("TRACEABILITY_MATRIX_{var}");
-    if let Some(content) = std::env::var_os(&var) {
-        content.into()
-    } else {
-        panic!("missing enviroment variable {var}");
-    }
-}
-
-fn maybe_env_path(var: &str) -> Option<PathBuf> {
-    let var = format!("TRACEABILITY_MATRIX_{var}");
-    std::env::var_os(var).map(|content| content.into())
-}
@@ -4,6 +4,7 @@
 use crate::annotations::{AnnotatedFile, Annotations};
 use crate::documentations::Documentation;
 use anyhow::Error;
+use serde::Serialize;
 use std::collections::{BTreeSet, HashSet};
 use std::fmt::Debug;
 use std::ops::Deref;
@@ -134,7 +135,7 @@ pub(crate) fn prepare(
     Ok(matrix)
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Serialize)]
 pub(crate) struct TraceabilityMatrix {
     pub(crate) sections: MatrixAnalysis,
     pub(crate) paragraphs: MatrixAnalysis,
@@ -148,13 +149,13 @@ pub(crate) fn analyses_by_kind(&self) -> impl Iterator<Item = &MatrixAnalysis> {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub(crate) struct UnknownAnnotation {
     pub(crate) annotation: String,
     pub(crate) file: AnnotatedFile,
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Serialize)]
 pub(crate) struct MatrixAnalysis {
     pub(crate) kind: &'static ElementKind,
     pub(crate) linked: BTreeSet<Link>,
@@ -189,40 +190,44 @@ fn add(
         } else {
             tests.sort();
             let mut untested_targets = BTreeSet::new();
+            let mut failing_targets = BTreeSet::new();
             for link_test in &tests {
                 if let LinkTest::File(annotated) = link_test {
-                    for target in &annotated.targets.ignored.0 {
-                        untested_targets.insert(target.to_owned());
-                    }
+                    untested_targets.extend(annotated.targets.untested().cloned());
+                    failing_targets.extend(annotated.targets.failed.0.iter().cloned());
                 }
             }
             // another loop, to capture targets that are not tested at all
             for link_test in &tests {
                 if let LinkTest::File(annotated) = link_test {
-                    for target in &annotated.targets.executed.0 {
+                    for target in &annotated.targets.passed.0 {
                         untested_targets.remove(target);
                     }
                 }
             }
-            if untested_targets.is_empty() {
-                self.linked.insert(Link { element, tests: tests.clone(), untested_targets });
+            // a test failing on a target is reported as such, even if other tests were ignored
+            for target in &failing_targets {
+                untested_targets.remove(target);
+            }
+
+            let link = Link { element, tests, untested_targets, failing_targets };
+            if link.untested_targets.is_empty() && link.failing_targets.is_empty() {
+                self.linked.insert(link);
             } else {
-                self.partially_linked.insert(Link {
-                    element,
-                    tests: tests.clone(),
-                    untested_targets,
-                });
+                self.partially_linked.insert(link);
             }
             true
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize)]
 pub(crate) struct Link {
+    #[serde(flatten)]
     pub(crate) element: Element,
     pub(crate) tests: Vec<LinkTest>,
     pub(crate) untested_targets: BTreeSet<String>,
+    pub(crate) failing_targets: BTreeSet<String>,
 }
 
 impl Deref for Link {
@@ -237,9 +242,15 @@ impl Link {
     pub(crate) fn hide_in_annotation_mode(&self) -> bool {
         self.tests.iter().all(|t| t.hide_in_annotation_mode())
     }
+
+    /// Whether the element is linked to tests that all passed on the target.
+    pub(crate) fn passes_on(&self, target: &str) -> bool {
+        !self.untested_targets.contains(target) && !self.failing_targets.contains(target)
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
 pub(crate) enum LinkTest {
     File(AnnotatedFile),
     NoParagraphsInSection,
@@ -258,8 +269,22 @@ fn hide_in_annotation_mode(&self) -> bool {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
+impl std::fmt::Display for LinkTest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkTest::File(file) => write!(f, "{file}"),
+            LinkTest::NoParagraphsInSection => write!(f, "no paragraphs within"),
+            LinkTest::Informational => write!(f, "informational"),
+            LinkTest::InheritFromSection { section_number, .. } => {
+                write!(f, "inherited from section {section_number}")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize)]
 pub(crate) struct Element {
+    #[serde(skip)]
     pub(crate) kind: &'static ElementKind,
     pub(crate) number: Option<ElementNumber>,
     pub(crate) page: Page,
@@ -279,22 +304,25 @@ pub(crate) fn name(&self) -> String {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Serialize)]
 pub(crate) struct ElementKind {
     pub(crate) singular: &'static str,
     pub(crate) plural: &'static str,
+    #[serde(skip)]
     pub(crate) hide_in_annotation_mode: bool,
+    #[serde(skip)]
     pub(crate) include_title_when_copying: bool,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash, Serialize)]
 pub(crate) struct Page {
     pub(crate) documentation: String,
     pub(crate) name: String,
     pub(crate) link: String,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize)]
+#[serde(transparent)]
 pub(crate) struct ElementNumber(String);
 
 impl Ord for ElementNumber {
@@ -353,7 +381,7 @@ fn from(f: T) -> Self {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::annotations::AnnotationSource;
+    use crate::annotations::{AnnotationSource, DisplayCommaSeparatedSet, Targets};
     use crate::documentations::{CliOption, Document, Paragraph, Section, TraceabilityIds};
     use std::collections::{BTreeMap, BTreeSet};
     use std::path::Path;
@@ -475,6 +503,7 @@ fn test_prepare() -> Result<(), Error> {
             ]),
             ignored_tests: BTreeMap::new(),
             considers_ignored_tests: true,
+            targets: BTreeSet::new(),
         };
 
         assert_eq!(
@@ -504,6 +533,7 @@ fn test_prepare() -> Result<(), Error> {
                                 },
                             ],
                             untested_targets: Default::default(),
+                            failing_targets: Default::default(),
                         },
                         Link {
                             element: Element {
@@ -523,6 +553,7 @@ fn test_prepare() -> Result<(), Error> {
                                 section_number: "12.2".into()
                             }],
                             untested_targets: Default::default(),
+                            failing_targets: Default::default(),
                         },
                         Link {
                             element: Element {
@@ -542,6 +573,7 @@ fn test_prepare() -> Result<(), Error> {
                                 section_number: "12.2.2".into()
                             }],
                             untested_targets: Default::default(),
+                            failing_targets: Default::default(),
                         },
                         Link {
                             element: Element {
@@ -561,6 +593,7 @@ fn test_prepare() -> Result<(), Error> {
                                 section_number: "A.1".into()
                             }],
                             untested_targets: Default::default(),
+                            failing_targets: Default::default(),
                         },
                     ]),
                     unlinked: BTreeSet::from([Element {
@@ -595,6 +628,7 @@ fn test_prepare() -> Result<(), Error> {
                             },
                             tests: vec![link_test_itself("/example/foobar.rs")],
                             untested_targets: Default::default(),
+                            failing_targets: Default::default(),
                         },
                         Link {
                             element: Element {
@@ -611,6 +645,7 @@ fn test_prepare() -> Result<(), Error> {
                             },
                             tests: vec![LinkTest::NoParagraphsInSection],
                             untested_targets: Default::default(),
+                            failing_targets: Default::default(),
                         },
                         Link {
                             element: Element {
@@ -627,6 +662,7 @@ fn test_prepare() -> Result<(), Error> {
                             },
                             tests: vec![LinkTest::Informational],
                             untested_targets: Default::default(),
+                            failing_targets: Default::default(),
                         },
                         Link {
                             element: Element {
@@ -643,6 +679,7 @@ fn test_prepare() -> Result<(), Error> {
                             },
                             tests: vec![LinkTest::Informational],
                             untested_targets: Default::default(),
+                            failing_targets: Default::default(),
                         },
                     ]),
                     unlinked: BTreeSet::from([Element {
@@ -676,6 +713,7 @@ fn test_prepare() -> Result<(), Error> {
                         },
                         tests: vec![link_test_itself("/example/foobar.rs")],
                         untested_targets: Default::default(),
+                        failing_targets: Default::default(),
                     }]),
                     unlinked: BTreeSet::default(),
                     partially_linked: Default::default(),
@@ -697,6 +735,71 @@ fn test_prepare() -> Result<(), Error> {
         Ok(())
     }
 
+    #[test]
+    fn test_prepare_targets() -> Result<(), Error> {
+        let targets = |passed: &[&str], failed: &[&str], excluded: &[&str], not_run: &[&str]| {
+            let set = |targets: &[&str]| {
+                DisplayCommaSeparatedSet(targets.iter().map(|t| t.to_string()).collect())
+            };
+            Targets {
+                passed: set(passed),
+                failed: set(failed),
+                ignored: set(&[]),
+                excluded: set(excluded),
+                not_run: set(not_run),
+            }
+        };
+        let file = |test: &str, targets| AnnotatedFile {
+            test: test.into(),
+            source: AnnotationSource::TestItself,
+            targets,
+        };
+
+        let annotations = Annotations {
+            ids: BTreeMap::from([
+                (
+                    "fls_a".into(),
+                    BTreeSet::from([
+                        file("a.rs", targets(&["x"], &[], &[], &["y"])),
+                        file("b.rs", targets(&["y"], &["z"], &[], &[])),
+                    ]),
+                ),
+                ("fls_b".into(), BTreeSet::from([file("c.rs", targets(&["x"], &[], &["y"], &[]))])),
+                (
+                    "fls_c".into(),
+                    BTreeSet::from([file("d.rs", targets(&["x", "y"], &[], &[], &[]))]),
+                ),
+            ]),
+            ignored_tests: BTreeMap::new(),
+            considers_ignored_tests: true,
+            targets: ["x".into(), "y".into(), "z".into()].into(),
+        };
+        let matrix = prepare(
+            &[crate::test_utils::documentation(&["fls_a", "fls_b", "fls_c"])],
+            &annotations,
+        )?;
+
+        let partial =
+            |id: &str| matrix.paragraphs.partially_linked.iter().find(|l| l.id == id).unwrap();
+        let set = |target: &str| BTreeSet::from([target.to_string()]);
+
+        let a = partial("fls_a");
+        assert!(a.untested_targets.is_empty());
+        assert_eq!(set("z"), a.failing_targets);
+        assert!(a.passes_on("x") && a.passes_on("y") && !a.passes_on("z"));
+
+        let b = partial("fls_b");
+        assert_eq!(set("y"), b.untested_targets);
+        assert!(b.failing_targets.is_empty());
+        assert!(b.passes_on("x") && !b.passes_on("y"));
+
+        assert_eq!(
+            vec!["fls_c"],
+            matrix.paragraphs.linked.iter().map(|l| l.id.as_str()).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_element_numbers_ordering() {
         fn numberize(numbers: &[&str]) -> Vec<ElementNumber> {
@@ -0,0 +1,258 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use crate::matrix::{ElementKind, TraceabilityMatrix};
+use anyhow::Error;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Display;
+use std::path::Path;
+
+/// Policy the traceability matrix has to satisfy, enforced in CI to prevent the traceability
+/// coverage from silently dropping.
+///
+/// ```toml
+/// [minimum-linked-percentage]
+/// sections = 95.0
+/// paragraphs = 80.0
+/// cli-options = 100.0
+///
+/// [[allowed-unlinked]]
+/// id = "fls_abcdef"
+/// justification = "Only describes the notation used in the rest of the chapter."
+/// ```
+///
+/// Elements present in the allow-list are counted as linked when checking the thresholds.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub(crate) struct Policy {
+    #[serde(default)]
+    minimum_linked_percentage: Thresholds,
+    #[serde(default)]
+    allowed_unlinked: Vec<AllowedUnlinked>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct Thresholds {
+    sections: Option<f32>,
+    paragraphs: Option<f32>,
+    cli_options: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AllowedUnlinked {
+    id: String,
+    justification: String,
+}
+
+impl Policy {
+    pub(crate) fn load(path: &Path) -> Result<Self, Error> {
+        let policy: Policy = toml::from_str(&std::fs::read_to_string(path)?)?;
+
+        let mut seen = HashSet::new();
+        for allowed in &policy.allowed_unlinked {
+            if !seen.insert(&allowed.id) {
+                anyhow::bail!("{} is allow-listed multiple times", allowed.id);
+            }
+            if allowed.justification.trim().is_empty() {
+                anyhow::bail!("{} is allow-listed without a justification", allowed.id);
+            }
+        }
+
+        Ok(policy)
+    }
+
+    pub(crate) fn check(&self, matrix: &TraceabilityMatrix) -> Vec<Violation> {
+        let allowed =
+            self.allowed_unlinked.iter().map(|a| (a.id.as_str(), a)).collect::<BTreeMap<_, _>>();
+        let mut used = HashSet::new();
+        let mut violations = Vec::new();
+
+        let thresholds = &self.minimum_linked_percentage;
+        for (analysis, minimum) in [
+            (&matrix.sections, thresholds.sections),
+            (&matrix.paragraphs, thresholds.paragraphs),
+            (&matrix.cli_options, thresholds.cli_options),
+        ] {
+            let mut linked = analysis.linked.len();
+            for link in &analysis.linked {
+                if allowed.contains_key(link.id.as_str()) {
+                    used.insert(link.id.as_str());
+                    violations.push(Violation::AllowedButLinked { id: link.id.clone() });
+                }
+            }
+            for element in analysis.partially_linked.iter().map(|l| &l.element) {
+                if allowed.contains_key(element.id.as_str()) {
+                    used.insert(element.id.as_str());
+                    linked += 1;
+                }
+            }
+            for element in &analysis.unlinked {
+                if allowed.contains_key(element.id.as_str()) {
+                    used.insert(element.id.as_str());
+                    linked += 1;
+                }
+            }
+
+            let total =
+                analysis.linked.len() + analysis.partially_linked.len() + analysis.unlinked.len();
+            let percentage = if total == 0 { 100.0 } else { linked as f32 * 100.0 / total as f32 };
+            if let Some(minimum) = minimum {
+                if percentage < minimum {
+                    violations.push(Violation::BelowThreshold {
+                        kind: analysis.kind,
+                        linked,
+                        total,
+                        percentage,
+                        minimum,
+                    });
+                }
+            }
+        }
+
+        for id in allowed.keys() {
+            if !used.contains(id) {
+                violations.push(Violation::AllowedButUnknown { id: id.to_string() });
+            }
+        }
+
+        violations
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Violation {
+    BelowThreshold {
+        kind: &'static ElementKind,
+        linked: usize,
+        total: usize,
+        percentage: f32,
+        minimum: f32,
+    },
+    AllowedButLinked {
+        id: String,
+    },
+    AllowedButUnknown {
+        id: String,
+    },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::BelowThreshold { kind, linked, total, percentage, minimum } => write!(
+                f,
+                "{percentage:.2}% of {} are linked or allow-listed ({linked}/{total}), \
+                 below the minimum of {minimum:.2}%",
+                kind.plural
+            ),
+            Violation::AllowedButLinked { id } => {
+                write!(f, "{id} is allow-listed as unlinked, but is linked to a test")
+            }
+            Violation::AllowedButUnknown { id } => {
+                write!(f, "{id} is allow-listed as unlinked, but does not exist")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::release;
+
+    fn policy(contents: &str) -> Result<Policy, Error> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("policy.toml");
+        std::fs::write(&path, contents)?;
+        Policy::load(&path)
+    }
+
+    fn violations(
+        policy: &Policy,
+        paragraphs: &[&str],
+        annotations: &[(&str, &str)],
+    ) -> Vec<String> {
+        let release = release(paragraphs, annotations);
+        policy.check(&release.matrix).iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_thresholds() -> Result<(), Error> {
+        let policy = policy(
+            "[minimum-linked-percentage]\n\
+             sections = 0.0\n\
+             paragraphs = 50.0\n",
+        )?;
+
+        let paragraphs = ["fls_a", "fls_b", "fls_c"];
+        assert!(
+            violations(&policy, &paragraphs, &[("fls_a", "a.rs"), ("fls_b", "b.rs")]).is_empty()
+        );
+        assert_eq!(
+            vec!["33.33% of paragraphs are linked or allow-listed (1/3), below the minimum of 50.00%"],
+            violations(&policy, &paragraphs, &[("fls_a", "a.rs")])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_allowed_unlinked() -> Result<(), Error> {
+        let policy = policy(
+            "[minimum-linked-percentage]\n\
+             paragraphs = 100.0\n\
+             \n\
+             [[allowed-unlinked]]\n\
+             id = \"fls_b\"\n\
+             justification = \"Only describes the notation.\"\n\
+             \n\
+             [[allowed-unlinked]]\n\
+             id = \"fls_z\"\n\
+             justification = \"Removed from the specification.\"\n",
+        )?;
+
+        assert_eq!(
+            vec!["fls_z is allow-listed as unlinked, but does not exist"],
+            violations(&policy, &["fls_a", "fls_b"], &[("fls_a", "a.rs")])
+        );
+        assert_eq!(
+            vec![
+                "fls_b is allow-listed as unlinked, but is linked to a test",
+                "fls_z is allow-listed as unlinked, but does not exist",
+            ],
+            violations(&policy, &["fls_a", "fls_b"], &[("fls_a", "a.rs"), ("fls_b", "b.rs")])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_invalid() {
+        let err = |contents| policy(contents).unwrap_err().to_string();
+
+        assert_eq!(
+            "fls_a is allow-listed without a justification",
+            err("[[allowed-unlinked]]\nid = \"fls_a\"\njustification = \" \"\n")
+        );
+        assert_eq!(
+            "fls_a is allow-listed multiple times",
+            err("[[allowed-unlinked]]\nid = \"fls_a\"\njustification = \"a\"\n\
+                 [[allowed-unlinked]]\nid = \"fls_a\"\njustification = \"b\"\n")
+        );
+        assert!(err("[minimum-linked-percentage]\nchapters = 50.0\n").contains("unknown field"));
+    }
+
+    #[test]
+    fn test_load_committed_policy() -> Result<(), Error> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../traceability-policy.toml");
+        let policy = Policy::load(&path)?;
+
+        let thresholds = &policy.minimum_linked_percentage;
+        assert_eq!(Some(99.9), thresholds.sections);
+        assert_eq!(Some(99.93), thresholds.paragraphs);
+        assert_eq!(Some(98.18), thresholds.cli_options);
+        assert!(policy.allowed_unlinked.is_empty());
+        Ok(())
+    }
+}
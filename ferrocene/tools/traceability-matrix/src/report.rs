@@ -12,11 +12,18 @@
 struct Report<'a> {
     considers_ignored_tests: bool,
     matrix: &'a TraceabilityMatrix,
-    summary: Vec<SummaryRow<'a>>,
+    summaries: Vec<Summary<'a>>,
+    targets: &'a BTreeSet<String>,
     ignored_tests: BTreeMap<String, BTreeSet<String>>,
     urls: Urls,
 }
 
+/// Summary of the coverage, either across all targets or for a single target.
+struct Summary<'a> {
+    target: Option<&'a str>,
+    rows: Vec<SummaryRow<'a>>,
+}
+
 struct SummaryRow<'a> {
     page: Option<&'a Page>,
     kinds: Vec<SummaryItem>,
@@ -54,9 +61,15 @@ pub(crate) fn generate(
     matrix: &TraceabilityMatrix,
     urls: Urls,
 ) -> Result<String, Error> {
+    let summaries = std::iter::once(None)
+        .chain(annotations.targets.iter().map(|target| Some(target.as_str())))
+        .map(|target| Summary { target, rows: build_summary(matrix, target) })
+        .collect();
+
     Ok(Report {
         matrix,
-        summary: build_summary(matrix),
+        summaries,
+        targets: &annotations.targets,
         urls,
         ignored_tests: annotations.ignored_tests.clone(),
         considers_ignored_tests: annotations.considers_ignored_tests,
@@ -64,7 +77,9 @@ pub(crate) fn generate(
     .render()?)
 }
 
-fn build_summary(matrix: &TraceabilityMatrix) -> Vec<SummaryRow<'_>> {
+/// Build the summary table. When a target is provided, partially linked elements whose tests all
+/// passed on that target are counted as linked.
+fn build_summary<'a>(matrix: &'a TraceabilityMatrix, target: Option<&str>) -> Vec<SummaryRow<'a>> {
     let sample_kinds = matrix
         .analyses_by_kind()
         .map(|a| (a.kind, SummaryItem { kind: a.kind, linked: 0, total: 0, percentage: 0.0 }))
@@ -73,7 +88,10 @@ fn build_summary(matrix: &TraceabilityMatrix) -> Vec<SummaryRow<'_>> {
     // The table is created in an earlier step because we want to make sure all kinds are present,
     // even if a page doesn't contain that kind of data.
     let mut rows = crate::utils::chain(
-        matrix.analyses_by_kind().flat_map(|a| a.linked.iter()).map(|l| &l.page),
+        matrix
+            .analyses_by_kind()
+            .flat_map(|a| a.linked.iter().chain(a.partially_linked.iter()))
+            .map(|l| &l.page),
         matrix.analyses_by_kind().flat_map(|a| a.unlinked.iter()).map(|u| &u.page),
     )
     .collect::<HashSet<_>>()
@@ -95,6 +113,10 @@ fn build_summary(matrix: &TraceabilityMatrix) -> Vec<SummaryRow<'_>> {
         }
         for item in &analysis.partially_linked {
             let page = rows.get_mut(&item.page).unwrap().get_mut(&item.kind).unwrap();
+            if target.map(|t| item.passes_on(t)).unwrap_or(false) {
+                page.linked += 1;
+                kind_all.linked += 1;
+            }
             page.total += 1;
             kind_all.total += 1;
         }
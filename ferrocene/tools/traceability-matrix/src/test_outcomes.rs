@@ -2,7 +2,8 @@
 // SPDX-FileCopyrightText: The Ferrocene Developers
 
 use anyhow::{Context, Error};
-use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::path::Path;
 
 const COMPILETEST_TYPE: &str = "bootstrap::test::Compiletest";
@@ -11,12 +12,27 @@
 
 #[derive(Debug, PartialEq, Eq, Default, Clone)]
 pub(crate) struct TestOutcomes {
+    // targets on which at least one compiletest suite was executed
+    pub(crate) targets: BTreeSet<String>,
     // key is name of test, represented by a path
-    // value is targets on which the tests were executed
-    pub(crate) executed_tests: BTreeMap<String, BTreeSet<String>>,
+    // value is targets on which the tests passed
+    pub(crate) passed_tests: BTreeMap<String, BTreeSet<String>>,
     // key is name of test, also represented by a path
-    // value can be any number of ignored tests
+    // value is targets on which the tests failed
+    pub(crate) failed_tests: BTreeMap<String, BTreeSet<String>>,
+    // key is name of test, also represented by a path
+    // value is targets on which the tests were ignored (for example by `//@ ignore-*`)
     pub(crate) ignored_tests: BTreeMap<String, BTreeSet<String>>,
+    // tests not even passed to compiletest, as they are listed in ferrocene/ignored-tests.toml
+    pub(crate) excluded_tests: Vec<ExcludedTests>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+pub(crate) struct ExcludedTests {
+    // paths of the tests, or of directories containing them
+    pub(crate) tests: Vec<String>,
+    pub(crate) targets: BTreeSet<String>,
+    pub(crate) reason: String,
 }
 
 impl TestOutcomes {
@@ -67,20 +83,17 @@ pub(crate) fn load(directory: &Path) -> Result<Self, Error> {
 
                             let (TestSuiteMetadata::CargoPackage { target }
                             | TestSuiteMetadata::Compiletest { target }) = metadata;
+                            test_outcomes.targets.insert(target.to_owned());
 
-                            if let MetricsTestOutcome::Ignored = outcome {
-                                test_outcomes
-                                    .ignored_tests
-                                    .entry(name)
-                                    .or_insert_with(BTreeSet::new)
-                                    .insert(target.to_owned());
-                            } else {
-                                test_outcomes
-                                    .executed_tests
-                                    .entry(name)
-                                    .or_insert_with(BTreeSet::new)
-                                    .insert(target.to_owned());
-                            }
+                            let tests = match outcome {
+                                MetricsTestOutcome::Passed => &mut test_outcomes.passed_tests,
+                                MetricsTestOutcome::Failed => &mut test_outcomes.failed_tests,
+                                MetricsTestOutcome::Ignored => &mut test_outcomes.ignored_tests,
+                            };
+                            tests
+                                .entry(name)
+                                .or_insert_with(BTreeSet::new)
+                                .insert(target.to_owned());
                         }
                     }
                 }
@@ -89,6 +102,26 @@ pub(crate) fn load(directory: &Path) -> Result<Self, Error> {
 
         Ok(test_outcomes)
     }
+
+    /// Load the list of tests excluded from the test suites, in the format of
+    /// `ferrocene/ignored-tests.toml`.
+    pub(crate) fn load_excluded_tests(&mut self, path: &Path) -> Result<(), Error> {
+        let contents = std::fs::read_to_string(path)?;
+        let suites: HashMap<String, Vec<ExcludedTests>> = toml::from_str(&contents)
+            .with_context(|| format!("failed to read excluded tests from {}", path.display()))?;
+        self.excluded_tests.extend(suites.into_values().flatten());
+        Ok(())
+    }
+
+    /// Targets on which the test was excluded, either directly or through one of its parent
+    /// directories.
+    pub(crate) fn excluded_targets(&self, test: &str) -> BTreeSet<String> {
+        self.excluded_tests
+            .iter()
+            .filter(|excluded| excluded.tests.iter().any(|t| Path::new(test).starts_with(t)))
+            .flat_map(|excluded| excluded.targets.iter().cloned())
+            .collect()
+    }
 }
 
 #[derive(serde::Deserialize)]
@@ -135,10 +168,9 @@ struct Test {
 
 #[derive(Debug, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
-// Failed is missing so as to trigger a deserialize failure,
-// because we should not reach this far if any test fails.
 enum MetricsTestOutcome {
     Passed,
+    Failed,
     Ignored,
 }
 
@@ -189,6 +221,10 @@ fn test_load_outcomes() {
                                                     "name": "[ui] tests/ui/baz.rs",
                                                     "outcome": "ignored",
                                                 },
+                                                {
+                                                    "name": "[ui] tests/ui/qux.rs",
+                                                    "outcome": "failed",
+                                                },
                                             ],
                                         },
                                     ],
@@ -322,7 +358,8 @@ fn test_load_outcomes() {
 
         assert_eq!(
             TestOutcomes {
-                executed_tests: BTreeMap::from([
+                targets: BTreeSet::from(["aarch64-unknown-linux-gnu".into()]),
+                passed_tests: BTreeMap::from([
                     (
                         "tests/ui/foo.rs".into(),
                         BTreeSet::from(["aarch64-unknown-linux-gnu".into()])
@@ -340,12 +377,51 @@ fn test_load_outcomes() {
                         BTreeSet::from(["aarch64-unknown-linux-gnu".into()])
                     ),
                 ]),
+                failed_tests: BTreeMap::from([(
+                    "tests/ui/qux.rs".into(),
+                    BTreeSet::from(["aarch64-unknown-linux-gnu".into()])
+                )]),
                 ignored_tests: BTreeMap::from([(
                     "tests/ui/baz.rs".into(),
                     BTreeSet::from(["aarch64-unknown-linux-gnu".into()])
                 )]),
+                excluded_tests: Vec::new(),
             },
             outcomes,
         )
     }
+
+    #[test]
+    fn test_load_excluded_tests() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("ignored-tests.toml");
+        std::fs::write(
+            &path,
+            r#"
+                [["tests/run-make"]]
+                tests = ["tests/run-make/foo"]
+                targets = ["aarch64-unknown-linux-gnu"]
+                reason = "Not supported by QEMU"
+
+                [["tests/ui"]]
+                tests = ["tests/ui/foo.rs", "tests/ui/bar.rs"]
+                targets = ["aarch64-unknown-none", "aarch64-unknown-linux-gnu"]
+                reason = "Requires a host toolchain"
+            "#,
+        )
+        .unwrap();
+
+        let mut outcomes = TestOutcomes::default();
+        outcomes.load_excluded_tests(&path).unwrap();
+
+        let excluded = |test| outcomes.excluded_targets(test).into_iter().collect::<Vec<_>>();
+        assert_eq!(vec!["aarch64-unknown-linux-gnu"], excluded("tests/run-make/foo"));
+        assert_eq!(vec!["aarch64-unknown-linux-gnu"], excluded("tests/run-make/foo/Makefile"));
+        assert!(excluded("tests/run-make/foobar").is_empty());
+        assert_eq!(
+            vec!["aarch64-unknown-linux-gnu", "aarch64-unknown-none"],
+            excluded("tests/ui/bar.rs")
+        );
+        assert!(excluded("tests/ui/baz.rs").is_empty());
+    }
 }
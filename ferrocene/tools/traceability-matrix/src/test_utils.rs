@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use crate::annotations::{AnnotatedFile, AnnotationSource, Annotations};
+use crate::documentations::{Document, Documentation, Paragraph, Section, TraceabilityIds};
+use crate::matrix::{self, TraceabilityMatrix};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub(crate) struct Release {
+    pub(crate) annotations: Annotations,
+    pub(crate) matrix: TraceabilityMatrix,
+}
+
+/// Build the traceability matrix of a release containing the [`documentation`] with the provided
+/// paragraphs, and with the provided `(id, test)` annotations.
+pub(crate) fn release(paragraphs: &[&str], annotations: &[(&str, &str)]) -> Release {
+    let mut ids: BTreeMap<String, BTreeSet<AnnotatedFile>> = BTreeMap::new();
+    for (id, test) in annotations {
+        ids.entry(id.to_string()).or_default().insert(AnnotatedFile {
+            test: test.into(),
+            source: AnnotationSource::TestItself,
+            targets: Default::default(),
+        });
+    }
+    let annotations = Annotations {
+        ids,
+        ignored_tests: BTreeMap::new(),
+        considers_ignored_tests: true,
+        targets: BTreeSet::new(),
+    };
+
+    let matrix = matrix::prepare(&[documentation(paragraphs)], &annotations).unwrap();
+    Release { annotations, matrix }
+}
+
+/// Build a documentation containing a single FLS section with the provided paragraphs.
+pub(crate) fn documentation(paragraphs: &[&str]) -> Documentation {
+    Documentation {
+        name: "FLS".into(),
+        url: "../fls".into(),
+        ids: TraceabilityIds {
+            documents: vec![Document {
+                title: "Example document".into(),
+                link: "example.html".into(),
+                informational: false,
+                sections: vec![Section {
+                    id: "fls_section".into(),
+                    number: "1".into(),
+                    title: "Example".into(),
+                    link: "example.html#example".into(),
+                    informational: false,
+                    paragraphs: paragraphs
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, id)| Paragraph {
+                            id: id.to_string(),
+                            number: format!("1:{}", idx + 1),
+                            link: format!("example.html#{id}"),
+                        })
+                        .collect(),
+                }],
+                options: Vec::new(),
+            }],
+        },
+    }
+}
@@ -17,6 +17,41 @@ let refreshAnnotationMode = () => {
 toggleAnnotationMode.addEventListener("change", refreshAnnotationMode);
 refreshAnnotationMode();
 
+let targetFilter = document.getElementById("target-filter");
+
+let refreshTargetFilter = () => {
+    let target = targetFilter === null ? "" : targetFilter.value;
+
+    document.querySelectorAll("tbody.target-summary").forEach(tbody => {
+        if (tbody.dataset.target === target) {
+            tbody.classList.remove("hidden");
+        } else {
+            tbody.classList.add("hidden");
+        }
+    });
+
+    document.querySelectorAll("tr[data-color]").forEach(row => {
+        let color = row.dataset.color;
+        if (target !== "") {
+            if (row.dataset.failingTargets.split(" ").includes(target)) {
+                color = "red";
+            } else if (row.dataset.untestedTargets.split(" ").includes(target)) {
+                color = "orange";
+            } else {
+                color = "green";
+            }
+        }
+
+        let circle = row.querySelector("div.circle");
+        circle.classList.remove("red", "orange", "green");
+        circle.classList.add(color);
+    });
+};
+if (targetFilter !== null) {
+    targetFilter.addEventListener("change", refreshTargetFilter);
+}
+refreshTargetFilter();
+
 document.querySelectorAll(".copiable").forEach(elem => {
     elem.addEventListener("click", (event => {
         // Only enable this when annotation mode is enabled.
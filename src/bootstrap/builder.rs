@@ -2104,6 +2104,13 @@ pub fn cargo(
         if target.contains("ferrocenecoretest") {
             rustflags.arg("-Zpanic-abort-tests");
         }
+        // The tests run through semihosting are bare metal images: std provides the entry point,
+        // and the targets only support `panic=abort`.
+        if cmd == "test" && self.semihosting_tested(target) {
+            rustflags.arg("-Zpanic-abort-tests");
+            rustflags.arg("-Clink-arg=-nostartfiles");
+            rustflags.arg("-Clink-arg=-static");
+        }
 
         Cargo { command: cargo, rustflags, rustdocflags, allow_features }
     }
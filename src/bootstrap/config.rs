@@ -301,7 +301,11 @@ pub struct Config {
     pub ferrocene_test_outcomes_dir: Option<PathBuf>,
     pub ferrocene_oxidos_src: Option<String>,
     pub ferrocene_tarball_signing_kms_key_arn: Option<String>,
+    pub ferrocene_tarball_signing_local_key: Option<PathBuf>,
+    pub ferrocene_self_test_keychain: Option<PathBuf>,
     pub ferrocene_document_signatures_s3_bucket: String,
+    pub ferrocene_document_signatures_local_dir: Option<PathBuf>,
+    pub ferrocene_document_signatures_private_key: Option<PathBuf>,
     pub ferrocene_ignore_document_signatures: bool,
 }
 
@@ -1084,7 +1088,11 @@ struct Ferrocene {
         test_outcomes_dir: Option<PathBuf> = "test-outcomes-dir",
         oxidos_src: Option<String> = "oxidos-src",
         tarball_signing_kms_key_arn: Option<String> = "tarball-signing-kms-key-arn",
+        tarball_signing_local_key: Option<PathBuf> = "tarball-signing-local-key",
+        self_test_keychain: Option<PathBuf> = "self-test-keychain",
         document_signatures_s3_bucket: Option<String> = "document-signatures-s3-bucket",
+        document_signatures_local_dir: Option<PathBuf> = "document-signatures-local-dir",
+        document_signatures_private_key: Option<PathBuf> = "document-signatures-private-key",
         ignore_document_signatures: Option<bool> = "ignore-document-signatures",
     }
 }
@@ -1674,9 +1682,13 @@ fn get_table(option: &str) -> Result<TomlConfig, toml::de::Error> {
             config.ferrocene_test_outcomes_dir = f.test_outcomes_dir;
             config.ferrocene_oxidos_src = f.oxidos_src;
             config.ferrocene_tarball_signing_kms_key_arn = f.tarball_signing_kms_key_arn;
+            config.ferrocene_tarball_signing_local_key = f.tarball_signing_local_key;
+            config.ferrocene_self_test_keychain = f.self_test_keychain;
             config.ferrocene_document_signatures_s3_bucket = f
                 .document_signatures_s3_bucket
                 .unwrap_or_else(|| "ferrocene-document-signatures".into());
+            config.ferrocene_document_signatures_local_dir = f.document_signatures_local_dir;
+            config.ferrocene_document_signatures_private_key = f.document_signatures_private_key;
             config.ferrocene_ignore_document_signatures =
                 f.ignore_document_signatures.unwrap_or(false);
         }
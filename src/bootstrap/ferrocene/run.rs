@@ -4,6 +4,7 @@
 use crate::builder::{Builder, RunConfig, ShouldRun, Step};
 use crate::config::{FerroceneTraceabilityMatrixMode, TargetSelection};
 use crate::ferrocene::doc::{Specification, UserManual};
+use crate::t;
 use crate::tool::Tool;
 use std::path::PathBuf;
 use std::process::Command;
@@ -34,6 +35,12 @@ fn run(self, builder: &Builder<'_>) -> Self::Output {
             builder.ensure(Specification { target: self.target, fresh_build: false });
         let user_manual = builder.ensure(UserManual { target: self.target, fresh_build: false });
 
+        // Let compiletest reject unknown annotations as soon as possible, pointing to where they are.
+        let traceability_ids = t!(std::env::join_paths([
+            specification.join("paragraph-ids.json"),
+            user_manual.join("traceability-ids.json"),
+        ]));
+
         let compiletest = builder.tool_exe(Tool::Compiletest);
         for (suite, mode) in &[("tests/ui", "ui"), ("tests/run-make", "run-make")] {
             builder.info(&format!("Loading test annotations from {suite}"));
@@ -45,7 +52,8 @@ fn run(self, builder: &Builder<'_>) -> Self::Output {
                     .env("FERROCENE_DEST", dest)
                     .env("FERROCENE_SRC_BASE", builder.src.join(suite))
                     .env("FERROCENE_MODE", mode)
-                    .env("FERROCENE_SUITE", suite),
+                    .env("FERROCENE_SUITE", suite)
+                    .env("FERROCENE_TRACEABILITY_IDS", &traceability_ids),
             );
         }
 
@@ -75,17 +83,35 @@ fn run(self, builder: &Builder<'_>) -> Self::Output {
         }
 
         let mut cmd = builder.tool_cmd(Tool::FerroceneTraceabilityMatrix);
-        cmd.env("TRACEABILITY_MATRIX_FLS_IDS", specification.join("paragraph-ids.json"))
-            .env("TRACEABILITY_MATRIX_FLS_URL", spec_url)
-            .env("TRACEABILITY_MATRIX_UM_IDS", user_manual.join("traceability-ids.json"))
-            .env("TRACEABILITY_MATRIX_UM_URL", user_manual_url)
-            .env("TRACEABILITY_MATRIX_ANNOTATIONS", test_annotations_base)
-            .env("TRACEABILITY_MATRIX_HTML_OUT", &html_output)
-            .env("TRACEABILITY_MATRIX_SRC_BASE", &builder.src)
-            .env("TRACEABILITY_MATRIX_SRC_URL", src_url);
+        cmd.arg("generate")
+            .arg("--fls-ids")
+            .arg(specification.join("paragraph-ids.json"))
+            .arg("--fls-url")
+            .arg(spec_url)
+            .arg("--um-ids")
+            .arg(user_manual.join("traceability-ids.json"))
+            .arg("--um-url")
+            .arg(user_manual_url)
+            .arg("--annotations")
+            .arg(test_annotations_base)
+            .arg("--src-base")
+            .arg(&builder.src)
+            .arg("--src-url")
+            .arg(src_url)
+            .arg("--policy")
+            .arg(builder.src.join("ferrocene").join("traceability-policy.toml"))
+            .arg("--html-out")
+            .arg(&html_output)
+            .arg("--json-out")
+            .arg(html_output.with_extension("json"))
+            .arg("--csv-out")
+            .arg(html_output.with_extension("csv"));
 
         if let Some(dir) = &builder.config.ferrocene_test_outcomes_dir {
-            cmd.env("TRACEABILITY_MATRIX_TEST_OUTCOMES_DIR", dir);
+            cmd.arg("--test-outcomes-dir")
+                .arg(dir)
+                .arg("--ignored-tests")
+                .arg(builder.src.join("ferrocene").join("ignored-tests.toml"));
         }
 
         builder.run(&mut cmd);
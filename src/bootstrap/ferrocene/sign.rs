@@ -137,16 +137,26 @@ pub(super) fn for_each_signable_document(
 ];
 
 pub(super) fn document_signatures_cmd(builder: &Builder<'_>, source_dir: &Path) -> Command {
-    let cosign = builder.ensure(CosignBinary);
-    let cache_dir = builder.ensure(CacheSignatureFiles { source_dir: source_dir.into() });
     let tool = builder.tool_exe(Tool::FerroceneDocumentSignatures);
 
     let mut cmd = Command::new(&tool);
-    cmd.env("DOCUMENT_SIGNATURES_COSIGN_BINARY", &cosign);
-    cmd.env("DOCUMENT_SIGNATURES_S3_BUCKET", "ferrocene-document-signatures");
-    cmd.env("DOCUMENT_SIGNATURES_S3_CACHE_DIR", &cache_dir);
-    if let Some(profile) = &builder.config.ferrocene_aws_profile {
-        cmd.env("AWS_PROFILE", profile);
+    if let Some(local_dir) = &builder.config.ferrocene_document_signatures_local_dir {
+        // Signature files stored locally are meant to be used without network access, so avoid
+        // downloading cosign. Only roles signing with local keys can be used in that case.
+        cmd.env("DOCUMENT_SIGNATURES_LOCAL_DIR", local_dir);
+    } else {
+        let cosign = builder.ensure(CosignBinary);
+        let cache_dir = builder.ensure(CacheSignatureFiles { source_dir: source_dir.into() });
+
+        cmd.env("DOCUMENT_SIGNATURES_COSIGN_BINARY", &cosign);
+        cmd.env("DOCUMENT_SIGNATURES_S3_BUCKET", "ferrocene-document-signatures");
+        cmd.env("DOCUMENT_SIGNATURES_S3_CACHE_DIR", &cache_dir);
+        if let Some(profile) = &builder.config.ferrocene_aws_profile {
+            cmd.env("AWS_PROFILE", profile);
+        }
+    }
+    if let Some(private_key) = &builder.config.ferrocene_document_signatures_private_key {
+        cmd.env("DOCUMENT_SIGNATURES_PRIVATE_KEY", private_key);
     }
     cmd
 }
@@ -234,6 +244,11 @@ struct SignatureToml {
             files: HashMap<String, String>,
         }
 
+        // Signature files stored locally don't need to be cached.
+        if let Some(local_dir) = &builder.config.ferrocene_document_signatures_local_dir {
+            return local_dir.clone();
+        }
+
         let cache_dir = builder.out.join("cache").join("ferrocene-document-signatures");
         if builder.config.dry_run() {
             return cache_dir;
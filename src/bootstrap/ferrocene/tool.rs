@@ -1,5 +1,6 @@
 use crate::builder::{Builder, RunConfig, ShouldRun, Step};
 use crate::config::TargetSelection;
+use crate::t;
 use crate::tool::{prepare_tool_cargo, SourceType};
 use crate::util::exe;
 use crate::Mode;
@@ -24,6 +25,10 @@ pub(super) fn update_command(
         if let Some(hash) = builder.cargo_info.sha() {
             cmd.env("SELFTEST_CARGO_HASH", hash);
         }
+        if let Some(keychain) = &builder.config.ferrocene_self_test_keychain {
+            let contents = t!(std::fs::read_to_string(keychain));
+            cmd.env("SELFTEST_KEYCHAIN", contents);
+        }
     }
 }
 
@@ -783,6 +783,11 @@ fn std_features(&self, target: TargetSelection) -> String {
         if self.config.profiler_enabled(target) {
             features.push_str(" profiler");
         }
+        // Bare metal targets built with std (to run the library tests through semihosting) don't
+        // have a libc providing the memory functions.
+        if self.semihosting_tested(target) {
+            features.push_str(" compiler-builtins-mem");
+        }
         features
     }
 
@@ -1360,6 +1365,23 @@ fn remote_tested(&self, target: TargetSelection) -> bool {
         self.qemu_rootfs(target).is_some()
             || target.contains("android")
             || env::var_os("TEST_DEVICE_ADDR").is_some()
+            || env::var_os("TEST_DEVICE_RUNNER").is_some()
+    }
+
+    /// Returns `true` if `remote-test-client` runs the tests in a new QEMU
+    /// instance on the host (through the `qemu-user` or `semihosting`
+    /// runners), rather than sending them to a `remote-test-server`.
+    fn remote_tested_without_server(&self) -> bool {
+        env::var("TEST_DEVICE_RUNNER")
+            .map_or(false, |runner| runner.split_whitespace().next() != Some("remote-test-server"))
+    }
+
+    /// Returns `true` if the tests of this bare metal target are run as bare
+    /// metal images by the `semihosting` runner of `remote-test-client`.
+    fn semihosting_tested(&self, target: TargetSelection) -> bool {
+        target.contains("-none")
+            && env::var("TEST_DEVICE_RUNNER")
+                .map_or(false, |runner| runner.split_whitespace().next() == Some("semihosting"))
     }
 
     /// Returns the root of the "rootfs" image that this target will be using,
@@ -320,6 +320,8 @@ fn non_bare_args(&self, cmd: &mut Command) {
         }
         if let Some(kms_key_arn) = &self.builder.config.ferrocene_tarball_signing_kms_key_arn {
             cmd.arg("--ferrocene-signing-kms-key-arn").arg(kms_key_arn);
+        } else if let Some(local_key) = &self.builder.config.ferrocene_tarball_signing_local_key {
+            cmd.arg("--ferrocene-signing-local-key").arg(local_key);
         }
         for proxied_binary in &self.proxied_binaries {
             cmd.arg("--ferrocene-proxied-binary").arg(proxied_binary);
@@ -62,6 +62,8 @@ impl Step for CrateBootstrap {
 
     fn should_run(run: ShouldRun<'_>) -> ShouldRun<'_> {
         run.path("src/tools/jsondoclint")
+            .path("src/tools/remote-test-client")
+            .path("src/tools/remote-test-server")
             .path("src/tools/suggest-tests")
             .path("src/tools/replace-version-placeholder")
             .alias("tidyselftest")
@@ -2414,7 +2416,15 @@ fn run(self, builder: &Builder<'_>) {
 
         run_cargo_test(
             cargo,
-            if target.contains("ferrocenecoretest") { &["--test-threads", "1"] } else { &[] },
+            if target.contains("ferrocenecoretest") {
+                &["--test-threads", "1"]
+            } else if builder.semihosting_tested(target) {
+                // A failing test aborts the whole run on bare metal, so its output can't be
+                // captured and printed at the end.
+                &["--nocapture"]
+            } else {
+                &[]
+            },
             &self.crates,
             &self.crates[0],
             &*crate_description(&self.crates),
@@ -2613,7 +2623,7 @@ fn should_run(run: ShouldRun<'_>) -> ShouldRun<'_> {
     fn run(self, builder: &Builder<'_>) {
         let compiler = self.compiler;
         let target = self.target;
-        if !builder.remote_tested(target) {
+        if !builder.remote_tested(target) || builder.remote_tested_without_server() {
             return;
         }
 
@@ -3,7 +3,7 @@
 
 use crate::common::{Config, Mode, TestPaths};
 use crate::find_tests_in_dir;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fs::File;
 use std::io::{BufWriter, Write};
@@ -11,6 +11,9 @@
 use std::str::FromStr;
 use std::sync::Arc;
 
+#[cfg(test)]
+mod tests;
+
 const BULK_ANNOTATIONS_FILE_NAME: &str = "ferrocene-annotations";
 
 #[derive(serde::Serialize)]
@@ -29,6 +32,8 @@ struct TestFile {
 struct Annotation {
     id: String,
     file: PathBuf,
+    #[serde(skip)]
+    line: usize,
 }
 
 pub fn maybe_collect_and_exit() {
@@ -40,8 +45,28 @@ pub fn maybe_collect_and_exit() {
     let dest: PathBuf = env("FERROCENE_DEST");
     let config = sample_config();
 
-    let mut collector = Collector::new(Arc::new(config));
+    // Validating the ids is optional, as the id files are only available after building the
+    // documentation. Multiple files can be provided, separated like the PATH variable.
+    let known_ids = std::env::var_os("FERROCENE_TRACEABILITY_IDS").map(|paths| {
+        let mut known_ids = HashSet::new();
+        for path in std::env::split_paths(&paths) {
+            load_known_ids(&path, &mut known_ids);
+        }
+        known_ids
+    });
+
+    let mut collector = Collector::new(Arc::new(config), known_ids);
     collector.collect();
+    for warning in &collector.warnings {
+        eprintln!("warning: {warning}");
+    }
+    for error in &collector.errors {
+        eprintln!("error: {error}");
+    }
+    if !collector.errors.is_empty() {
+        eprintln!("error: found {} invalid annotations", collector.errors.len());
+        std::process::exit(1);
+    }
     let found = collector.write(&dest);
 
     println!("collected {found} tests with annotations");
@@ -51,13 +76,23 @@ pub fn maybe_collect_and_exit() {
 
 struct Collector {
     config: Arc<Config>,
+    known_ids: Option<HashSet<String>>,
     tests: Vec<TestFile>,
     directory_annotations: HashMap<PathBuf, Vec<Annotation>>,
+    warnings: Vec<String>,
+    errors: Vec<String>,
 }
 
 impl Collector {
-    fn new(config: Arc<Config>) -> Self {
-        Self { config, tests: Vec::new(), directory_annotations: HashMap::new() }
+    fn new(config: Arc<Config>, known_ids: Option<HashSet<String>>) -> Self {
+        Self {
+            config,
+            known_ids,
+            tests: Vec::new(),
+            directory_annotations: HashMap::new(),
+            warnings: Vec::new(),
+            errors: Vec::new(),
+        }
     }
 
     fn collect(&mut self) {
@@ -86,7 +121,23 @@ fn collect_test(&mut self, paths: &TestPaths) -> Option<TestFile> {
         let contents =
             std::fs::read_to_string(&path).expect(&format!("failed to read {}", path.display()));
         let mut annotations = self.collect_annotations(&path, &contents);
-        self.append_directory_annotations(paths, &mut annotations);
+
+        if let Some(parent) = paths.file.parent() {
+            let directory_annotations = self.directory_annotations(parent);
+            for annotation in &annotations {
+                if let Some(bulk) = directory_annotations.iter().find(|a| a.id == annotation.id) {
+                    self.warnings.push(format!(
+                        "{}:{}: annotation {} is already applied to the whole directory by {}:{}",
+                        annotation.file.display(),
+                        annotation.line,
+                        annotation.id,
+                        bulk.file.display(),
+                        bulk.line,
+                    ));
+                }
+            }
+            annotations.extend(directory_annotations);
+        }
 
         if annotations.is_empty() {
             None
@@ -95,26 +146,23 @@ fn collect_test(&mut self, paths: &TestPaths) -> Option<TestFile> {
         }
     }
 
-    fn append_directory_annotations(&mut self, paths: &TestPaths, extend: &mut Vec<Annotation>) {
-        if let Some(parent) = paths.file.parent() {
-            if let Some(annotations) = self.directory_annotations.get(parent) {
-                extend.extend_from_slice(&annotations);
-            } else {
-                let file = parent.join(BULK_ANNOTATIONS_FILE_NAME);
-                let mut annotations = match std::fs::read_to_string(&file) {
-                    Ok(contents) => self.collect_annotations(&file, &contents),
-                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
-                    Err(err) => panic!("failed to load {}: {err}", file.display()),
-                };
-                self.directory_annotations.insert(parent.into(), annotations.clone());
-                extend.append(&mut annotations);
-            }
+    fn directory_annotations(&mut self, directory: &Path) -> Vec<Annotation> {
+        if let Some(annotations) = self.directory_annotations.get(directory) {
+            return annotations.clone();
         }
+        let file = directory.join(BULK_ANNOTATIONS_FILE_NAME);
+        let annotations = match std::fs::read_to_string(&file) {
+            Ok(contents) => self.collect_annotations(&file, &contents),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(err) => panic!("failed to load {}: {err}", file.display()),
+        };
+        self.directory_annotations.insert(directory.into(), annotations.clone());
+        annotations
     }
 
-    fn collect_annotations(&self, path: &Path, contents: &str) -> Vec<Annotation> {
-        let mut found = Vec::new();
-        for line in contents.lines() {
+    fn collect_annotations(&mut self, path: &Path, contents: &str) -> Vec<Annotation> {
+        let mut found: Vec<Annotation> = Vec::new();
+        for (idx, line) in contents.lines().enumerate() {
             let prefix = if path.file_name() == Some(OsStr::new("Makefile")) {
                 "# "
             } else if path.extension() == Some(OsStr::new("rs"))
@@ -131,7 +179,10 @@ fn collect_annotations(&self, path: &Path, contents: &str) -> Vec<Annotation> {
             };
             if let Some(remaining) = remaining.strip_prefix("ferrocene-annotations: ") {
                 if !remaining.is_empty() {
-                    found.push(Annotation { id: remaining.into(), file: path.into() });
+                    let annotation =
+                        Annotation { id: remaining.into(), file: path.into(), line: idx + 1 };
+                    self.validate(&annotation, &found);
+                    found.push(annotation);
                 }
             } else if remaining.starts_with("ferrocene-annotation: ") {
                 // Prevent common typos
@@ -144,6 +195,26 @@ fn collect_annotations(&self, path: &Path, contents: &str) -> Vec<Annotation> {
         found
     }
 
+    fn validate(&mut self, annotation: &Annotation, previous: &[Annotation]) {
+        let location = format!("{}:{}", annotation.file.display(), annotation.line);
+
+        if let Some(duplicate) = previous.iter().find(|a| a.id == annotation.id) {
+            self.warnings.push(format!(
+                "{location}: duplicate annotation {} (already present at line {})",
+                annotation.id, duplicate.line
+            ));
+        }
+
+        let Some(known_ids) = &self.known_ids else { return };
+        if !known_ids.contains(&annotation.id) {
+            let mut error = format!("{location}: unknown annotation {}", annotation.id);
+            if let Some(suggestion) = nearest_match(&annotation.id, known_ids) {
+                error.push_str(&format!(" (did you mean {suggestion}?)"));
+            }
+            self.errors.push(error);
+        }
+    }
+
     fn write(self, dest: &Path) -> usize {
         if let Some(parent) = dest.parent() {
             std::fs::create_dir_all(parent).unwrap();
@@ -161,6 +232,74 @@ fn write(self, dest: &Path) -> usize {
     }
 }
 
+/// Load the ids contained in a traceability ids file generated by the documentation, like the
+/// `paragraph-ids.json` of the specification or the `traceability-ids.json` of the user manual.
+fn load_known_ids(path: &Path, known_ids: &mut HashSet<String>) {
+    #[derive(serde::Deserialize)]
+    struct IdsFile {
+        documents: Vec<Document>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Document {
+        #[serde(default)]
+        sections: Vec<Section>,
+        #[serde(default)]
+        options: Vec<Id>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Section {
+        id: String,
+        paragraphs: Vec<Id>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Id {
+        id: String,
+    }
+
+    let contents = std::fs::read(path)
+        .unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()));
+    let file: IdsFile = serde_json::from_slice(&contents).unwrap_or_else(|err| {
+        panic!("failed to parse traceability ids in {}: {err}", path.display())
+    });
+    for document in file.documents {
+        for section in document.sections {
+            known_ids.insert(section.id);
+            known_ids.extend(section.paragraphs.into_iter().map(|p| p.id));
+        }
+        known_ids.extend(document.options.into_iter().map(|o| o.id));
+    }
+}
+
+/// Find the known id closest to the provided one, if it's close enough to likely be a typo.
+fn nearest_match<'a>(id: &str, known_ids: &'a HashSet<String>) -> Option<&'a str> {
+    let max_distance = std::cmp::max(id.len() / 3, 1);
+    known_ids
+        .iter()
+        .map(|known| (edit_distance(id, known), known))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, known)| known.as_str())
+}
+
+/// Levenshtein distance between the two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
 fn sample_config() -> Config {
     Config {
         color: test::ColorConfig::NeverColor,
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+// SPDX-FileCopyrightText: The Ferrocene Developers
+
+use super::*;
+
+fn collector(known_ids: &[&str]) -> Collector {
+    let known_ids = known_ids.iter().map(|id| id.to_string()).collect();
+    Collector::new(Arc::new(Config::default()), Some(known_ids))
+}
+
+#[test]
+fn test_edit_distance() {
+    assert_eq!(0, edit_distance("fls_abc", "fls_abc"));
+    assert_eq!(1, edit_distance("fls_abc", "fls_abd"));
+    assert_eq!(1, edit_distance("fls_abc", "fls_ab"));
+    assert_eq!(2, edit_distance("fls_abc", "fls_bac"));
+    assert_eq!(3, edit_distance("", "abc"));
+}
+
+#[test]
+fn test_nearest_match() {
+    let known_ids = ["fls_4rhjpdu4zfqj", "fls_4rhjpdu4zfqk", "fls_yztwtek0y34v"]
+        .into_iter()
+        .map(String::from)
+        .collect::<HashSet<_>>();
+
+    assert_eq!(Some("fls_4rhjpdu4zfqj"), nearest_match("fls_4rhjpdu4zfq", &known_ids));
+    assert_eq!(Some("fls_yztwtek0y34v"), nearest_match("fls_yztwtekOy34v", &known_ids));
+    assert_eq!(None, nearest_match("fls_completely_different", &known_ids));
+}
+
+#[test]
+fn test_collect_annotations() {
+    let mut collector = collector(&["fls_4rhjpdu4zfqj", "fls_yztwtek0y34v"]);
+    let found = collector.collect_annotations(
+        Path::new("tests/ui/example.rs"),
+        "// ferrocene-annotations: fls_4rhjpdu4zfqj\n\
+         // ferrocene-annotations: fls_yztwtekOy34v\n\
+         fn main() {}\n\
+         // ferrocene-annotations: fls_4rhjpdu4zfqj\n\
+         // ferrocene-annotations: fls_unknown\n",
+    );
+
+    assert_eq!(
+        vec![
+            ("fls_4rhjpdu4zfqj", 1),
+            ("fls_yztwtekOy34v", 2),
+            ("fls_4rhjpdu4zfqj", 4),
+            ("fls_unknown", 5)
+        ],
+        found.iter().map(|a| (a.id.as_str(), a.line)).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![
+            "tests/ui/example.rs:2: unknown annotation fls_yztwtekOy34v \
+             (did you mean fls_yztwtek0y34v?)",
+            "tests/ui/example.rs:5: unknown annotation fls_unknown",
+        ],
+        collector.errors
+    );
+    assert_eq!(
+        vec![
+            "tests/ui/example.rs:4: duplicate annotation fls_4rhjpdu4zfqj \
+             (already present at line 1)"
+        ],
+        collector.warnings
+    );
+}
+
+#[test]
+fn test_collect_annotations_without_known_ids() {
+    let mut collector = Collector::new(Arc::new(Config::default()), None);
+    let found = collector.collect_annotations(
+        Path::new("tests/run-make/example/Makefile"),
+        "# ferrocene-annotations: fls_unknown\n",
+    );
+
+    assert_eq!(1, found.len());
+    assert!(collector.errors.is_empty());
+    assert!(collector.warnings.is_empty());
+}
+
+#[test]
+fn test_collect_test_shadowed_by_directory() {
+    let dir = std::env::temp_dir().join(format!("ferrocene-annotations-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join(BULK_ANNOTATIONS_FILE_NAME),
+        "// ferrocene-annotations: fls_4rhjpdu4zfqj\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("example.rs"),
+        "// ferrocene-annotations: fls_yztwtek0y34v\n\
+         // ferrocene-annotations: fls_4rhjpdu4zfqj\n",
+    )
+    .unwrap();
+
+    let mut collector = collector(&["fls_4rhjpdu4zfqj", "fls_yztwtek0y34v"]);
+    let test = collector
+        .collect_test(&TestPaths { file: dir.join("example.rs"), relative_dir: PathBuf::new() })
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(3, test.annotations.len());
+    assert!(collector.errors.is_empty());
+    assert_eq!(
+        vec![format!(
+            "{}:2: annotation fls_4rhjpdu4zfqj is already applied to the whole directory by {}:1",
+            dir.join("example.rs").display(),
+            dir.join(BULK_ANNOTATIONS_FILE_NAME).display(),
+        )],
+        collector.warnings
+    );
+}
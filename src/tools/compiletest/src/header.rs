@@ -72,6 +72,8 @@ pub struct TestProps {
     pub compile_flags: Vec<String>,
     // Extra flags to pass when the compiled code is run (such as --bench)
     pub run_flags: Option<String>,
+    // Extra flags to pass to `llvm-cov show` in run-coverage tests (such as --show-mcdc)
+    pub llvm_cov_flags: Vec<String>,
     // If present, the name of a file that this test should match when
     // pretty-printed
     pub pp_exact: Option<PathBuf>,
@@ -185,6 +187,7 @@ mod directives {
     pub const REGEX_ERROR_PATTERN: &'static str = "regex-error-pattern";
     pub const COMPILE_FLAGS: &'static str = "compile-flags";
     pub const RUN_FLAGS: &'static str = "run-flags";
+    pub const LLVM_COV_FLAGS: &'static str = "llvm-cov-flags";
     pub const SHOULD_ICE: &'static str = "should-ice";
     pub const BUILD_AUX_DOCS: &'static str = "build-aux-docs";
     pub const FORCE_HOST: &'static str = "force-host";
@@ -227,6 +230,7 @@ pub fn new() -> Self {
             regex_error_patterns: vec![],
             compile_flags: vec![],
             run_flags: None,
+            llvm_cov_flags: vec![],
             pp_exact: None,
             aux_builds: vec![],
             aux_crates: vec![],
@@ -337,6 +341,10 @@ fn load_from(&mut self, testfile: &Path, cfg: Option<&str>, config: &Config) {
 
                 config.set_name_value_directive(ln, RUN_FLAGS, &mut self.run_flags, |r| r);
 
+                if let Some(flags) = config.parse_name_value_directive(ln, LLVM_COV_FLAGS) {
+                    self.llvm_cov_flags.extend(flags.split_whitespace().map(|s| s.to_owned()));
+                }
+
                 if self.pp_exact.is_none() {
                     self.pp_exact = config.parse_pp_exact(ln, testfile);
                 }
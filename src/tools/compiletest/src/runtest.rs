@@ -18,7 +18,6 @@
 use regex::{Captures, Regex};
 use rustfix::{apply_suggestions, get_suggestions_from_json, Filter};
 
-use std::borrow::Cow;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::env;
@@ -533,10 +532,14 @@ fn run_coverage_test(&self) {
             std::fs::remove_file(&profdata_path).unwrap();
         }
 
-        let proc_res = self.exec_compiled_test_general(
-            &[("LLVM_PROFILE_FILE", &profraw_path.to_str().unwrap())],
-            false,
-        );
+        let output_base_dir = self.output_base_dir();
+        let mut env_extra = vec![("LLVM_PROFILE_FILE", profraw_path.to_str().unwrap())];
+        if self.config.remote_test_client.is_some() {
+            // The profraw file is written on the remote device, so ask remote-test-client to
+            // copy it back to the output directory once the test finishes.
+            env_extra.push(("TEST_DEVICE_PULL_DIRS", output_base_dir.to_str().unwrap()));
+        }
+        let proc_res = self.exec_compiled_test_general(&env_extra, false);
         if self.props.failure_status.is_some() {
             self.check_correct_failure_status(&proc_res);
         } else if !proc_res.status.success() {
@@ -565,6 +568,11 @@ fn run_coverage_test(&self) {
         // Run `llvm-cov show` to produce a coverage report in text format.
         let proc_res = self.run_llvm_tool("llvm-cov", |cmd| {
             cmd.args(["show", "--format=text", "--show-line-counts-or-regions"]);
+            // Tests built without branch coverage have no branch regions, so
+            // this only affects the reports of tests that enable it.
+            cmd.arg("--show-branches=count");
+            // Extra flags requested by the test, such as `--show-mcdc`.
+            cmd.args(&self.props.llvm_cov_flags);
 
             cmd.arg("--Xdemangler");
             cmd.arg(self.config.rust_demangler_path.as_ref().unwrap());
@@ -724,7 +732,7 @@ fn normalize_coverage_output(&self, coverage: &str) -> Result<String, String> {
 
     /// Replace line numbers in coverage reports with the placeholder `LL`,
     /// so that the tests are less sensitive to lines being added/removed.
-    fn anonymize_coverage_line_numbers(coverage: &str) -> Cow<'_, str> {
+    fn anonymize_coverage_line_numbers(coverage: &str) -> String {
         // The coverage reporter prints line numbers at the start of a line.
         // They are truncated or left-padded to occupy exactly 5 columns.
         // (`LineNumberColumnWidth` in `SourceCoverageViewText.cpp`.)
@@ -734,7 +742,31 @@ fn anonymize_coverage_line_numbers(coverage: &str) -> Cow<'_, str> {
         // have an additional prefix of `  |` for each nesting level.
         static LINE_NUMBER_RE: Lazy<Regex> =
             Lazy::new(|| Regex::new(r"(?m:^)(?<prefix>(?:  \|)*) *[0-9]+\|").unwrap());
-        LINE_NUMBER_RE.replace_all(coverage, "$prefix   LL|")
+        let coverage = LINE_NUMBER_RE.replace_all(coverage, "$prefix   LL|");
+
+        // Branch regions are listed below their line, with the line and
+        // column where the branch condition starts, e.g. `Branch (12:8)`.
+        static BRANCH_LINE_NUMBER_RE: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"(?m:^)(?<prefix>(?:  \|)+  Branch \()[0-9]+:").unwrap());
+        let coverage = BRANCH_LINE_NUMBER_RE.replace_all(&coverage, "${prefix}LL:");
+
+        // MC/DC decision regions are listed with the line and column where
+        // the decision starts and ends, e.g. `MC/DC Decision Region (12:8) to (12:14)`.
+        static MCDC_DECISION_LINE_NUMBER_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(
+                r"(?m:^)(?<prefix>(?:  \|)+---> MC/DC Decision Region \()[0-9]+:(?<middle>[0-9]+\) to \()[0-9]+:",
+            )
+            .unwrap()
+        });
+        let coverage =
+            MCDC_DECISION_LINE_NUMBER_RE.replace_all(&coverage, "${prefix}LL:${middle}LL:");
+
+        // The conditions of an MC/DC decision are listed with the line and
+        // column where they start, e.g. `Condition C1 --> (12:8)`.
+        static MCDC_CONDITION_LINE_NUMBER_RE: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(r"(?m:^)(?<prefix>(?:  \|)+     Condition C[0-9]+ --> \()[0-9]+:").unwrap()
+        });
+        MCDC_CONDITION_LINE_NUMBER_RE.replace_all(&coverage, "${prefix}LL:").into_owned()
     }
 
     /// Coverage reports can describe multiple source files, separated by
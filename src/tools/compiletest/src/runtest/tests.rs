@@ -48,3 +48,38 @@ fn normalize_platform_differences() {
         r#"println!("test\ntest")"#,
     );
 }
+
+#[test]
+fn anonymize_coverage_line_numbers() {
+    let coverage = "    5|      4|    if return_error {
+  ------------------
+  |  Branch (5:8): [True: 1, False: 3]
+  ------------------
+  |   12|      1|fn foo() {}
+   17|      2|    if a && b {
+  ------------------
+  |---> MC/DC Decision Region (17:8) to (17:14)
+  |
+  |  Number of Conditions: 2
+  |     Condition C1 --> (17:8)
+  |     Condition C2 --> (17:13)
+  ------------------
+";
+    assert_eq!(
+        TestCx::anonymize_coverage_line_numbers(coverage),
+        "   LL|      4|    if return_error {
+  ------------------
+  |  Branch (LL:8): [True: 1, False: 3]
+  ------------------
+  |   LL|      1|fn foo() {}
+   LL|      2|    if a && b {
+  ------------------
+  |---> MC/DC Decision Region (LL:8) to (LL:14)
+  |
+  |  Number of Conditions: 2
+  |     Condition C1 --> (LL:8)
+  |     Condition C2 --> (LL:13)
+  ------------------
+"
+    );
+}
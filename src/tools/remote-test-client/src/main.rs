@@ -1,22 +1,27 @@
 //! This is a small client program intended to pair with `remote-test-server` in
 //! this repository. This client connects to the server over TCP and is used to
-//! push artifacts and run tests on the server instead of locally.
+//! push artifacts and run tests on the server instead of locally, and to pull
+//! back the files produced by the tests.
 //!
 //! Here is also where we bake in the support to spawn the QEMU emulator as
-//! well.
+//! well, and to run the tests directly in QEMU without a server (either with
+//! user space emulation or on bare metal with semihosting).
 
 use std::env;
+use std::ffi::OsStr;
 use std::fs::{self, File};
 use std::io::prelude::*;
 use std::io::{self, BufWriter};
 use std::net::TcpStream;
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Command, ExitStatus, Stdio};
 use std::thread;
 use std::time::Duration;
 
 const REMOTE_ADDR_ENV: &str = "TEST_DEVICE_ADDR";
 const DEFAULT_ADDR: &str = "127.0.0.1:12345";
+const PULL_DIRS_ENV: &str = "TEST_DEVICE_PULL_DIRS";
+const RUNNER_ENV: &str = "TEST_DEVICE_RUNNER";
 
 macro_rules! t {
     ($e:expr) => {
@@ -27,6 +32,44 @@ macro_rules! t {
     };
 }
 
+/// How the tests are executed, configured through the `TEST_DEVICE_RUNNER`
+/// environment variable.
+#[derive(Debug, PartialEq)]
+enum Runner {
+    /// Tests are sent to a `remote-test-server` running on the device.
+    Server,
+    /// Tests are run on the host with the given QEMU user space emulator
+    /// command. Support libraries are loaded from their host paths.
+    QemuUser(Vec<String>),
+    /// Tests are bare metal images run with the given QEMU system emulator
+    /// command, using semihosting for the arguments, output and exit code.
+    Semihosting(Vec<String>),
+}
+
+impl Runner {
+    fn from_env() -> Runner {
+        Runner::parse(env::var(RUNNER_ENV).ok().as_deref())
+    }
+
+    fn parse(runner: Option<&str>) -> Runner {
+        let Some(runner) = runner else {
+            return Runner::Server;
+        };
+        let mut words = runner.split_whitespace().map(|s| s.to_string());
+        let kind = words.next().unwrap_or_default();
+        let command = words.collect::<Vec<_>>();
+        match &kind[..] {
+            "remote-test-server" => Runner::Server,
+            "qemu-user" | "semihosting" if command.is_empty() => {
+                panic!("missing the QEMU command in {}: {}", RUNNER_ENV, runner)
+            }
+            "qemu-user" => Runner::QemuUser(command),
+            "semihosting" => Runner::Semihosting(command),
+            _ => panic!("unknown runner in {}: {}", RUNNER_ENV, runner),
+        }
+    }
+}
+
 fn main() {
     let mut args = env::args().skip(1);
     let next = args.next();
@@ -34,23 +77,36 @@ fn main() {
         return help();
     }
 
-    match &next.unwrap()[..] {
-        "spawn-emulator" => spawn_emulator(
+    let runner = Runner::from_env();
+    match (&next.unwrap()[..], &runner) {
+        ("spawn-emulator", Runner::Server) => spawn_emulator(
             &args.next().unwrap(),
             Path::new(&args.next().unwrap()),
             Path::new(&args.next().unwrap()),
             args.next().map(|s| s.into()),
         ),
-        "push" => push(Path::new(&args.next().unwrap())),
-        "run" => run(
-            args.next().and_then(|count| count.parse().ok()).unwrap(),
+        // A new emulator is started for each test, and the libraries are
+        // loaded from the host, so there is nothing to prepare.
+        ("spawn-emulator" | "push", Runner::QemuUser(_) | Runner::Semihosting(_)) => {}
+        ("push", Runner::Server) => push(Path::new(&args.next().unwrap())),
+        ("run", _) => {
+            let support_lib_count = args.next().and_then(|count| count.parse().ok()).unwrap();
             // the last required parameter must remain the executable
             // path so that the client works as a cargo runner
-            args.next().unwrap(),
-            args.collect(),
-        ),
-        "help" | "-h" | "--help" => help(),
-        cmd => {
+            let exe = args.next().unwrap();
+            let all_args = args.collect();
+            match &runner {
+                Runner::Server => run(support_lib_count, exe, all_args),
+                Runner::QemuUser(command) => {
+                    run_qemu_user(command, support_lib_count, exe, all_args)
+                }
+                Runner::Semihosting(command) => {
+                    run_semihosting(command, support_lib_count, exe, all_args)
+                }
+            }
+        }
+        ("help" | "-h" | "--help", _) => help(),
+        (cmd, _) => {
             println!("unknown command: {}", cmd);
             help();
         }
@@ -277,7 +333,7 @@ fn run(support_lib_count: usize, exe: String, all_args: Vec<String>) {
     // by the client.
     for (k, v) in env::vars() {
         match &k[..] {
-            "PATH" | "LD_LIBRARY_PATH" | "PWD" | "RUST_TEST_TMPDIR" => continue,
+            "PATH" | "LD_LIBRARY_PATH" | "PWD" | "RUST_TEST_TMPDIR" | PULL_DIRS_ENV => continue,
             _ => {}
         }
         t!(client.write_all(k.as_bytes()));
@@ -287,6 +343,18 @@ fn run(support_lib_count: usize, exe: String, all_args: Vec<String>) {
     }
     t!(client.write_all(&[0]));
 
+    // Send over the directories the test will write files into, which the
+    // server will send back once the test finishes.
+    let pull_dirs = match env::var_os(PULL_DIRS_ENV) {
+        Some(dirs) => env::split_paths(&dirs).collect::<Vec<_>>(),
+        None => Vec::new(),
+    };
+    for dir in &pull_dirs {
+        t!(client.write_all(dir.to_str().unwrap().as_bytes()));
+        t!(client.write_all(&[0]));
+    }
+    t!(client.write_all(&[0]));
+
     // Send over support libraries
     for file in support_libs.iter().map(Path::new) {
         send(&file, &mut client);
@@ -329,6 +397,26 @@ fn run(support_lib_count: usize, exe: String, all_args: Vec<String>) {
         }
     }
 
+    // Then receive the files the test wrote in the directories to pull.
+    let mut client = io::BufReader::new(client);
+    for dir in &pull_dirs {
+        loop {
+            let mut name = Vec::new();
+            t!(client.read_until(0, &mut name));
+            if name.len() == 1 {
+                break;
+            }
+            let dst = dir.join(t!(std::str::from_utf8(&name[..name.len() - 1])));
+            if let Some(parent) = dst.parent() {
+                t!(fs::create_dir_all(parent));
+            }
+            let mut len = [0; 4];
+            t!(client.read_exact(&mut len));
+            let amt = u32::from_be_bytes(len) as u64;
+            t!(io::copy(&mut (&mut client).take(amt), &mut t!(File::create(&dst))));
+        }
+    }
+
     // Finally, read out the exit status
     let mut status = [0; 5];
     t!(client.read_exact(&mut status));
@@ -344,6 +432,77 @@ fn run(support_lib_count: usize, exe: String, all_args: Vec<String>) {
     }
 }
 
+fn run_qemu_user(command: &[String], support_lib_count: usize, exe: String, all_args: Vec<String>) {
+    let (support_libs, args) = all_args.split_at(support_lib_count);
+    let library_path = env::var_os("LD_LIBRARY_PATH");
+    let mut cmd = qemu_user_command(command, support_libs, &exe, args, library_path.as_deref());
+    exit_with(t!(cmd.status()));
+}
+
+fn qemu_user_command(
+    command: &[String],
+    support_libs: &[String],
+    exe: &str,
+    args: &[String],
+    library_path: Option<&OsStr>,
+) -> Command {
+    let mut cmd = Command::new(&command[0]);
+    cmd.args(&command[1..]).arg(exe).args(args);
+
+    // The emulated program sees the host filesystem, so make the directories
+    // of the support libraries available to the dynamic linker.
+    let mut paths = support_libs
+        .iter()
+        .filter_map(|lib| Path::new(lib).parent().map(|p| p.to_path_buf()))
+        .collect::<Vec<_>>();
+    if let Some(library_path) = library_path {
+        paths.extend(env::split_paths(library_path));
+    }
+    cmd.env("LD_LIBRARY_PATH", t!(env::join_paths(paths)));
+    cmd
+}
+
+fn run_semihosting(
+    command: &[String],
+    support_lib_count: usize,
+    exe: String,
+    all_args: Vec<String>,
+) {
+    let (support_libs, args) = all_args.split_at(support_lib_count);
+    assert!(support_libs.is_empty(), "bare metal tests can't load support libraries");
+    exit_with(t!(semihosting_command(command, &exe, args).status()));
+}
+
+fn semihosting_command(command: &[String], exe: &str, args: &[String]) -> Command {
+    // The arguments are retrieved by the program through semihosting, with
+    // the program name as the first one. Commas must be doubled to escape
+    // them in the QEMU options.
+    let mut config = String::from("enable=on,target=native");
+    for arg in std::iter::once(exe).chain(args.iter().map(|arg| &arg[..])) {
+        config.push_str(",arg=");
+        config.push_str(&arg.replace(',', ",,"));
+    }
+
+    let mut cmd = Command::new(&command[0]);
+    cmd.args(&command[1..])
+        .arg("-nographic")
+        .arg("-semihosting-config")
+        .arg(config)
+        .arg("-kernel")
+        .arg(exe);
+    cmd
+}
+
+fn exit_with(status: ExitStatus) {
+    match status.code() {
+        Some(code) => std::process::exit(code),
+        None => {
+            println!("died due to signal");
+            std::process::exit(3);
+        }
+    }
+}
+
 fn send(path: &Path, dst: &mut dyn Write) {
     t!(dst.write_all(path.file_name().unwrap().to_str().unwrap().as_bytes()));
     t!(dst.write_all(&[0]));
@@ -384,9 +543,115 @@ fn help() {
 the <file> and any specified support libs are pushed to the target. Finally, the
 <file> is executed in the emulator, preserving the current environment.
 That command's status code is returned.
+
+If {3} is set in the environment, it contains a list of host directories
+the <file> writes files into. They are replaced with directories on the target
+in the arguments and environment of the <file>, and the files written in them
+are copied back to the host directories once the <file> exits.
+
+Selecting how tests are run:
+
+The {4} environment variable selects how the commands above behave:
+
+    remote-test-server              Use a remote-test-server (the default)
+    qemu-user <qemu> [args...]      Run the <file> on the host with the QEMU user
+                                    space emulator, e.g. `qemu-aarch64 -L /rootfs`
+    semihosting <qemu> [args...]    Run the <file> as a bare metal image with the
+                                    QEMU system emulator, e.g. `qemu-system-aarch64
+                                    -machine virt -cpu cortex-a57`, using
+                                    semihosting for its arguments, output and
+                                    exit code
+
+With the last two there is no emulator to spawn and nothing to push, as each
+<file> is run in a new emulator and the host files are used directly.
 ",
         env::args().next().unwrap(),
         REMOTE_ADDR_ENV,
-        DEFAULT_ADDR
+        DEFAULT_ADDR,
+        PULL_DIRS_ENV,
+        RUNNER_ENV,
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{qemu_user_command, semihosting_command, Runner};
+    use std::ffi::OsStr;
+    use std::process::Command;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn command_line(cmd: &Command) -> Vec<&OsStr> {
+        std::iter::once(cmd.get_program()).chain(cmd.get_args()).collect()
+    }
+
+    #[test]
+    fn test_parse_runner() {
+        assert_eq!(Runner::parse(None), Runner::Server);
+        assert_eq!(Runner::parse(Some("remote-test-server")), Runner::Server);
+        assert_eq!(
+            Runner::parse(Some("qemu-user  qemu-aarch64 -L /rootfs")),
+            Runner::QemuUser(strings(&["qemu-aarch64", "-L", "/rootfs"]))
+        );
+        assert_eq!(
+            Runner::parse(Some("semihosting qemu-system-aarch64 -machine virt")),
+            Runner::Semihosting(strings(&["qemu-system-aarch64", "-machine", "virt"]))
+        );
+    }
+
+    #[test]
+    #[should_panic = "missing the QEMU command"]
+    fn test_parse_runner_without_command() {
+        Runner::parse(Some("semihosting"));
+    }
+
+    #[test]
+    #[should_panic = "unknown runner"]
+    fn test_parse_unknown_runner() {
+        Runner::parse(Some("qemu-system qemu-system-aarch64"));
+    }
+
+    #[test]
+    fn test_qemu_user_command() {
+        let cmd = qemu_user_command(
+            &strings(&["qemu-aarch64", "-L", "/rootfs"]),
+            &strings(&["/host/libs/libstd.so"]),
+            "/host/test",
+            &strings(&["--test-threads", "1"]),
+            Some(OsStr::new("/usr/lib")),
+        );
+        assert_eq!(
+            command_line(&cmd),
+            ["qemu-aarch64", "-L", "/rootfs", "/host/test", "--test-threads", "1"]
+        );
+        let envs = cmd.get_envs().collect::<Vec<_>>();
+        assert_eq!(
+            envs,
+            [(OsStr::new("LD_LIBRARY_PATH"), Some(OsStr::new("/host/libs:/usr/lib")))]
+        );
+    }
+
+    #[test]
+    fn test_semihosting_command() {
+        let cmd = semihosting_command(
+            &strings(&["qemu-system-aarch64", "-machine", "virt"]),
+            "/host/test",
+            &strings(&["--skip", "a,b"]),
+        );
+        assert_eq!(
+            command_line(&cmd),
+            [
+                "qemu-system-aarch64",
+                "-machine",
+                "virt",
+                "-nographic",
+                "-semihosting-config",
+                "enable=on,target=native,arg=/host/test,arg=--skip,arg=a,,b",
+                "-kernel",
+                "/host/test",
+            ]
+        );
+    }
+}
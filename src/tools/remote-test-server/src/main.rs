@@ -5,6 +5,7 @@
 //!
 //! 1. Pushing shared libraries to the server
 //! 2. Running tests through the server
+//! 3. Sending the files produced by tests back to the client
 //!
 //! The server supports running tests concurrently and also supports tests
 //! themselves having support libraries. All data over the TCP sockets is in a
@@ -229,6 +230,33 @@ fn handle_run(socket: TcpStream, work: &Path, tmp: &Path, lock: &Mutex<()>, conf
         arg.truncate(0);
     }
 
+    // Then the host directories the test is going to write files into, which
+    // must be sent back to the client once the test finishes. Each of them is
+    // replaced by a fresh directory in our test directory, both in the
+    // arguments and in the env vars.
+    let mut pull_dirs = Vec::new();
+    arg.truncate(0);
+    while t!(reader.read_until(0, &mut arg)) > 1 {
+        let host_dir = t!(str::from_utf8(&arg[..arg.len() - 1])).to_string();
+        let remote_dir = path.join(format!("pull{}", pull_dirs.len()));
+        t!(fs::create_dir(&remote_dir));
+        pull_dirs.push((host_dir, remote_dir));
+        arg.truncate(0);
+    }
+    for (host_dir, remote_dir) in &pull_dirs {
+        let remote_dir = remote_dir.to_str().unwrap();
+        for arg in &mut args {
+            if let Some(replaced) = replace_dir_prefix(arg, host_dir, remote_dir) {
+                *arg = replaced;
+            }
+        }
+        for (_, val) in &mut env {
+            if let Some(replaced) = replace_dir_prefix(val, host_dir, remote_dir) {
+                *val = replaced;
+            }
+        }
+    }
+
     // The section of code from here down to where we drop the lock is going to
     // be a critical section for us. On Linux you can't execute a file which is
     // open somewhere for writing, as you'll receive the error "text file busy".
@@ -307,6 +335,16 @@ fn handle_run(socket: TcpStream, work: &Path, tmp: &Path, lock: &Mutex<()>, conf
         t!(child.wait())
     };
 
+    // Send back the files the test wrote in the directories to pull.
+    {
+        let mut socket = socket.lock().unwrap();
+        for (_, remote_dir) in &pull_dirs {
+            print_verbose(&format!("pull {:#?}", remote_dir), config);
+            send_dir(remote_dir, "", &mut *socket);
+            t!(socket.write_all(&[0]));
+        }
+    }
+
     // Finally send over the exit status.
     let (which, code) = get_status_code(&status);
 
@@ -353,6 +391,36 @@ fn recv<B: BufRead>(dir: &Path, io: &mut B) -> PathBuf {
     dst
 }
 
+/// Rewrites `value` to point inside `remote_dir` if it's `host_dir` itself or a
+/// path inside of it. Other values are left alone, even if they happen to
+/// contain `host_dir` somewhere.
+fn replace_dir_prefix(value: &str, host_dir: &str, remote_dir: &str) -> Option<String> {
+    let rest = value.strip_prefix(host_dir)?;
+    // The client might not use the same path separator as us.
+    if rest.is_empty() || rest.starts_with(['/', '\\']) {
+        Some(format!("{}{}", remote_dir, rest))
+    } else {
+        None
+    }
+}
+
+fn send_dir(dir: &Path, prefix: &str, dst: &mut dyn Write) {
+    for entry in t!(fs::read_dir(dir)) {
+        let entry = t!(entry);
+        let name = format!("{}{}", prefix, entry.file_name().to_str().unwrap());
+        if t!(entry.file_type()).is_dir() {
+            send_dir(&entry.path(), &format!("{}/", name), dst);
+            continue;
+        }
+        t!(dst.write_all(name.as_bytes()));
+        t!(dst.write_all(&[0]));
+        let mut file = t!(File::open(entry.path()));
+        let amt = t!(file.metadata()).len();
+        t!(dst.write_all(&(amt as u32).to_be_bytes()));
+        t!(io::copy(&mut file, dst));
+    }
+}
+
 #[cfg(not(windows))]
 fn set_permissions(path: &Path) {
     t!(fs::set_permissions(&path, Permissions::from_mode(0o755)));
@@ -395,3 +463,23 @@ fn read_u32(r: &mut dyn Read) -> u32 {
     t!(r.read_exact(&mut len));
     u32::from_be_bytes(len)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::replace_dir_prefix;
+
+    #[test]
+    fn test_replace_dir_prefix() {
+        let replace = |value| replace_dir_prefix(value, "/host/out", "/tmp/work/pull0");
+
+        assert_eq!(replace("/host/out").as_deref(), Some("/tmp/work/pull0"));
+        assert_eq!(replace("/host/out/").as_deref(), Some("/tmp/work/pull0/"));
+        assert_eq!(replace("/host/out/a.profraw").as_deref(), Some("/tmp/work/pull0/a.profraw"));
+        assert_eq!(replace("/host/out\\a.profraw").as_deref(), Some("/tmp/work/pull0\\a.profraw"));
+
+        assert_eq!(replace("/host/output/a.profraw"), None);
+        assert_eq!(replace("/other/host/out/a.profraw"), None);
+        assert_eq!(replace("--out=/host/out"), None);
+        assert_eq!(replace("/host"), None);
+    }
+}
@@ -0,0 +1,9 @@
+// compile-flags: --edition=2021 -Zunstable-options -Cinstrument-coverage=branch
+
+fn main() {
+    let cond = std::env::args().len() == 1;
+    if cond {
+        println!("true");
+    }
+    println!("done");
+}
@@ -0,0 +1,31 @@
+// compile-flags: --edition=2021 -Zunstable-options -Cinstrument-coverage=mcdc
+// min-llvm-version: 18
+
+fn mcdc_and(a: bool, b: bool) {
+    if a && b {
+        say("a and b");
+    }
+}
+
+fn mcdc_or_not(a: bool, b: bool) {
+    if !a || b {
+        say("not a or b");
+    }
+}
+
+fn mcdc_nested(a: bool, b: bool, c: bool) {
+    if a && (b || !c) {
+        say("a and (b or not c)");
+    }
+}
+
+#[inline(never)]
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+fn main() {
+    mcdc_and(true, false);
+    mcdc_or_not(true, false);
+    mcdc_nested(true, false, true);
+}
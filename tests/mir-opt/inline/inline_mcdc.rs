@@ -0,0 +1,26 @@
+// EMIT_MIR_FOR_EACH_PANIC_STRATEGY
+// Checks that functions instrumented for MC/DC coverage are not inlined, because the condition
+// bitmap of their decisions lives in their own stack frame.
+
+// ignore-aarch64-unknown-ferrocenecoretest - instrument-coverage is not supported
+// unit-test: Inline
+// needs-profiler-support
+// ignore-windows
+// compile-flags: -Zunstable-options -Cinstrument-coverage=mcdc
+// compile-flags: --remap-path-prefix={{src-base}}=/the/src
+
+// EMIT_MIR inline_mcdc.main.Inline.diff
+fn main() {
+    with_decision(true, false);
+    without_decision(true, false);
+}
+
+#[inline(always)]
+fn with_decision(a: bool, b: bool) -> u32 {
+    if a && b { 1 } else { 0 }
+}
+
+#[inline(always)]
+fn without_decision(a: bool, b: bool) -> bool {
+    a & b
+}
@@ -0,0 +1,49 @@
+#![allow(unused_assignments)]
+// compile-flags: -Zunstable-options -Cinstrument-coverage=branch
+
+fn call(return_error: bool) -> Result<(), ()> {
+    if return_error {
+        Err(())
+    } else {
+        Ok(())
+    }
+}
+
+fn try_call(return_error: bool) -> Result<(), ()> {
+    call(return_error)?;
+    Ok(())
+}
+
+fn classify(n: u32) -> u32 {
+    match n {
+        0 => 10,
+        1 => 20,
+        _ => 30,
+    }
+}
+
+fn count_down(mut n: u32) -> u32 {
+    let mut steps = 0;
+    while n > 0 {
+        n -= 1;
+        steps += 1;
+    }
+    steps
+}
+
+fn main() {
+    let _ = try_call(false);
+    let _ = try_call(true);
+    let _ = try_call(false);
+    let _ = try_call(false);
+
+    classify(0);
+    classify(1);
+    classify(2);
+    classify(3);
+
+    count_down(0);
+    count_down(1);
+    count_down(2);
+    count_down(3);
+}
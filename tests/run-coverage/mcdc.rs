@@ -0,0 +1,52 @@
+// compile-flags: -Zunstable-options -Cinstrument-coverage=mcdc
+// llvm-cov-flags: --show-mcdc
+// min-llvm-version: 18
+
+fn and(a: bool, b: bool) {
+    if a && b {
+        say("a and b");
+    } else {
+        say("not both");
+    }
+}
+
+fn or_not(a: bool, b: bool) {
+    if !a || b {
+        say("not a, or b");
+    }
+}
+
+fn nested(a: bool, b: bool, c: bool) {
+    if a && (b || !c) {
+        say("a, and b or not c");
+    }
+}
+
+fn not_and(a: bool, b: bool) {
+    if !(a && b) {
+        say("not both a and b");
+    }
+}
+
+fn say(message: &str) {
+    core::hint::black_box(message);
+}
+
+fn main() {
+    and(true, true);
+    and(true, false);
+    and(false, true);
+
+    or_not(true, true);
+    or_not(false, false);
+    or_not(true, false);
+
+    nested(true, false, false);
+    nested(true, true, true);
+    nested(false, true, true);
+    nested(true, false, true);
+
+    not_and(true, true);
+    not_and(false, true);
+    not_and(true, false);
+}
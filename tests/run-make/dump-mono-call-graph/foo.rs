@@ -0,0 +1,29 @@
+pub fn bar() -> fn() {
+    baz(1u32);
+    let _guard = Guard;
+    let speaker: &dyn Speak = &Dog;
+    speaker.speak();
+    qux
+}
+
+fn baz<T>(_: T) {}
+
+fn qux() {
+    std::process::abort();
+}
+
+struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {}
+}
+
+trait Speak {
+    fn speak(&self);
+}
+
+struct Dog;
+
+impl Speak for Dog {
+    fn speak(&self) {}
+}
@@ -0,0 +1,35 @@
+fn get(v: &[u32], i: usize) -> u32 {
+    v[i]
+}
+
+fn first(v: &[u32]) -> u32 {
+    *v.first().unwrap()
+}
+
+fn parse(s: &str) -> u32 {
+    s.parse::<u32>().unwrap()
+}
+
+fn expect_first(v: &[u32]) -> u32 {
+    *v.first().expect("no first element")
+}
+
+fn tail(v: &[u32], start: usize, end: usize) -> &[u32] {
+    &v[start..end]
+}
+
+fn no_panics() -> u32 {
+    42
+}
+
+fn calls_upstream() -> u32 {
+    std::process::id()
+}
+
+fn main() {
+    let v = [1, 2, 3];
+    let i = std::env::args().count();
+    println!("{} {} {}", get(&v, i), first(&v), no_panics());
+    println!("{} {} {:?}", parse("4"), expect_first(&v), tail(&v, 1, i));
+    println!("{}", calls_upstream());
+}
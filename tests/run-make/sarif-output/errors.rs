@@ -0,0 +1,4 @@
+pub fn mismatched_types() -> u32 {
+    let number: u32 = "one";
+    number
+}
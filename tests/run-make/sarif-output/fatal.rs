@@ -0,0 +1 @@
+pub const UNTERMINATED: char = '!
@@ -0,0 +1,84 @@
+#!/usr/bin/env python
+
+import sys
+import json
+
+
+def check(errors, condition, message):
+    if not condition:
+        errors.append(message)
+
+
+if len(sys.argv) != 3:
+    print("Expected the kind of crate and the SARIF log to check!")
+    sys.exit(1)
+kind = sys.argv[1]
+
+# A single log is printed, even if there were diagnostics before the session was created.
+with open(sys.argv[2]) as f:
+    lines = f.read().splitlines()
+if len(lines) != 1:
+    print("Expected a single line with the SARIF log, found {}:".format(len(lines)))
+    print("\n".join(lines))
+    sys.exit(1)
+log = json.loads(lines[0])
+
+errors = []
+check(errors, log["version"] == "2.1.0", "Unexpected version: `{}`".format(log["version"]))
+check(errors, len(log["runs"]) == 1, "Expected a single run: `{}`".format(log["runs"]))
+run = log["runs"][0]
+driver = run["tool"]["driver"]
+check(errors, driver["name"] == "rustc", "Unexpected tool: `{}`".format(driver["name"]))
+rules = {rule["id"]: rule for rule in driver["rules"]}
+results = run["results"]
+check(errors, len(results) == 1, "Expected a single result: `{}`".format(results))
+result = results[0]
+region = result["locations"][0]["physicalLocation"]["region"]
+uri = result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"]
+check(errors, uri == kind + ".rs", "Unexpected location: `{}`".format(uri))
+
+if kind == "warnings":
+    check(errors, result["level"] == "warning", "Unexpected level: `{}`".format(result["level"]))
+    check(errors, result["ruleId"] == "unused_variables",
+          "Unexpected rule: `{}`".format(result["ruleId"]))
+    check(errors, "unused_variables" in rules, "Missing rule: `{}`".format(sorted(rules)))
+    check(errors, "on by default" in result["message"]["text"],
+          "Missing note in the message: `{}`".format(result["message"]["text"]))
+    check(errors, (region["startLine"], region["startColumn"], region["endColumn"]) == (2, 9, 15),
+          "Unexpected region: `{}`".format(region))
+    insertions = [replacement["insertedContent"]["text"]
+                  for fix in result["fixes"]
+                  for change in fix["artifactChanges"]
+                  for replacement in change["replacements"]]
+    check(errors, insertions == ["_unused"], "Unexpected fixes: `{}`".format(result["fixes"]))
+
+elif kind == "errors":
+    check(errors, result["level"] == "error", "Unexpected level: `{}`".format(result["level"]))
+    check(errors, result["ruleId"] == "E0308", "Unexpected rule: `{}`".format(result["ruleId"]))
+    rule = rules.get("E0308", {})
+    check(errors, rule.get("helpUri") == "https://doc.rust-lang.org/error_codes/E0308.html",
+          "Unexpected help of E0308: `{}`".format(rule.get("helpUri")))
+    check(errors, "shortDescription" in rule, "Missing explanation of E0308: `{}`".format(rule))
+    check(errors, (region["startLine"], region["startColumn"], region["endColumn"]) == (2, 23, 28),
+          "Unexpected region: `{}`".format(region))
+    related = [location["message"]["text"] for location in result.get("relatedLocations", [])]
+    check(errors, "expected due to this" in related,
+          "Unexpected related locations: `{}`".format(related))
+
+elif kind == "fatal":
+    # Fatal errors abort the compilation right away, and are still part of the log.
+    check(errors, result["level"] == "error", "Unexpected level: `{}`".format(result["level"]))
+    check(errors, result["ruleId"] == "E0762", "Unexpected rule: `{}`".format(result["ruleId"]))
+    check(errors, result["message"]["text"] == "unterminated character literal",
+          "Unexpected message: `{}`".format(result["message"]["text"]))
+    check(errors, (region["startLine"], region["startColumn"]) == (1, 32),
+          "Unexpected region: `{}`".format(region))
+
+else:
+    errors.append("Unknown kind of crate: `{}`".format(kind))
+
+for err in errors:
+    print("=> {}".format(err))
+if len(errors) != 0:
+    print(json.dumps(log, indent=4))
+    sys.exit(1)
@@ -0,0 +1,3 @@
+pub fn unused_variable() {
+    let unused = 1;
+}
@@ -0,0 +1,64 @@
+use std::hint::black_box;
+
+static TABLE: [u8; 4] = [1, 2, 3, 4];
+
+trait Answer {
+    fn answer(&self) -> u8;
+}
+
+struct FortyTwo;
+
+impl Answer for FortyTwo {
+    fn answer(&self) -> u8 {
+        42
+    }
+}
+
+#[inline(never)]
+fn small() -> u8 {
+    black_box([1u8; 16])[0]
+}
+
+#[inline(never)]
+fn large() -> u8 {
+    black_box([2u8; 4096])[0]
+}
+
+#[inline(never)]
+fn countdown(n: u32) -> u32 {
+    if n == 0 {
+        0
+    } else {
+        countdown(black_box(n - 1)) + 1
+    }
+}
+
+#[inline(never)]
+fn call_fn_ptr(f: fn() -> u8) -> u8 {
+    f()
+}
+
+#[inline(never)]
+fn call_dyn(answer: &dyn Answer) -> u8 {
+    answer.answer()
+}
+
+fn main() {
+    black_box(small());
+    black_box(large());
+}
+
+#[no_mangle]
+pub extern "C" fn recursive_entry() -> u32 {
+    countdown(black_box(10))
+}
+
+#[no_mangle]
+pub extern "C" fn indirect_entry() -> u8 {
+    call_fn_ptr(black_box(small)) + call_dyn(black_box(&FortyTwo))
+}
+
+#[no_mangle]
+pub extern "C" fn bounds_checked_entry(index: usize) -> u8 {
+    TABLE[index]
+}
@@ -0,0 +1,75 @@
+#!/usr/bin/env python
+
+import sys
+import json
+
+
+def check(errors, condition, message):
+    if not condition:
+        errors.append(message)
+
+
+def unbounded_reasons(entry_point):
+    return {(item["reason"], item["function"]) for item in entry_point["unbounded"]}
+
+
+if len(sys.argv) != 2:
+    print("Expected the stack usage report to check!")
+    sys.exit(1)
+
+with open(sys.argv[1]) as f:
+    report = json.load(f)
+
+errors = []
+check(errors, report["crate"] == "main", "Unexpected crate name: `{}`".format(report["crate"]))
+entry_points = {entry_point["name"]: entry_point for entry_point in report["entry_points"]}
+check(errors,
+      sorted(entry_points) == ["bounds_checked_entry", "indirect_entry", "main", "recursive_entry"],
+      "Unexpected entry points: `{}`".format(sorted(entry_points)))
+
+# `main` only makes direct calls, and its worst call chain goes through the large frame.
+main = entry_points["main"]
+check(errors, main["kind"] == "main", "Unexpected kind of `main`: `{}`".format(main["kind"]))
+check(errors, main["bounded"], "Expected `main` to be bounded: `{}`".format(main["unbounded"]))
+chain = [function["name"] for function in main["worst_call_chain"]]
+check(errors, chain == ["main", "large"],
+      "Unexpected worst call chain of `main`: `{}`".format(chain))
+large = main["worst_call_chain"][-1]["frame_bytes"]
+check(errors, large is not None and large >= 4096,
+      "Unexpected frame of `large`: `{}`".format(large))
+check(errors, main["stack_bytes"] >= large,
+      "Unexpected stack usage of `main`: `{}`".format(main["stack_bytes"]))
+
+recursive = entry_points["recursive_entry"]
+check(errors, recursive["kind"] == "exported",
+      "Unexpected kind of `recursive_entry`: `{}`".format(recursive["kind"]))
+check(errors, not recursive["bounded"], "Expected `recursive_entry` to be unbounded")
+check(errors, unbounded_reasons(recursive) == {("recursion", "countdown")},
+      "Unexpected unbounded calls of `recursive_entry`: `{}`".format(recursive["unbounded"]))
+check(errors, any(item["detail"] == "countdown -> countdown" for item in recursive["unbounded"]),
+      "Expected the recursion of `countdown`: `{}`".format(recursive["unbounded"]))
+
+indirect = entry_points["indirect_entry"]
+check(errors, not indirect["bounded"], "Expected `indirect_entry` to be unbounded")
+check(errors,
+      unbounded_reasons(indirect) == {("fn_ptr_call", "call_fn_ptr"), ("dyn_call", "call_dyn")},
+      "Unexpected unbounded calls of `indirect_entry`: `{}`".format(indirect["unbounded"]))
+
+# The panic of the bounds check is in `core`, whose frame sizes are not known.
+bounds_checked = entry_points["bounds_checked_entry"]
+check(errors, not bounds_checked["bounded"], "Expected `bounds_checked_entry` to be unbounded")
+check(errors,
+      [(item["reason"], item["function"], item["detail"]) for item in bounds_checked["unbounded"]]
+      == [("external_call", "bounds_checked_entry", "core::panicking::panic_bounds_check")],
+      "Unexpected unbounded calls of `bounds_checked_entry`: `{}`".format(
+          bounds_checked["unbounded"]))
+
+# No function of the crate has dynamically sized stack allocations.
+for entry_point in entry_points.values():
+    check(errors, all(item["reason"] != "unknown_frame_size" for item in entry_point["unbounded"]),
+          "Unexpected unknown frame sizes: `{}`".format(entry_point["unbounded"]))
+
+for err in errors:
+    print("=> {}".format(err))
+if len(errors) != 0:
+    sys.exit(1)
@@ -0,0 +1,52 @@
+static mut COUNTER: u32 = 0;
+
+union IntOrFloat {
+    i: u32,
+    f: f32,
+}
+
+pub struct Wrapper(u32);
+
+impl Wrapper {
+    pub unsafe fn get_unchecked(ptr: *const Wrapper) -> u32 {
+        (*ptr).0
+    }
+}
+
+pub unsafe trait Zeroable {}
+
+unsafe impl Zeroable for Wrapper {}
+
+extern "C" {
+    fn abs(x: i32) -> i32;
+}
+
+pub fn read(ptr: *const u32) -> u32 {
+    unsafe { *ptr }
+}
+
+pub fn bump() -> u32 {
+    unsafe {
+        COUNTER += 1;
+        COUNTER
+    }
+}
+
+pub fn bits(f: f32) -> u32 {
+    let value = IntOrFloat { f };
+    unsafe { value.i }
+}
+
+pub fn get(wrapper: &Wrapper) -> u32 {
+    unsafe { Wrapper::get_unchecked(wrapper) }
+}
+
+pub fn absolute(x: i32) -> i32 {
+    unsafe { abs(x) }
+}
+
+#[no_mangle]
+pub extern "C" fn exported() {}
+
+#[link_section = ".data.foo"]
+pub static SECTIONED: u32 = 0;
@@ -0,0 +1,12 @@
+// Checks that MC/DC coverage is rejected when generating code with an LLVM older than 18, which
+// lacks the MC/DC intrinsics and coverage mapping regions.
+//
+// build-fail
+// ignore-llvm-version: 18 - 99
+// compile-flags: --crate-type=lib -Zno-profiler-runtime
+// compile-flags: -Zunstable-options -Cinstrument-coverage=mcdc
+// normalize-stderr-test: "LLVM \d+\.\d+\.\d+" -> "LLVM $$VERSION"
+
+pub fn two_conditions(a: bool, b: bool) -> bool {
+    if a && b { true } else { false }
+}
@@ -0,0 +1,26 @@
+// Checks that decisions with more conditions than MC/DC coverage supports are only instrumented
+// for branch coverage, and that decisions with a `let` in them are not instrumented for MC/DC at
+// all, so they don't warn about their number of conditions.
+//
+// check-pass
+// compile-flags: --crate-type=lib -Zno-profiler-runtime
+// compile-flags: -Zunstable-options -Cinstrument-coverage=mcdc
+
+#![feature(let_chains)]
+
+pub fn six_conditions(a: bool, b: bool, c: bool, d: bool, e: bool, f: bool) -> bool {
+    if a && b && c && d && e && f { true } else { false }
+}
+
+pub fn seven_conditions(a: bool, b: bool, c: bool, d: bool, e: bool, f: bool, g: bool) -> bool {
+    if a && b && (c || d) && !(e && f) && g {
+        //~^ WARN this decision has 7 conditions, but MC/DC coverage supports at most 6
+        true
+    } else {
+        false
+    }
+}
+
+pub fn let_chain(x: Option<bool>, a: bool, b: bool, c: bool, d: bool, e: bool, f: bool) -> bool {
+    if let Some(g) = x && a && b && c && d && e && f && g { true } else { false }
+}